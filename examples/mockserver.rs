@@ -0,0 +1,407 @@
+//! A stand-in for a Skedda venue, serving the recorded responses in `tests/fixtures/http` and
+//! the venue data in `tests/fixtures/webs`, so every part of syres can be tried without a
+//! membership. Bookings made and cancelled through it change the venue data it serves until
+//! it's stopped.
+//!
+//! ```sh
+//! cargo run --example mockserver -- --today --auth
+//! ```
+//!
+//! and point a venue at it with `base_url = "http://127.0.0.1:8080"`.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use clap::Parser;
+use serde_json::{Value, json};
+use time::{
+    Date, OffsetDateTime, PrimitiveDateTime, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
+
+/// The form token on the recorded booking page, which requests must send back.
+const TOKEN: &str = "CfDJ8Example-Token_0123456789abcdef";
+/// The cookie a login sets when `--auth` is on.
+const SESSION: &str = "mock-session";
+/// Booking times in the venue data, e.g. "2024-05-01T09:00:00".
+const TIME: &[BorrowedFormatItem] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]");
+
+#[derive(Debug, Parser)]
+#[command(about = "Serve a fake Skedda venue for local development")]
+struct Args {
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+    /// The `/webs` venue data to serve.
+    #[arg(long, default_value = "tests/fixtures/webs/small_venue.json")]
+    webs: PathBuf,
+    /// Make clients log in first, as Skedda does when a session runs out.
+    #[arg(long)]
+    auth: bool,
+    #[arg(long, default_value = "member@example.com")]
+    email: String,
+    #[arg(long, default_value = "password")]
+    password: String,
+    /// Move the venue's bookings to today, keeping their times.
+    #[arg(long)]
+    today: bool,
+}
+
+/// What the server knows between requests.
+struct State {
+    args: Args,
+    webs: Value,
+    next_id: u64,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn logged_in(&self) -> bool {
+        self.header("Cookie").is_some_and(|cookies| {
+            cookies
+                .split(';')
+                .any(|c| c.trim() == format!("{SESSION}=1"))
+        })
+    }
+
+    /// A form field from an `application/x-www-form-urlencoded` body.
+    fn field(&self, name: &str) -> Option<String> {
+        String::from_utf8_lossy(&self.body)
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| decode(value))
+    }
+}
+
+struct Response {
+    status: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    /// A response recorded in `tests/fixtures/http`.
+    fn fixture(name: &str) -> Self {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/http")
+            .join(format!("{name}.http"));
+        let text = fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()));
+        let (head, body) = text.split_once("\n\n").unwrap_or((&text, ""));
+        let mut lines = head.lines();
+        let status = lines.next().unwrap_or_default().to_string();
+        let headers = lines
+            .filter_map(|line| line.split_once(": "))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Self {
+            status,
+            headers,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn json(status: &str, body: &Value) -> Self {
+        Self {
+            status: format!("HTTP/1.1 {status}"),
+            headers: vec![(
+                "Content-Type".to_string(),
+                "application/json; charset=utf-8".to_string(),
+            )],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    fn redirect(location: &str) -> Self {
+        Self {
+            status: "HTTP/1.1 302 Found".to_string(),
+            headers: vec![("Location".to_string(), location.to_string())],
+            body: Vec::new(),
+        }
+    }
+
+    fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn write(&self, stream: &mut TcpStream) -> std::io::Result<()> {
+        let mut head = format!("{}\r\n", self.status);
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        head.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        ));
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(&self.body)
+    }
+}
+
+fn main() -> color_eyre::Result<()> {
+    let args = Args::parse();
+    let mut webs: Value = serde_json::from_slice(
+        &fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join(&args.webs))
+            .or_else(|_| fs::read(&args.webs))?,
+    )?;
+    if args.today {
+        let today = OffsetDateTime::now_local()
+            .unwrap_or_else(|_| OffsetDateTime::now_utc())
+            .date();
+        move_bookings_to(&mut webs, today);
+    }
+    let next_id = bookings(&mut webs)
+        .iter()
+        .filter_map(|booking| number(&booking["id"]))
+        .max()
+        .unwrap_or(9000)
+        + 1;
+
+    let listener = TcpListener::bind(("127.0.0.1", args.port))?;
+    eprintln!(
+        "serving {} at http://127.0.0.1:{}{}",
+        args.webs.display(),
+        args.port,
+        if args.auth {
+            format!(", log in as {} / {}", args.email, args.password)
+        } else {
+            String::new()
+        }
+    );
+    let state = Arc::new(Mutex::new(State {
+        args,
+        webs,
+        next_id,
+    }));
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else { continue };
+        let state = Arc::clone(&state);
+        thread::spawn(move || {
+            let Some(request) = read_request(&mut stream) else {
+                return;
+            };
+            let response = respond(
+                &mut state.lock().unwrap_or_else(PoisonError::into_inner),
+                &request,
+            );
+            eprintln!("{} {} -> {}", request.method, request.path, response.status);
+            let _ = response.write(&mut stream);
+        });
+    }
+    Ok(())
+}
+
+fn respond(state: &mut State, request: &Request) -> Response {
+    let path = request.path.split('?').next().unwrap_or_default();
+    let logged_out = state.args.auth && !request.logged_in();
+    let token_ok = request.header("X-Skedda-RequestVerificationToken") == Some(TOKEN);
+    match (request.method.as_str(), path) {
+        ("GET" | "HEAD", "/") => Response::redirect("/booking"),
+        ("GET", "/account/login") => Response::fixture("login_page"),
+        ("POST", "/account/login") => {
+            let accepted = request.field("Email").as_deref() == Some(state.args.email.as_str())
+                && request.field("Password").as_deref() == Some(state.args.password.as_str());
+            if accepted {
+                Response::redirect("/booking").with_header(
+                    "Set-Cookie",
+                    &format!("{SESSION}=1; path=/; max-age=86400; httponly"),
+                )
+            } else {
+                Response::fixture("login_rejected")
+            }
+        }
+        (_, "/booking") if logged_out => Response::fixture("booking_logged_out"),
+        (_, "/webs" | "/bookings") if logged_out => Response::fixture("webs_logged_out"),
+        (_, path) if path.starts_with("/bookings/") && logged_out => {
+            Response::fixture("webs_logged_out")
+        }
+        ("GET", "/booking") => Response::fixture("booking_page"),
+        (_, "/webs" | "/bookings") if !token_ok => Response::fixture("webs_forbidden"),
+        (_, path) if path.starts_with("/bookings/") && !token_ok => {
+            Response::fixture("webs_forbidden")
+        }
+        ("GET", "/webs") => Response::json("200 OK", &state.webs),
+        ("POST", "/bookings") => book(state, &request.body),
+        ("DELETE", path) if path.starts_with("/bookings/") => {
+            let id = path["/bookings/".len()..].parse().ok();
+            let bookings = bookings(&mut state.webs);
+            let before = bookings.len();
+            bookings.retain(|booking| number(&booking["id"]) != id);
+            if bookings.len() < before {
+                Response::fixture("cancel_booking")
+            } else {
+                Response::fixture("cancel_booking_not_found")
+            }
+        }
+        _ => Response::json("404 Not Found", &json!({ "message": "Not found" })),
+    }
+}
+
+/// Makes the booking in `body` unless it overlaps another booking of one of its spaces.
+fn book(state: &mut State, body: &[u8]) -> Response {
+    let Ok(request) = serde_json::from_slice::<Value>(body) else {
+        return Response::json(
+            "400 Bad Request",
+            &json!({ "message": "Unreadable booking" }),
+        );
+    };
+    let request = &request["booking"];
+    let spaces: Vec<u64> = request["spaces"]
+        .as_array()
+        .map(|spaces| spaces.iter().filter_map(number).collect())
+        .unwrap_or_default();
+    let (Some(start), Some(end)) = (request["start"].as_str(), request["end"].as_str()) else {
+        return Response::json("400 Bad Request", &json!({ "message": "No start or end" }));
+    };
+    // The times are all written the same way, so they compare as text.
+    let taken = bookings(&mut state.webs).iter().any(|booking| {
+        booking["start"].as_str() < Some(end)
+            && booking["end"].as_str() > Some(start)
+            && booking["spaces"].as_array().is_some_and(|booked| {
+                booked
+                    .iter()
+                    .filter_map(number)
+                    .any(|id| spaces.contains(&id))
+            })
+    });
+    if taken {
+        return Response::fixture("booking_rejected");
+    }
+
+    let booking = json!({
+        "id": state.next_id,
+        "venueuser": number(&request["venueuser"]).or_else(|| state.webs["venueuser"]["id"].as_u64()),
+        "title": request["title"],
+        "spaces": spaces,
+        "start": start,
+        "end": end,
+    });
+    state.next_id += 1;
+    bookings(&mut state.webs).push(booking.clone());
+    let mut created = booking;
+    created["venue"] = number(&request["venue"]).into();
+    created["type"] = 1.into();
+    Response::json("200 OK", &json!({ "booking": created }))
+}
+
+/// An id, which Skedda writes as a number and syres sends as a string.
+fn number(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|text| text.parse().ok()))
+}
+
+/// The venue data's bookings, which a booking or cancellation changes.
+fn bookings(webs: &mut Value) -> &mut Vec<Value> {
+    if !webs["bookings"].is_array() {
+        webs["bookings"] = json!([]);
+    }
+    webs["bookings"].as_array_mut().unwrap()
+}
+
+/// Moves every booking by the same number of days, so the first day with bookings is `day`.
+fn move_bookings_to(webs: &mut Value, day: Date) {
+    let read = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|text| PrimitiveDateTime::parse(text, TIME).ok())
+    };
+    let bookings = bookings(webs);
+    let Some(first) = bookings
+        .iter()
+        .filter_map(|booking| read(&booking["start"]))
+        .map(|start| start.date())
+        .min()
+    else {
+        return;
+    };
+    let shift = day - first;
+    for booking in bookings {
+        for field in ["start", "end"] {
+            if let Some(moved) =
+                read(&booking[field]).and_then(|time| (time + shift).format(TIME).ok())
+            {
+                booking[field] = moved.into();
+            }
+        }
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Undoes form encoding: `+` for spaces and `%XX` escapes.
+fn decode(value: &str) -> String {
+    let bytes = value.replace('+', " ").into_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../src/page.rs"]
+mod page;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(html) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Some(token) = page::csrf_token(html) {
+        // Whatever the page, a token has to be safe to send back as a header.
+        assert!(!token.is_empty() && token.len() <= 1024);
+        assert!(token.bytes().all(|byte| byte.is_ascii_graphic()));
+    }
+    if let Some(title) = page::title(html) {
+        assert!(!title.is_empty() && title.trim() == title);
+    }
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/models.rs"]
+mod models;
+
+fuzz_target!(|data: &[u8]| {
+    // Malformed venue data must fail to parse, never panic, in either model.
+    let full = serde_json::from_slice::<models::WebsResponse>(data);
+    let summary = models::WebsSummary::parse(data);
+    if let (Ok(full), Ok(summary)) = (full, summary) {
+        assert_eq!(full.spaces.len(), summary.spaces.len());
+        assert_eq!(full.bookings.len(), summary.bookings.len());
+    }
+});
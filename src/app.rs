@@ -1,73 +1,755 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    ops::RangeInclusive,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 
+use crate::availability::{self, Alternatives, LocationAvailability};
+use crate::batch::{Batch, ItemState};
+use crate::calendar::{self, CalendarDay};
+use crate::clock;
+use crate::config::{
+    Config, GoNowConfig, GridConfig, HooksConfig, NotifyConfig, NotifyEvent, Preset,
+    QuickBookConfig, StartupView, VenueConfig,
+};
+use crate::draft::{BookingDraft, DraftStore};
 use crate::event::{AppEvent, Event, EventHandler};
+use crate::grid::{self, TimeGrid};
+use crate::hooks::{self, HookBooking, HookEvent};
+use crate::input::TextInput;
+use crate::list::{self, ListMove};
+use crate::logging;
+use crate::models::{Booking, BookingRequest, CreatedBooking, WebsResponse, WebsSummary};
+use crate::navigation::{self, Effect, Nav};
+use crate::notify::{self, Message};
+use crate::openings::{self, Opening};
+use crate::paths::Paths;
+use crate::plan::{self, PlanDay, PlanEntry};
+use crate::polling::{self, Backoff};
+use crate::receipt::{self, Receipt};
+use crate::scheduler::Scheduler;
+use crate::session::SessionHealth;
+use crate::skedda::{MyBooking, Role, Skedda, SkeddaError, SpaceDetails};
+use crate::toast::{Severity, Toasts};
 use crate::ui;
 
+use anyhow::Context as _;
+use image::DynamicImage;
 use ratatui::{
     DefaultTerminal,
     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    style::Stylize,
     widgets::{ListItem, ListState},
 };
+use time::{
+    Date, PrimitiveDateTime, Time, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
+use tracing::{debug, error, info, warn};
 
-const LOCATIONS: [&str; 12] = [
-    "Adair Park",
-    "Avondale Estates",
-    "Buckhead",
-    "Cabbagetown",
-    "Chamblee",
-    "Decatur",
-    "Downtown",
-    "Midtown",
-    "Old Fourth Ward",
-    "Roswell",
-    "Virginia-Highland",
-    "Westside",
-];
+/// Space photos are scaled down to fit within this many pixels once decoded.
+const THUMBNAIL_SIZE: (u32, u32) = (160, 120);
 
-#[derive(Debug, Clone, PartialEq)]
+/// How many venues the overview fetches at once.
+const OVERVIEW_CONCURRENCY: usize = 4;
+
+/// How many space photos are downloaded at once when a location opens, leaving the venue's
+/// other request slots for what you ask for.
+const PREFETCH_CONCURRENCY: usize = 2;
+
+/// How long to wait before retrying a failed session refresh.
+const SESSION_REFRESH_RETRY: Duration = Duration::from_secs(60);
+
+/// How often to reopen the connection to the highlighted venue while the location list is up;
+/// shorter than the HTTP client's 90 second idle timeout so it's still open when used.
+const WARM_UP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long to wait before reloading the booking form when Skedda is down and doesn't say.
+const OUTAGE_RETRY: Duration = Duration::from_secs(30);
+
+/// The longest wait between reloads while Skedda stays down.
+const OUTAGE_RETRY_MAX: Duration = Duration::from_secs(5 * 60);
+
+/// How often to check for sessions to refresh, connections to warm up and outages to retry.
+const HOUSEKEEPING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the open booking form re-fetches bookings so other people's show up.
+const BOOKING_DATA_REFRESH: Duration = Duration::from_secs(2 * 60);
+
+/// How soon after one `r` another is ignored, so holding the key can't hammer Skedda.
+const MANUAL_REFRESH_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// How often each venue's own bookings are re-fetched for the countdown in the status bar.
+const MY_BOOKINGS_REFRESH: Duration = Duration::from_secs(10 * 60);
+
+/// The most days one booking can be repeated over with `+`.
+const MAX_REPEAT_DAYS: u8 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ViewState {
     LocationSelection,
     BookingForm,
     Confirmation,
+    VenuePicker,
+    /// Everyone's bookings for the highlighted space, for admins.
+    SpaceBookings,
+    /// Which locations have free spaces right now.
+    Overview,
+    /// My bookings and the gaps between them over the next week.
+    WeekPlan,
+    /// My upcoming bookings, soonest first.
+    MyBookings,
+    /// A month of bookings of the highlighted space.
+    MonthCalendar,
+    /// A week of bookings of the highlighted space, slot by slot.
+    Calendar,
+    /// How each booking of a batch went, with ways to retry, keep or roll back.
+    BatchResults,
+}
+
+/// A field of the booking form's date and time row, focused with Tab and changed with ←/→.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormField {
+    Date,
+    Start,
+    Duration,
+    /// The booking's title, typed rather than stepped.
+    Title,
+}
+
+impl FormField {
+    const ALL: [FormField; 4] = [
+        FormField::Date,
+        FormField::Start,
+        FormField::Duration,
+        FormField::Title,
+    ];
+
+    /// The field after `field` going `step` fields along, or `None` past either end, where
+    /// the grid has the keys again.
+    fn step(field: Option<Self>, step: isize) -> Option<Self> {
+        let position = match field {
+            Some(field) => Self::ALL.iter().position(|&other| other == field)? as isize + 1,
+            None => 0,
+        };
+        let next = (position + step).rem_euclid(Self::ALL.len() as isize + 1);
+        next.checked_sub(1)
+            .and_then(|index| Self::ALL.get(index as usize).copied())
+    }
+}
+
+/// What the booking form's text prompt is asking for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prompt {
+    StartTime,
+    /// The member an admin is booking for.
+    BookFor,
+    /// The optional message sent with a bulk cancellation.
+    CancelMessage,
+}
+
+/// A row in the location list: either a venue heading or one of its locations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocationRow {
+    Venue(usize),
+    Location { venue: usize, name: String },
+}
+
+/// A row in the booking form's space list: a heading for one type of space, or a space by
+/// its index in the location's spaces.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpaceRow {
+    Group {
+        kind: String,
+        spaces: usize,
+        collapsed: bool,
+    },
+    Space(usize),
+}
+
+/// Spaces without a type are grouped under this heading.
+const OTHER_SPACES: &str = "Other";
+
+/// Space photos waiting to be downloaded, by venue and URL, shared with the workers
+/// downloading them.
+#[derive(Debug, Default)]
+struct ImageQueue {
+    waiting: VecDeque<(usize, VenueConfig, String)>,
+    /// Workers still taking from `waiting`; each stops once it finds it empty.
+    workers: usize,
+}
+
+/// Session state tracked separately for each venue.
+#[derive(Debug, Default)]
+pub struct VenueSession {
+    /// Whether the saved session has been read yet; it's read in the background at startup.
+    pub loaded: bool,
+    pub expires_at: Option<SystemTime>,
+    pub refreshing: bool,
+    /// When a failed refresh may be tried again.
+    pub refresh_retry_at: Option<Instant>,
+    /// When a connection to the venue was last opened ahead of use.
+    pub warmed_at: Option<Instant>,
+    /// When the session was last used, by a keep-alive or a fetch, while keep-alives are on.
+    pub kept_alive_at: Option<Instant>,
 }
 
 /// Application.
 #[derive(Debug)]
 pub struct App<'a> {
     pub running: bool,
+    pub venues: Vec<VenueConfig>,
+    pub sessions: Vec<VenueSession>,
+    /// The venue the location list is limited to, or `None` to show every venue.
+    pub active_venue: Option<usize>,
+    pub location_rows: Vec<LocationRow>,
     pub locations: Vec<ListItem<'a>>,
     pub events: EventHandler,
     pub list_state: ListState,
+    pub venue_list_state: ListState,
     pub current_view: ViewState,
+    pub selected_venue: Option<usize>,
     pub selected_location: Option<String>,
-    pub test_http: bool,
-    pub venue_space_ids: HashMap<String, String>,
+    pub venue_spaces: HashMap<String, SpaceDetails>,
     pub selected_location_space_ids: Vec<String>,
+    /// Whether the booking form's data is still being fetched.
+    pub loading_spaces: bool,
+    /// Whether the fetch under way is a background refresh of a form that's already filled.
+    refreshing_spaces: bool,
+    /// The highlighted space, by index in `selected_location_space_ids`; `None` while a
+    /// group heading is highlighted.
+    pub space_list_state: ListState,
+    /// The group heading under the cursor in the space list, if it's on one.
+    pub highlighted_group: Option<String>,
+    /// The space list's scroll position, as drawn.
+    pub space_rows_state: ListState,
+    /// Types of space whose group is folded away in the space list.
+    pub collapsed_groups: HashSet<String>,
+    /// Decoded space photos by URL; `None` while loading or when the download failed.
+    pub space_images: HashMap<String, Option<DynamicImage>>,
+    image_queue: Arc<Mutex<ImageQueue>>,
+    /// When the next day at the selected location comes within its advance-booking window.
+    pub opening: Option<Opening>,
+    pub grid_config: GridConfig,
+    /// Today's slots for the selected location, the columns of the availability grid.
+    pub grid: TimeGrid,
+    /// The slot under the cursor in the availability grid.
+    pub grid_slot: usize,
+    /// Where a Shift+arrow selection started; the selection runs from here to `grid_slot`.
+    pub grid_anchor: Option<usize>,
+    pub bookings: Vec<Booking>,
+    /// The step booking times must fall on, from the venue's settings.
+    pub booking_increment: Option<u32>,
+    /// A typed start time that falls inside the slot under the cursor rather than on its edge.
+    pub booking_start: Option<PrimitiveDateTime>,
+    /// A preset's end time that falls inside the last selected slot.
+    pub booking_end: Option<PrimitiveDateTime>,
+    /// A window the user was warned overlaps one of their own bookings; pressing Enter on
+    /// it again books it anyway.
+    overlap_confirmed: Option<(String, PrimitiveDateTime, PrimitiveDateTime)>,
+    /// The booking form presets for each venue.
+    pub presets: Vec<Vec<Preset>>,
+    /// The named windows from config, to name a selection that matches one.
+    windows: Vec<(String, Time, Time)>,
+    /// The prompt being typed into and its text, while one is open.
+    pub prompt: Option<(Prompt, String)>,
+    /// The member an admin is booking on behalf of, or `None` to book for themselves.
+    pub booking_for: Option<String>,
+    /// Where half-made bookings are saved; `None` when there's no data directory.
+    drafts: Option<DraftStore>,
+    /// The form as it was when it loaded, so only changes to it are saved as a draft.
+    initial_draft: Option<BookingDraft>,
+    /// The draft last written for the open form.
+    saved_draft: Option<BookingDraft>,
+    /// A draft saved earlier for the open location, offered when the form loads.
+    pub offered_draft: Option<BookingDraft>,
+    /// Whether the space bookings view covers the week rather than the day.
+    pub space_bookings_week: bool,
+    pub space_bookings_state: ListState,
+    /// Availability now at each location, grouped by venue in config order.
+    pub overview: Vec<LocationAvailability>,
+    /// Venues the overview is still fetching.
+    pub overview_pending: HashSet<usize>,
+    pub overview_errors: HashMap<usize, String>,
+    pub overview_state: ListState,
+    /// Whether the overview is answering "where can I go right now?".
+    pub overview_go_now: bool,
+    /// Where else a space like the one the booking form can't book is free, when it was the
+    /// last of its kind at its location.
+    pub alternatives: Option<Alternatives>,
+    pub go_now: GoNowConfig,
+    /// Each venue's role for the logged-in account, once the venue data has said.
+    pub roles: Vec<Option<Role>>,
+    /// The logged-in account's own bookings at each venue, soonest first.
+    pub my_bookings: Vec<Vec<MyBooking>>,
+    /// For each venue that couldn't be reached, when the data shown of it in its place was
+    /// fetched. Booking and cancelling there are off until it's back.
+    pub offline: Vec<Option<SystemTime>>,
+    /// The day, from today, the planning board's cursor is on.
+    pub plan_day: usize,
+    pub plan_state: ListState,
+    pub my_bookings_state: ListState,
+    /// One of my bookings waiting on a yes or no before it's cancelled, with its venue.
+    pub cancel_pending: Option<(usize, MyBooking)>,
+    /// Whether the request inspector is open over the current view.
+    pub show_inspector: bool,
+    /// How far back the log viewer is scrolled, in lines from the newest, when it's open over
+    /// the current view.
+    pub log_viewer: Option<usize>,
+    /// Skedda's id for the venue being booked, from its data.
+    venue_id: Option<String>,
+    /// Names of the venue's members by id, for booking on someone's behalf.
+    members: HashMap<String, String>,
+    /// The booking on its way to Skedda.
+    submitting: Option<BookingRequest>,
+    /// A booking that failed without a clear answer; the venue is checked for it before it's
+    /// sent again.
+    unconfirmed_booking: Option<BookingRequest>,
+    /// What Skedda said about the booking on the confirmation screen.
+    pub created_booking: Option<CreatedBooking>,
+    /// What `b` books.
+    quick_book: QuickBookConfig,
+    /// Where to say a booking was made.
+    notify: NotifyConfig,
+    /// What to run after a booking is made or cancelled.
+    hooks: HooksConfig,
+    /// How long a venue's session may sit unused before it's touched to keep it going.
+    keep_alive: Option<Duration>,
+    /// The quick-book location by venue index, from config.
+    quick_book_location: Option<(usize, String)>,
+    /// Whether the booking form being loaded should be filled in for a quick booking.
+    quick_booking: bool,
+    /// The day under the month or week view's cursor.
+    pub calendar_date: Date,
+    /// The slot under the week view's cursor, as in the booking form's grid.
+    pub week_slot: usize,
+    /// Where the booking form's cursor starts once it loads, when opened from a gap in the
+    /// planning board.
+    planned_start: Option<PrimitiveDateTime>,
+    /// The booking form's date and time field taking ←/→, or its title taking typing, if one
+    /// is.
+    pub form_field: Option<FormField>,
+    /// The title the next booking from the form is made with, if it isn't blank.
+    pub booking_title: TextInput,
+    /// Spaces marked to book along with the highlighted one, by id.
+    pub marked_spaces: Vec<String>,
+    /// How many days, from the grid's, the selected window is booked on.
+    pub repeat_days: u8,
+    /// Bookings made together from the booking form, while they're sent and until they're
+    /// kept or rolled back.
+    pub batch: Option<Batch>,
+    pub batch_state: ListState,
+    /// Bookings marked for bulk cancellation, by id.
+    pub marked_bookings: HashSet<String>,
+    /// The outcome of cancelling each booking, by id.
+    pub cancellations: HashMap<String, Result<(), String>>,
+    pub cancelling: bool,
+    /// A one-line message for the booking form, e.g. how a typed time was adjusted.
+    pub notice: Option<String>,
+    /// Why the booking form is empty when Skedda is down, and when it will try again.
+    pub outage: Option<(String, Instant)>,
+    /// Spaces out reloads while Skedda stays down.
+    outage_backoff: Backoff,
+    /// When a key was last pressed; background work that only helps an active user stops
+    /// when there's been none since it last ran.
+    last_input: Instant,
+    pub scheduler: Scheduler,
+    /// Feedback on background work, shown in a corner for a few seconds.
+    pub toasts: Toasts,
+    /// How many rows the current view's list shows at once, for paging; set when drawn.
+    pub list_page: usize,
+    /// When the booking form's data, the overview and my bookings last arrived, for the age
+    /// shown by each view's title.
+    booking_data_at: Option<Instant>,
+    overview_at: Option<Instant>,
+    my_bookings_at: Option<Instant>,
+    /// When `r` last refreshed something.
+    manual_refresh_at: Option<Instant>,
+    /// Whether the last key was a lone `g`, the first half of `gg`.
+    pending_g: bool,
 }
 
 impl Default for App<'_> {
     fn default() -> Self {
-        Self {
+        Self::new(&Config::default())
+    }
+}
+
+impl App<'_> {
+    /// Constructs a new instance of [`App`].
+    pub fn new(config: &Config) -> Self {
+        let venues = config.venues.clone();
+        let sessions = venues.iter().map(|_| VenueSession::default()).collect();
+
+        let mut app = Self {
             running: true,
-            locations: LOCATIONS
-                .iter()
-                .map(|&s| ListItem::new(s.to_string()))
-                .collect(),
+            venues,
+            sessions,
+            active_venue: None,
+            location_rows: Vec::new(),
+            locations: Vec::new(),
             events: EventHandler::new(),
-            list_state: ListState::default().with_selected(Some(0)),
+            list_state: ListState::default(),
+            venue_list_state: ListState::default().with_selected(Some(0)),
             current_view: ViewState::LocationSelection,
+            selected_venue: None,
             selected_location: None,
-            test_http: false,
             selected_location_space_ids: Vec::new(),
-            venue_space_ids: HashMap::new(),
+            loading_spaces: false,
+            refreshing_spaces: false,
+            venue_spaces: HashMap::new(),
+            space_list_state: ListState::default(),
+            highlighted_group: None,
+            space_rows_state: ListState::default(),
+            collapsed_groups: HashSet::new(),
+            space_images: HashMap::new(),
+            image_queue: Arc::default(),
+            opening: None,
+            grid_config: config.grid.clone(),
+            grid: TimeGrid::new(clock::now().date(), &config.grid, None),
+            grid_slot: 0,
+            grid_anchor: None,
+            bookings: Vec::new(),
+            booking_increment: None,
+            booking_start: None,
+            booking_end: None,
+            overlap_confirmed: None,
+            presets: config
+                .venues
+                .iter()
+                .map(|venue| config.presets_for(venue))
+                .collect(),
+            windows: config
+                .windows
+                .iter()
+                .filter_map(|(name, window)| {
+                    let (start, end) = window.times()?;
+                    Some((name.clone(), start, end))
+                })
+                .collect(),
+            prompt: None,
+            booking_for: None,
+            drafts: Paths::resolve().map(|paths| DraftStore::new(paths.drafts_file())),
+            initial_draft: None,
+            saved_draft: None,
+            offered_draft: None,
+            space_bookings_week: false,
+            space_bookings_state: ListState::default(),
+            overview: Vec::new(),
+            overview_pending: HashSet::new(),
+            overview_errors: HashMap::new(),
+            overview_state: ListState::default(),
+            overview_go_now: false,
+            alternatives: None,
+            go_now: config.go_now.clone(),
+            roles: vec![None; config.venues.len()],
+            my_bookings: vec![Vec::new(); config.venues.len()],
+            offline: vec![None; config.venues.len()],
+            plan_day: 0,
+            calendar_date: clock::now().date(),
+            week_slot: 0,
+            venue_id: None,
+            members: HashMap::new(),
+            submitting: None,
+            unconfirmed_booking: None,
+            created_booking: None,
+            quick_book: config.quick_book.clone(),
+            notify: config.notify.clone(),
+            hooks: config.hooks.clone(),
+            keep_alive: config
+                .network
+                .session_keep_alive_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            quick_book_location: config
+                .quick_book
+                .location
+                .as_deref()
+                .or(config.startup.location.as_deref())
+                .and_then(|location| config.find_location(location))
+                .map(|(venue, location)| (venue, location.to_string())),
+            quick_booking: false,
+            plan_state: ListState::default(),
+            my_bookings_state: ListState::default(),
+            cancel_pending: None,
+            show_inspector: false,
+            log_viewer: None,
+            planned_start: None,
+            form_field: None,
+            booking_title: TextInput::default(),
+            marked_spaces: Vec::new(),
+            repeat_days: 1,
+            batch: None,
+            batch_state: ListState::default(),
+            marked_bookings: HashSet::new(),
+            cancellations: HashMap::new(),
+            cancelling: false,
+            notice: None,
+            outage: None,
+            outage_backoff: Backoff::new(OUTAGE_RETRY, OUTAGE_RETRY_MAX),
+            last_input: Instant::now(),
+            scheduler: Scheduler::default(),
+            toasts: Toasts::default(),
+            booking_data_at: None,
+            overview_at: None,
+            my_bookings_at: None,
+            manual_refresh_at: None,
+            list_page: 1,
+            pending_g: false,
+        };
+        app.rebuild_locations();
+        app.load_sessions();
+        app.discover_locations();
+        app.open_startup_view(config);
+        app
+    }
+
+    /// Moves on from the location list to the view config asks to start on.
+    fn open_startup_view(&mut self, config: &Config) {
+        match config.startup.view {
+            StartupView::Locations => {}
+            StartupView::MyBookings => self.navigate(Nav::Plan),
+            StartupView::Overview => self.navigate(Nav::Overview { go_now: false }),
+            StartupView::Venues => self.navigate(Nav::Venues),
+            StartupView::Availability => {
+                let Some((venue, location)) = config
+                    .startup
+                    .location
+                    .as_deref()
+                    .and_then(|location| config.find_location(location))
+                else {
+                    return;
+                };
+                if let Some(row) = self.location_row(venue, location) {
+                    // Selected in the list too, so going back lands on it.
+                    self.list_state.select(Some(row));
+                    self.navigate(Nav::Open);
+                }
+            }
         }
     }
-}
 
-impl App<'_> {
-    /// Constructs a new instance of [`App`].
-    pub fn new() -> Self {
-        Self::default()
+    /// Reads each venue's saved session in the background, so the UI is up before the session
+    /// files are decrypted.
+    fn load_sessions(&self) {
+        let sender = self.events.sender();
+        let venues = self.venues.clone();
+        thread::spawn(move || {
+            for (venue, config) in venues.iter().enumerate() {
+                let expires_at = Skedda::new(config).session_expiry();
+                if sender
+                    .send(Event::App(AppEvent::SessionLoaded(venue, expires_at)))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Fetches the space tags of each venue that offers its own as locations, one after
+    /// another, keeping the configured list for any that can't be reached.
+    fn discover_locations(&self) {
+        let sender = self.events.sender();
+        let venues: Vec<(usize, VenueConfig)> = self
+            .venues
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(_, config)| config.discover_locations)
+            .collect();
+        if venues.is_empty() {
+            return;
+        }
+        thread::spawn(move || {
+            for (venue, config) in venues {
+                let names = Skedda::new(&config)
+                    .get_cached_booking_data(false)
+                    .map(|(webs_data, _)| Skedda::location_names(&webs_data));
+                match names {
+                    Ok(names) => {
+                        if sender
+                            .send(Event::App(AppEvent::LocationsLoaded(venue, names)))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(err) => warn!(venue = config.name, "failed to fetch locations: {err}"),
+                }
+            }
+        });
+    }
+
+    /// Lists the locations of the active venue, or of every venue grouped under headings.
+    fn rebuild_locations(&mut self) {
+        let venues: Vec<usize> = match self.active_venue {
+            Some(venue) => vec![venue],
+            None => (0..self.venues.len()).collect(),
+        };
+        let grouped = venues.len() > 1;
+
+        self.location_rows.clear();
+        for venue in venues {
+            if grouped {
+                self.location_rows.push(LocationRow::Venue(venue));
+            }
+            self.location_rows
+                .extend(
+                    self.venues[venue]
+                        .locations
+                        .iter()
+                        .map(|name| LocationRow::Location {
+                            venue,
+                            name: name.clone(),
+                        }),
+                );
+        }
+        self.locations = self
+            .location_rows
+            .iter()
+            .map(|row| match row {
+                LocationRow::Venue(venue) => ListItem::new(self.venues[*venue].name.clone()).bold(),
+                LocationRow::Location { name, .. } if grouped => ListItem::new(format!("  {name}")),
+                LocationRow::Location { name, .. } => ListItem::new(name.clone()),
+            })
+            .collect();
+        self.list_state.select(
+            self.location_rows
+                .iter()
+                .position(|row| matches!(row, LocationRow::Location { .. })),
+        );
+    }
+
+    /// Limits the app to one venue (or all of them) and fetches the venue's data in the
+    /// background.
+    fn switch_venue(&mut self, venue: Option<usize>) {
+        self.active_venue = venue;
+        self.selected_venue = None;
+        self.selected_location = None;
+        self.venue_spaces.clear();
+        self.selected_location_space_ids.clear();
+        self.space_images.clear();
+        self.image_queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .waiting
+            .clear();
+        self.opening = None;
+        self.rebuild_locations();
+
+        let Some(venue) = venue else {
+            return;
+        };
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let skedda = Skedda::new(&config);
+            let event = match skedda.get_cached_booking_data(false) {
+                Ok((webs_data, _)) => AppEvent::VenueDataLoaded(
+                    venue,
+                    Skedda::space_details(&webs_data),
+                    skedda.session_expiry(),
+                ),
+                Err(err) => AppEvent::VenueDataFailed(venue, err.to_string()),
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    /// Fetches every venue in view, a few at a time, and works out which of their locations
+    /// have free spaces right now, or with `go_now`, which have a space of the configured type
+    /// free for the next few hours.
+    fn load_overview(&mut self, go_now: bool) {
+        let venues: Vec<usize> = match self.active_venue {
+            Some(venue) => vec![venue],
+            None => (0..self.venues.len()).collect(),
+        };
+        self.overview.clear();
+        self.overview_errors.clear();
+        self.overview_state.select(None);
+        self.overview_pending = venues.iter().copied().collect();
+        self.overview_go_now = go_now;
+
+        let space_type = self.go_now.space_type.clone();
+        let hours = self.go_now.hours;
+        self.survey_locations(
+            venues,
+            move |venue, config, summary| {
+                let now = clock::now();
+                if go_now {
+                    availability::overview(
+                        venue,
+                        &config.locations,
+                        summary,
+                        (now, now + time::Duration::hours(i64::from(hours))),
+                        |label| availability::is_kind(label, &space_type),
+                    )
+                } else {
+                    availability::overview(
+                        venue,
+                        &config.locations,
+                        summary,
+                        (now, now + time::Duration::MINUTE),
+                        availability::is_meeting_room,
+                    )
+                }
+            },
+            |venue, result| match result {
+                Ok(locations) => AppEvent::OverviewLoaded(venue, locations),
+                Err(err) => AppEvent::OverviewFailed(venue, err),
+            },
+        );
+    }
+
+    /// Fetches each of `venues`, a few at a time, and sends back what `survey` makes of its
+    /// locations as the event `done` builds.
+    fn survey_locations(
+        &self,
+        venues: Vec<usize>,
+        survey: impl Fn(usize, &VenueConfig, &WebsSummary) -> Vec<LocationAvailability>
+        + Send
+        + Sync
+        + 'static,
+        done: impl Fn(usize, Result<Vec<LocationAvailability>, String>) -> AppEvent
+        + Send
+        + Sync
+        + 'static,
+    ) {
+        let workers = OVERVIEW_CONCURRENCY.min(venues.len());
+        let queue: VecDeque<(usize, VenueConfig)> = venues
+            .into_iter()
+            .map(|venue| (venue, self.venues[venue].clone()))
+            .collect();
+        let queue = Arc::new(Mutex::new(queue));
+        let (survey, done) = (Arc::new(survey), Arc::new(done));
+        for _ in 0..workers {
+            let queue = Arc::clone(&queue);
+            let (survey, done) = (Arc::clone(&survey), Arc::clone(&done));
+            let sender = self.events.sender();
+            thread::spawn(move || {
+                loop {
+                    let next = queue.lock().ok().and_then(|mut queue| queue.pop_front());
+                    let Some((venue, config)) = next else {
+                        break;
+                    };
+                    let body = Skedda::new(&config).get_booking_data_body();
+                    let locations = body.and_then(|body| {
+                        let summary = WebsSummary::parse(&body).map_err(|err| {
+                            SkeddaError::Parse(format!(
+                                "Failed to parse JSON response from /webs: {err}"
+                            ))
+                        })?;
+                        Ok(survey(venue, &config, &summary))
+                    });
+                    let event = done(venue, locations.map_err(|err| err.to_string()));
+                    if sender.send(Event::App(event)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
     }
 
     /// Run the application's main loop.
@@ -82,12 +764,227 @@ impl App<'_> {
     pub fn handle_events(&mut self) -> color_eyre::Result<()> {
         match self.events.next()? {
             Event::Tick => self.tick(),
-            Event::Crossterm(event) => match event {
-                crossterm::event::Event::Key(key_event) => self.handle_key_event(key_event)?,
-                _ => {}
-            },
+            Event::Crossterm(event) => {
+                if let crossterm::event::Event::Key(key_event) = event {
+                    self.handle_key_event(key_event)?
+                }
+            }
             Event::App(app_event) => match app_event {
-                AppEvent::Quit => self.quit()
+                AppEvent::Quit => self.quit(),
+                AppEvent::SessionLoaded(venue, expires_at) => {
+                    if let Some(session) = self.sessions.get_mut(venue) {
+                        session.loaded = true;
+                        session.expires_at = session.expires_at.or(expires_at);
+                    }
+                    if expires_at.is_some() && polling::try_spend() {
+                        self.load_my_bookings(venue);
+                    }
+                }
+                AppEvent::SessionRefreshed(venue, expires_at) => {
+                    if let Some(session) = self.sessions.get_mut(venue) {
+                        session.refreshing = false;
+                        session.expires_at = expires_at;
+                    }
+                    let message = self.for_venue(venue, "Session refreshed");
+                    self.toasts.push(Severity::Success, message);
+                }
+                AppEvent::SessionRefreshFailed(venue, err) => {
+                    if let Some(session) = self.sessions.get_mut(venue) {
+                        session.refreshing = false;
+                    }
+                    warn!("failed to refresh session: {err}");
+                    let message = self.for_venue(venue, "Couldn't refresh the session");
+                    self.toasts.push(Severity::Warning, message);
+                }
+                AppEvent::SessionKeptAlive(venue, expires_at) => {
+                    if let Some(session) = self.sessions.get_mut(venue) {
+                        session.expires_at = expires_at.or(session.expires_at);
+                    }
+                }
+                AppEvent::KeepAliveFailed(venue, err) => {
+                    warn!("failed to keep the session alive: {err}");
+                    let message = self.for_venue(venue, "Couldn't keep the session alive");
+                    self.toasts.push(Severity::Warning, message);
+                }
+                AppEvent::VenueDataLoaded(venue, spaces, expires_at) => {
+                    self.sessions[venue].expires_at = expires_at;
+                    if self.active_venue == Some(venue) && self.selected_location.is_none() {
+                        self.venue_spaces = spaces;
+                    }
+                }
+                AppEvent::BookingDataLoaded(venue, webs_data, expires_at, fetched_at) => {
+                    self.venue_reached(venue, fetched_at);
+                    self.booking_data_loaded(venue, &webs_data, expires_at, fetched_at)
+                }
+                AppEvent::RequestFailed(venue, err) => self.request_failed(venue, err),
+                AppEvent::MyBookingsLoaded(venue, bookings, fetched_at) => {
+                    self.venue_reached(venue, fetched_at);
+                    if let Some(mine) = self.my_bookings.get_mut(venue) {
+                        *mine = bookings;
+                        let age = SystemTime::now()
+                            .duration_since(fetched_at)
+                            .unwrap_or_default();
+                        self.my_bookings_at = Some(Instant::now() - age);
+                    }
+                }
+                AppEvent::Offline(venue, fetched_at) => {
+                    if self.offline[venue].is_none() {
+                        self.toasts.push(
+                            Severity::Warning,
+                            self.for_venue(
+                                venue,
+                                "Can't reach the venue; showing the data last fetched",
+                            ),
+                        );
+                    }
+                    self.offline[venue] = Some(fetched_at);
+                }
+                // A photo from a venue left since it was asked for is dropped, and fetched
+                // again should that venue come back.
+                AppEvent::SpaceImageLoaded(venue, url, _) if self.selected_venue != Some(venue) => {
+                    self.forget_space_image(&url);
+                }
+                AppEvent::SpaceImageLoaded(_, url, image) => {
+                    self.space_images.insert(url, Some(image));
+                }
+                AppEvent::SpaceImageFailed(venue, url, _) if self.selected_venue != Some(venue) => {
+                    self.forget_space_image(&url);
+                }
+                AppEvent::SpaceImageFailed(_, url, err) => {
+                    warn!(%url, "failed to load space image: {err}");
+                }
+                AppEvent::OverviewLoaded(venue, locations) => {
+                    self.overview_pending.remove(&venue);
+                    self.overview_at = Some(Instant::now());
+                    self.overview.retain(|location| location.venue != venue);
+                    self.overview.extend(locations);
+                    if self.overview_go_now {
+                        self.overview.retain(|location| location.free_matching > 0);
+                        let preferred = &self.go_now.preferred_locations;
+                        self.overview.sort_by_key(|location| {
+                            let rank = preferred
+                                .iter()
+                                .position(|name| name.eq_ignore_ascii_case(&location.location))
+                                .unwrap_or(preferred.len());
+                            (rank, location.venue)
+                        });
+                    } else {
+                        self.overview.sort_by_key(|location| location.venue);
+                    }
+                    if self.overview_state.selected().is_none() && !self.overview.is_empty() {
+                        self.overview_state.select(Some(0));
+                    }
+                }
+                AppEvent::OverviewFailed(venue, err) => {
+                    self.overview_pending.remove(&venue);
+                    self.overview_at = Some(Instant::now());
+                    error!(
+                        venue = self.venues[venue].name,
+                        "failed to load overview: {err}"
+                    );
+                    self.overview_errors.insert(venue, err);
+                }
+                AppEvent::AlternativesLoaded(venue, window, result) => {
+                    let Some(alternatives) = self
+                        .alternatives
+                        .as_mut()
+                        .filter(|alternatives| alternatives.window == window)
+                    else {
+                        return Ok(());
+                    };
+                    let locations = result.unwrap_or_else(|err| {
+                        warn!(
+                            venue = self.venues[venue].name,
+                            "failed to look for alternatives: {err}"
+                        );
+                        Vec::new()
+                    });
+                    alternatives.add(venue, locations);
+                    let describe = alternatives.describe();
+                    let still_selected = self
+                        .selected_window()
+                        .is_some_and(|(_, start, end)| (start, end) == window);
+                    if self.current_view == ViewState::BookingForm && still_selected {
+                        self.notice = Some(describe);
+                    }
+                }
+                AppEvent::BookingsCancelled(results) => {
+                    self.cancelling = false;
+                    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+                    if let Some(venue) = self.selected_venue {
+                        let cancelled: HashSet<&str> = results
+                            .iter()
+                            .filter(|(_, result)| result.is_ok())
+                            .map(|(id, _)| id.as_str())
+                            .collect();
+                        self.run_cancelled_hooks(venue, &cancelled);
+                    }
+                    for (id, result) in results {
+                        if let Err(err) = &result {
+                            warn!(booking = id, "failed to cancel booking: {err}");
+                        }
+                        self.marked_bookings.remove(&id);
+                        self.cancellations.insert(id, result);
+                    }
+                    self.notice = Some(match failed {
+                        0 => "Cancelled all marked bookings".to_string(),
+                        failed => format!("{failed} bookings couldn't be cancelled"),
+                    });
+                }
+                AppEvent::MyBookingCancelled(venue, booking, result) => {
+                    self.my_booking_cancelled(venue, &booking, result)
+                }
+                AppEvent::BookingCreated(venue, created) => self.booking_created(venue, created),
+                AppEvent::BatchItemSent(venue, index, result) => {
+                    self.batch_item_sent(venue, index, result)
+                }
+                AppEvent::BatchRolledBack(venue, results) => {
+                    self.batch_rolled_back(venue, &results)
+                }
+                AppEvent::BatchRollbackFailed(venue, err) => {
+                    error!(
+                        venue = self.venues[venue].name,
+                        "failed to roll back bookings: {err}"
+                    );
+                    if let Some(batch) = &mut self.batch {
+                        batch.rolling_back = false;
+                    }
+                    self.notice = Some(format!("Couldn't roll back: {err}"));
+                }
+                AppEvent::BookingFailed(venue, err) => self.booking_failed(venue, err),
+                AppEvent::CancellationFailed(err) => {
+                    self.cancelling = false;
+                    error!("failed to cancel bookings: {err}");
+                    self.notice = Some(format!("Couldn't cancel bookings: {err}"));
+                }
+                AppEvent::LocationsLoaded(venue, names) => {
+                    if names.is_empty() || self.venues[venue].locations == names {
+                        return Ok(());
+                    }
+                    self.venues[venue].locations = names;
+                    // Keep the highlighted location where it still is.
+                    let highlighted = self
+                        .list_state
+                        .selected()
+                        .and_then(|row| self.location_rows.get(row))
+                        .cloned();
+                    self.rebuild_locations();
+                    if let Some(LocationRow::Location { venue, name }) = highlighted {
+                        if let Some(row) = self.location_row(venue, &name) {
+                            self.list_state.select(Some(row));
+                        }
+                    }
+                }
+                AppEvent::VenueDataFailed(venue, err) => {
+                    error!(
+                        venue = self.venues[venue].name,
+                        "failed to load venue: {err}"
+                    );
+                    self.toasts.push(
+                        Severity::Error,
+                        format!("Couldn't load {}: {err}", self.venues[venue].name),
+                    );
+                }
             },
         }
         Ok(())
@@ -95,81 +992,2715 @@ impl App<'_> {
 
     /// Handles the key events and updates the state of [`App`].
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
-        match key_event.code {
-            KeyCode::Esc | KeyCode::Char('q') => match self.current_view {
-                ViewState::LocationSelection => self.events.send(AppEvent::Quit),
-                ViewState::BookingForm | ViewState::Confirmation => {
-                    self.current_view = ViewState::LocationSelection;
-                    self.selected_location = None;
+        self.handle_key(key_event)?;
+        self.autosave_draft();
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
+        self.last_input = Instant::now();
+        if let Some((prompt, input)) = &mut self.prompt {
+            match key_event.code {
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => self.submit_prompt(),
+                KeyCode::Backspace => {
+                    input.pop();
                 }
-            },
+                KeyCode::Char(c) if *prompt != Prompt::StartTime => input.push(c),
+                KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == ':' => input.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.show_inspector {
+            if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('I' | 'q')) {
+                self.show_inspector = false;
+            }
+            return Ok(());
+        }
+        if let Some(scroll) = &mut self.log_viewer {
+            let oldest = logging::recent().len().saturating_sub(1);
+            let page = self.list_page.max(1);
+            match key_event.code {
+                KeyCode::Esc | KeyCode::Char('J' | 'q') => self.log_viewer = None,
+                KeyCode::Up | KeyCode::Char('k') => *scroll = (*scroll + 1).min(oldest),
+                KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_sub(1),
+                KeyCode::PageUp => *scroll = (*scroll + page).min(oldest),
+                KeyCode::PageDown => *scroll = scroll.saturating_sub(page),
+                KeyCode::Home => *scroll = oldest,
+                KeyCode::End => *scroll = 0,
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.cancel_pending.is_some() {
+            match key_event.code {
+                KeyCode::Char('y') | KeyCode::Enter => self.cancel_my_booking(),
+                KeyCode::Char('n' | 'q') | KeyCode::Esc => self.cancel_pending = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.current_view == ViewState::BookingForm
+            && self.form_field == Some(FormField::Title)
+            && self.booking_title.handle_key(key_event)
+        {
+            return Ok(());
+        }
+        let pending_g = std::mem::take(&mut self.pending_g);
+        let in_field = self.current_view == ViewState::BookingForm && self.form_field.is_some();
+        match key_event.code {
+            KeyCode::Esc if in_field => self.form_field = None,
+            KeyCode::Esc | KeyCode::Char('q') => self.navigate(Nav::Back),
             KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => {
                 self.events.send(AppEvent::Quit)
             }
-            KeyCode::Char('t') => {
-                // Set flag to test HTTP client
-                self.test_http = true;
+            KeyCode::Char('V') if self.can_navigate(Nav::Venues) => self.navigate(Nav::Venues),
+            KeyCode::Char('r') if self.current_view != ViewState::BatchResults => {
+                self.refresh_view()
+            }
+            KeyCode::Char('I') => self.show_inspector = true,
+            KeyCode::Char('J') => self.log_viewer = Some(0),
+            KeyCode::Char('P') if self.can_navigate(Nav::Plan) => self.navigate(Nav::Plan),
+            KeyCode::Char('m') if self.can_navigate(Nav::MyBookings) => {
+                self.navigate(Nav::MyBookings)
+            }
+            KeyCode::Char(key)
+                if self.current_view == ViewState::BookingForm && self.preset(key).is_some() =>
+            {
+                self.apply_preset(key);
+            }
+            KeyCode::Char('b') => self.navigate(Nav::QuickBook),
+            KeyCode::Up | KeyCode::Char('k') => self.move_in_list(ListMove::Previous),
+            KeyCode::Down | KeyCode::Char('j') => self.move_in_list(ListMove::Next),
+            KeyCode::Home => self.move_in_list(ListMove::First),
+            KeyCode::End => self.move_in_list(ListMove::Last),
+            KeyCode::PageUp => self.move_in_list(ListMove::PageUp),
+            KeyCode::PageDown => self.move_in_list(ListMove::PageDown),
+            KeyCode::Char('M')
+                if self.can_navigate(Nav::Month) && self.highlighted_space().is_some() =>
+            {
+                self.navigate(Nav::Month);
+            }
+            KeyCode::Char('W')
+                if self.can_navigate(Nav::Week) && self.highlighted_space().is_some() =>
+            {
+                self.navigate(Nav::Week);
+            }
+            KeyCode::Left | KeyCode::Char('h')
+                if matches!(
+                    self.current_view,
+                    ViewState::MonthCalendar | ViewState::Calendar
+                ) =>
+            {
+                self.calendar_date -= time::Duration::DAY;
+            }
+            KeyCode::Right | KeyCode::Char('l')
+                if matches!(
+                    self.current_view,
+                    ViewState::MonthCalendar | ViewState::Calendar
+                ) =>
+            {
+                self.calendar_date += time::Duration::DAY;
+            }
+            KeyCode::Left | KeyCode::Char('h') if self.current_view == ViewState::WeekPlan => {
+                self.move_plan_day(-1)
+            }
+            KeyCode::Right | KeyCode::Char('l') if self.current_view == ViewState::WeekPlan => {
+                self.move_plan_day(1)
+            }
+            KeyCode::Tab if self.current_view == ViewState::BookingForm => {
+                self.form_field = FormField::step(self.form_field, 1);
+            }
+            KeyCode::BackTab if self.current_view == ViewState::BookingForm => {
+                self.form_field = FormField::step(self.form_field, -1);
+            }
+            KeyCode::Left | KeyCode::Char('h') if in_field => self.adjust_form_field(-1),
+            KeyCode::Right | KeyCode::Char('l') if in_field => self.adjust_form_field(1),
+            KeyCode::Left | KeyCode::Char('h' | 'H')
+                if self.current_view == ViewState::BookingForm =>
+            {
+                self.move_grid_cursor(-1, Self::extends_selection(key_event));
+            }
+            KeyCode::Right | KeyCode::Char('l' | 'L')
+                if self.current_view == ViewState::BookingForm =>
+            {
+                self.move_grid_cursor(1, Self::extends_selection(key_event));
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                let selected = self.list_state.selected().unwrap_or(0);
-                let new_selected = if selected == 0 {
-                    self.locations.len().saturating_sub(1)
+            KeyCode::Char('D')
+                if self.current_view == ViewState::BookingForm && self.offered_draft.is_some() =>
+            {
+                self.resume_draft();
+            }
+            KeyCode::Char(' ') if self.current_view == ViewState::BookingForm => {
+                self.toggle_space_group();
+            }
+            KeyCode::Enter
+                if self.current_view == ViewState::BookingForm
+                    && self.highlighted_group.is_some() =>
+            {
+                self.toggle_space_group();
+            }
+            KeyCode::Char('x') if self.current_view == ViewState::BookingForm => {
+                self.toggle_marked_space();
+            }
+            KeyCode::Char('+' | '=') if self.current_view == ViewState::BookingForm => {
+                self.repeat_days = (self.repeat_days + 1).min(MAX_REPEAT_DAYS);
+            }
+            KeyCode::Char('-') if self.current_view == ViewState::BookingForm => {
+                self.repeat_days = self.repeat_days.saturating_sub(1).max(1);
+            }
+            KeyCode::Char('r') if self.current_view == ViewState::BatchResults => {
+                self.retry_batch();
+            }
+            KeyCode::Char('u') if self.current_view == ViewState::BatchResults => {
+                self.roll_back_batch();
+            }
+            KeyCode::Char('T') if self.current_view == ViewState::BookingForm => {
+                self.prompt = Some((Prompt::StartTime, String::new()));
+            }
+            KeyCode::Char('F')
+                if self.current_view == ViewState::BookingForm && self.is_admin() =>
+            {
+                self.prompt = Some((
+                    Prompt::BookFor,
+                    self.booking_for.clone().unwrap_or_default(),
+                ));
+            }
+            KeyCode::Char(key @ ('O' | 'N'))
+                if self.can_navigate(Nav::Overview { go_now: false }) =>
+            {
+                self.navigate(Nav::Overview { go_now: key == 'N' });
+            }
+            KeyCode::Char('B')
+                if self.can_navigate(Nav::SpaceBookings)
+                    && self.is_admin()
+                    && self.highlighted_space().is_some() =>
+            {
+                self.navigate(Nav::SpaceBookings);
+            }
+            KeyCode::Char('c') | KeyCode::Delete if self.current_view == ViewState::MyBookings => {
+                self.ask_to_cancel();
+            }
+            KeyCode::Char('w') if self.current_view == ViewState::SpaceBookings => {
+                self.space_bookings_week = !self.space_bookings_week;
+                self.space_bookings_state.select(Some(0));
+                self.marked_bookings.clear();
+            }
+            KeyCode::Char(' ') if self.current_view == ViewState::SpaceBookings => {
+                if let Some(id) = self
+                    .space_bookings_state
+                    .selected()
+                    .and_then(|selected| self.space_bookings().get(selected)?.id.clone())
+                {
+                    if !self.marked_bookings.remove(&id) {
+                        self.marked_bookings.insert(id);
+                    }
+                }
+            }
+            KeyCode::Char('a') if self.current_view == ViewState::SpaceBookings => {
+                let ids: HashSet<String> = self
+                    .space_bookings()
+                    .iter()
+                    .filter_map(|booking| booking.id.clone())
+                    .filter(|id| !self.cancellations.get(id).is_some_and(Result::is_ok))
+                    .collect();
+                self.marked_bookings = if self.marked_bookings == ids {
+                    HashSet::new()
                 } else {
-                    selected.saturating_sub(1)
+                    ids
                 };
-                self.list_state.select(Some(new_selected));
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                let selected = self.list_state.selected().unwrap_or(0);
-                let new_selected = if selected >= self.locations.len().saturating_sub(1) {
-                    0
-                } else {
-                    selected.saturating_add(1)
+            KeyCode::Char('C')
+                if self.current_view == ViewState::SpaceBookings
+                    && !self.marked_bookings.is_empty()
+                    && !self.cancelling =>
+            {
+                match self
+                    .selected_venue
+                    .and_then(|venue| self.offline_notice(venue))
+                {
+                    Some(notice) => self.notice = Some(notice),
+                    None => self.prompt = Some((Prompt::CancelMessage, String::new())),
+                }
+            }
+            KeyCode::Char('e') if self.current_view == ViewState::Confirmation => {
+                self.export_receipt(receipt::Format::Markdown)
+            }
+            KeyCode::Char('p') if self.current_view == ViewState::Confirmation => {
+                self.export_receipt(receipt::Format::Pdf)
+            }
+            KeyCode::Char('g') if pending_g => self.move_in_list(ListMove::First),
+            KeyCode::Char('g') => self.pending_g = true,
+            KeyCode::Char('G') => self.move_in_list(ListMove::Last),
+            KeyCode::Enter => self.navigate(Nav::Open),
+            // Other handlers you could add here.
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Moves the highlight in the current view's list.
+    fn move_in_list(&mut self, step: ListMove) {
+        let page = self.list_page;
+        match self.current_view {
+            ViewState::VenuePicker => {
+                // "All venues" comes first.
+                let len = self.venues.len() + 1;
+                list::select(&mut self.venue_list_state, len, page, step);
+            }
+            ViewState::Overview => {
+                list::select(&mut self.overview_state, self.overview.len(), page, step);
+            }
+            ViewState::SpaceBookings => {
+                let len = self.space_bookings().len();
+                list::select(&mut self.space_bookings_state, len, page, step);
+            }
+            ViewState::BatchResults => {
+                let len = self.batch.as_ref().map_or(0, |batch| batch.items.len());
+                list::select(&mut self.batch_state, len, page, step);
+            }
+            ViewState::BookingForm => {
+                let rows = self.space_rows();
+                if let Some(row) = list::target(self.space_row(), rows.len(), page, step) {
+                    self.highlight_space_row(&rows[row]);
+                }
+            }
+            ViewState::MonthCalendar => {
+                let date = self.calendar_date;
+                self.calendar_date = match step {
+                    ListMove::Previous => date - time::Duration::WEEK,
+                    ListMove::Next => date + time::Duration::WEEK,
+                    ListMove::First => date.replace_day(1).unwrap_or(date),
+                    ListMove::Last => date
+                        .replace_day(date.month().length(date.year()))
+                        .unwrap_or(date),
+                    ListMove::PageUp => calendar::add_months(date, -1),
+                    ListMove::PageDown => calendar::add_months(date, 1),
                 };
-                self.list_state.select(Some(new_selected));
             }
-            KeyCode::Enter => {
-                match self.current_view {
-                    ViewState::LocationSelection => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected < self.locations.len() {
-                                // Get the location name from the ListItem
-                                let location_name = LOCATIONS[selected];
-                                self.selected_location = Some(location_name.to_string());
-                                self.current_view = ViewState::BookingForm;
-                            }
-                        }
+            ViewState::Calendar => {
+                let last = self.grid.slots.saturating_sub(1);
+                match step {
+                    ListMove::Previous => self.week_slot = self.week_slot.saturating_sub(1),
+                    ListMove::Next => self.week_slot = (self.week_slot + 1).min(last),
+                    ListMove::First => self.week_slot = 0,
+                    ListMove::Last => self.week_slot = last,
+                    ListMove::PageUp => self.calendar_date -= time::Duration::WEEK,
+                    ListMove::PageDown => self.calendar_date += time::Duration::WEEK,
+                }
+            }
+            ViewState::WeekPlan => {
+                let len = self
+                    .week_plan()
+                    .get(self.plan_day)
+                    .map_or(0, |day| day.entries.len());
+                list::select(&mut self.plan_state, len, page, step);
+            }
+            ViewState::MyBookings => {
+                let len = self.upcoming_bookings().len();
+                list::select(&mut self.my_bookings_state, len, page, step);
+            }
+            _ => match step {
+                ListMove::Previous => self.select_location(-1),
+                ListMove::Next => self.select_location(1),
+                _ => {
+                    // Jumps land on locations only, never on venue headings.
+                    let rows: Vec<usize> = (0..self.location_rows.len())
+                        .filter(|&row| {
+                            matches!(self.location_rows[row], LocationRow::Location { .. })
+                        })
+                        .collect();
+                    let selected = self
+                        .list_state
+                        .selected()
+                        .and_then(|selected| rows.iter().position(|&row| row == selected));
+                    if let Some(target) = list::target(selected, rows.len(), page, step) {
+                        self.list_state.select(Some(rows[target]));
                     }
-                    ViewState::BookingForm => {
-                        self.current_view = ViewState::Confirmation;
+                }
+            },
+        }
+    }
 
+    /// Where the current view sits, outermost first, e.g. venue, location, space and day in
+    /// the booking form; empty at the top level.
+    pub fn breadcrumb(&self) -> Vec<String> {
+        const DAY: &[BorrowedFormatItem] =
+            format_description!("[weekday repr:short] [month repr:short] [day padding:none]");
+        let day = |date: Date| date.format(DAY).unwrap_or_else(|_| date.to_string());
+        match self.current_view {
+            ViewState::LocationSelection | ViewState::VenuePicker => Vec::new(),
+            ViewState::Overview if self.overview_go_now => vec!["Go now".to_string()],
+            ViewState::Overview => vec!["Available now".to_string()],
+            ViewState::WeekPlan => {
+                let date = clock::now().date() + time::Duration::days(self.plan_day as i64);
+                vec!["Week ahead".to_string(), day(date)]
+            }
+            ViewState::MyBookings => vec!["My bookings".to_string()],
+            ViewState::BookingForm
+            | ViewState::Confirmation
+            | ViewState::SpaceBookings
+            | ViewState::MonthCalendar
+            | ViewState::Calendar
+            | ViewState::BatchResults => {
+                let mut crumbs: Vec<String> = [
+                    self.selected_venue
+                        .map(|venue| self.venues[venue].name.clone()),
+                    self.selected_location.clone(),
+                    self.highlighted_space().map(|space| space.name.clone()),
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+                match self.current_view {
+                    ViewState::Calendar => crumbs.push(format!(
+                        "Week of {}",
+                        day(calendar::week(self.calendar_date)[0])
+                    )),
+                    ViewState::MonthCalendar => crumbs.push(format!(
+                        "{} {}",
+                        self.calendar_date.month(),
+                        self.calendar_date.year()
+                    )),
+                    ViewState::SpaceBookings => {
+                        crumbs.push(day(self.grid.date));
+                        crumbs.push("All bookings".to_string());
                     }
-                    ViewState::Confirmation => {
-                        // Could reset to location selection or quit
-                        self.current_view = ViewState::LocationSelection;
-                        self.selected_location = None;
+                    ViewState::BatchResults => {
+                        // The batch can cover more than the highlighted space.
+                        if self.highlighted_space().is_some() {
+                            crumbs.pop();
+                        }
+                        crumbs.push("Bookings made".to_string());
                     }
+                    _ => crumbs.push(day(self.grid.date)),
                 }
+                crumbs
             }
-            // Other handlers you could add here.
-            _ => {}
         }
-        Ok(())
     }
 
-    /// Handles the tick event of the terminal.
-    ///
-    /// The tick event is where you can update the state of your application with any logic that
-    /// needs to be updated at a fixed frame rate. E.g. polling a server, updating an animation.
-    pub fn tick(&self) {}
+    /// My bookings that haven't ended yet, soonest first, across the venues in view.
+    pub fn upcoming_bookings(&self) -> Vec<(usize, &MyBooking)> {
+        let now = clock::now();
+        let mut upcoming: Vec<(usize, &MyBooking)> = self
+            .my_bookings
+            .iter()
+            .enumerate()
+            .filter(|(venue, _)| self.active_venue.is_none_or(|active| active == *venue))
+            .flat_map(|(venue, bookings)| bookings.iter().map(move |booking| (venue, booking)))
+            .filter(|(_, booking)| booking.end > now)
+            .collect();
+        upcoming.sort_by_key(|(_, booking)| booking.start);
+        upcoming
+    }
 
-    /// Set running to false to quit the application.
-    pub fn quit(&mut self) {
-        self.running = false;
+    /// My bookings and the gaps between them for the next week, across the venues in view.
+    pub fn week_plan(&self) -> Vec<PlanDay> {
+        let bookings: Vec<(usize, &MyBooking)> = self
+            .my_bookings
+            .iter()
+            .enumerate()
+            .filter(|(venue, _)| self.active_venue.is_none_or(|active| active == *venue))
+            .flat_map(|(venue, bookings)| bookings.iter().map(move |booking| (venue, booking)))
+            .collect();
+        plan::week(
+            clock::now(),
+            &bookings,
+            (self.grid_config.start_hour, self.grid_config.end_hour),
+        )
     }
 
-    /// Renders the user interface.
-    pub fn render(&mut self, frame: &mut ratatui::Frame) {
-        ui::render(self, frame);
+    /// Moves the planning board's cursor `step` days, onto that day's first gap.
+    fn move_plan_day(&mut self, step: isize) {
+        self.plan_day = self
+            .plan_day
+            .saturating_add_signed(step)
+            .min(plan::DAYS - 1);
+        self.select_first_gap();
+    }
+
+    fn select_first_gap(&mut self) {
+        let entries = self
+            .week_plan()
+            .get(self.plan_day)
+            .map(|day| day.entries.clone())
+            .unwrap_or_default();
+        self.plan_state.select(
+            entries
+                .iter()
+                .position(|entry| matches!(entry, PlanEntry::Gap { .. }))
+                .or((!entries.is_empty()).then_some(0)),
+        );
+    }
+
+    fn can_navigate(&self, nav: Nav) -> bool {
+        navigation::transition(self.current_view, nav).is_some()
+    }
+
+    /// The keys that work anywhere in the current view, with what they do, for the status
+    /// bar. A popup or prompt takes the keyboard, so only its own keys are listed then.
+    pub fn status_keys(&self) -> Vec<(&'static str, &'static str)> {
+        if self.prompt.is_some() {
+            return vec![("Enter", "ok"), ("Esc", "cancel")];
+        }
+        if self.show_inspector {
+            return vec![("Esc", "close")];
+        }
+        if self.log_viewer.is_some() {
+            return vec![("↑↓", "scroll"), ("End", "latest"), ("Esc", "close")];
+        }
+        if self.cancel_pending.is_some() {
+            return vec![("y", "cancel it"), ("n", "keep it")];
+        }
+        if self.current_view == ViewState::BookingForm && self.form_field.is_some() {
+            return vec![("Esc", "leave field")];
+        }
+        let mut keys = vec![if self.current_view == ViewState::LocationSelection {
+            ("q", "quit")
+        } else {
+            ("q", "back")
+        }];
+        for (nav, key, action) in [
+            (Nav::Venues, "V", "venues"),
+            (Nav::Plan, "P", "plan"),
+            (Nav::MyBookings, "m", "mine"),
+        ] {
+            if self.can_navigate(nav) {
+                keys.push((key, action));
+            }
+        }
+        keys.extend([
+            ("b", "quick book"),
+            ("r", "refresh"),
+            ("I", "requests"),
+            ("J", "log"),
+        ]);
+        keys
+    }
+
+    /// Moves to the view `nav` leads to, running the transition's effects first and staying
+    /// put if one of them can't run.
+    fn navigate(&mut self, nav: Nav) {
+        let Some((next, effects)) = navigation::transition(self.current_view, nav) else {
+            return;
+        };
+        for &effect in effects {
+            if !self.run_effect(effect) {
+                return;
+            }
+        }
+        self.current_view = next;
+    }
+
+    /// Runs one effect of a view change, returning `false` when it can't.
+    fn run_effect(&mut self, effect: Effect) -> bool {
+        match effect {
+            Effect::Quit => self.events.send(AppEvent::Quit),
+            Effect::OpenLocation => {
+                let Some(LocationRow::Location { venue, name }) = self
+                    .list_state
+                    .selected()
+                    .and_then(|selected| self.location_rows.get(selected))
+                    .cloned()
+                else {
+                    return false;
+                };
+                self.open_location(venue, &name);
+            }
+            Effect::OpenOverviewLocation => {
+                let Some(LocationAvailability {
+                    venue, location, ..
+                }) = self
+                    .overview_state
+                    .selected()
+                    .and_then(|selected| self.overview.get(selected))
+                    .cloned()
+                else {
+                    return false;
+                };
+                self.open_location(venue, &location);
+            }
+            Effect::LoadOverview { go_now } => self.load_overview(go_now),
+            Effect::ShowVenuePicker => {
+                self.venue_list_state
+                    .select(Some(self.active_venue.map_or(0, |venue| venue + 1)));
+            }
+            Effect::SwitchVenue => {
+                // Row 0 is "All venues", the rest are the venues in config order.
+                let selected = self.venue_list_state.selected().unwrap_or(0);
+                self.switch_venue(
+                    selected
+                        .checked_sub(1)
+                        .filter(|venue| *venue < self.venues.len()),
+                );
+            }
+            Effect::ShowMonth => self.calendar_date = self.grid.date,
+            Effect::OpenCalendarDay => {
+                self.grid = TimeGrid::new(
+                    self.calendar_date,
+                    &self.grid_config,
+                    self.booking_increment,
+                );
+                self.grid_slot = self.grid.slot_at(clock::now()).unwrap_or(0);
+                self.grid_anchor = None;
+                self.booking_start = None;
+                self.booking_end = None;
+                self.notice = None;
+            }
+            Effect::ShowWeek => {
+                self.calendar_date = self.grid.date;
+                self.week_slot = self.grid_slot;
+            }
+            Effect::OpenWeekSlot => {
+                self.grid = TimeGrid::new(
+                    self.calendar_date,
+                    &self.grid_config,
+                    self.booking_increment,
+                );
+                self.grid_slot = self.week_slot.min(self.grid.slots.saturating_sub(1));
+                self.grid_anchor = None;
+                self.booking_start = None;
+                self.booking_end = None;
+                self.notice = None;
+            }
+            Effect::QuickBook => {
+                let Some((venue, location)) = self.quick_book_location.clone() else {
+                    self.toasts.push(
+                        Severity::Warning,
+                        "Set quick_book.location or startup.location to quick-book",
+                    );
+                    return false;
+                };
+                if let Some(row) = self.location_row(venue, &location) {
+                    self.list_state.select(Some(row));
+                }
+                self.quick_booking = true;
+                self.open_location(venue, &location);
+            }
+            Effect::ShowPlan => {
+                self.plan_day = 0;
+                self.select_first_gap();
+            }
+            Effect::ShowMyBookings => {
+                let any = !self.upcoming_bookings().is_empty();
+                self.my_bookings_state.select(any.then_some(0));
+            }
+            Effect::OpenMyBooking => {
+                let Some((venue, booking)) = self
+                    .my_bookings_state
+                    .selected()
+                    .and_then(|selected| self.upcoming_bookings().get(selected).copied())
+                else {
+                    return false;
+                };
+                let (start, location) = (booking.start, booking.location.clone());
+                let Some(location) = location else {
+                    self.toasts.push(
+                        Severity::Info,
+                        "That space isn't in any of the venue's configured locations",
+                    );
+                    return false;
+                };
+                if let Some(row) = self.location_row(venue, &location) {
+                    self.list_state.select(Some(row));
+                }
+                self.planned_start = Some(start);
+                self.open_location(venue, &location);
+            }
+            Effect::OpenPlannedGap => {
+                let Some(PlanEntry::Gap { start, .. }) =
+                    self.plan_state.selected().and_then(|selected| {
+                        self.week_plan()
+                            .get(self.plan_day)?
+                            .entries
+                            .get(selected)
+                            .cloned()
+                    })
+                else {
+                    return false;
+                };
+                self.planned_start = Some(start);
+                if !self.run_effect(Effect::OpenLocation) {
+                    self.planned_start = None;
+                    return false;
+                }
+            }
+            Effect::ClearSelection => {
+                self.selected_venue = None;
+                self.selected_location = None;
+                self.created_booking = None;
+                self.marked_spaces.clear();
+                self.repeat_days = 1;
+            }
+            Effect::SubmitBooking => {
+                if self.submitting.is_some() || self.batch.is_some() {
+                    return false;
+                }
+                if let Some(notice) = self
+                    .selected_venue
+                    .and_then(|venue| self.offline_notice(venue))
+                {
+                    self.notice = Some(notice);
+                    return false;
+                }
+                if self.batch_windows().len() > 1 {
+                    return self.submit_batch();
+                }
+                let Some(request) = self.booking_request() else {
+                    return false;
+                };
+                self.submit_booking(request);
+            }
+            Effect::AwaitSubmission => {
+                if self.submitting.is_some() {
+                    self.notice = Some("Wait for the booking on its way".to_string());
+                    return false;
+                }
+            }
+            Effect::KeepBatch => {
+                let Some(batch) = &self.batch else {
+                    return true;
+                };
+                if !batch.is_settled() {
+                    self.notice = Some("Wait for the bookings on their way".to_string());
+                    return false;
+                }
+                if batch.tally().booked > 0 {
+                    self.run_effect(Effect::DiscardDraft);
+                }
+                self.batch = None;
+                self.marked_spaces.clear();
+                self.repeat_days = 1;
+                self.notice = None;
+            }
+            Effect::CheckBookingRules => {
+                if let Some(reason) = self.rule_violation() {
+                    if !self.look_elsewhere(&reason) {
+                        self.notice = Some(reason);
+                    }
+                    return false;
+                }
+            }
+            Effect::CheckMyBookings => {
+                if let Some(reason) = self.my_clash() {
+                    self.notice = Some(format!("{reason} · Enter to book anyway"));
+                    return false;
+                }
+            }
+            Effect::DiscardDraft => {
+                if let (Some(drafts), Some(draft)) = (&self.drafts, self.current_draft()) {
+                    if let Err(err) = drafts.discard(&draft.venue, &draft.location) {
+                        warn!("failed to discard the booking draft: {err:#}");
+                    }
+                }
+                self.offered_draft = None;
+            }
+            Effect::ResetSpaceBookings => {
+                self.space_bookings_week = false;
+                self.space_bookings_state.select(Some(0));
+                self.marked_bookings.clear();
+                self.notice = None;
+            }
+        }
+        true
+    }
+
+    fn open_location(&mut self, venue: usize, location: &str) {
+        self.form_field = None;
+        self.alternatives = None;
+        self.booking_title.clear();
+        self.marked_spaces.clear();
+        self.repeat_days = 1;
+        self.selected_venue = Some(venue);
+        self.selected_location = Some(location.to_string());
+        self.load_spaces(venue, false);
+    }
+
+    /// The location list's row for the venue's location, if the list shows it.
+    fn location_row(&self, venue: usize, location: &str) -> Option<usize> {
+        self.location_rows.iter().position(|row| {
+            matches!(row, LocationRow::Location { venue: row_venue, name }
+                if *row_venue == venue && name == location)
+        })
+    }
+
+    /// Moves the selection by `step` rows, wrapping around and skipping venue headings.
+    fn select_location(&mut self, step: isize) {
+        let len = self.location_rows.len() as isize;
+        let mut selected = self.list_state.selected().unwrap_or(0) as isize;
+        for _ in 0..len {
+            selected = (selected + step).rem_euclid(len);
+            if matches!(
+                self.location_rows[selected as usize],
+                LocationRow::Location { .. }
+            ) {
+                self.list_state.select(Some(selected as usize));
+                return;
+            }
+        }
+    }
+
+    /// The venue being booked, or the one under the cursor in the location list.
+    pub fn current_venue(&self) -> Option<usize> {
+        self.selected_venue.or(self.active_venue).or_else(|| {
+            match self
+                .list_state
+                .selected()
+                .and_then(|selected| self.location_rows.get(selected))
+            {
+                Some(LocationRow::Venue(venue) | LocationRow::Location { venue, .. }) => {
+                    Some(*venue)
+                }
+                None => None,
+            }
+        })
+    }
+
+    /// Fetches the venue's spaces, the ones tagged with the selected location and their
+    /// bookings in the background, from the cache unless `refresh` is set. The result comes
+    /// back as [`AppEvent::BookingDataLoaded`] or [`AppEvent::RequestFailed`].
+    fn load_spaces(&mut self, venue: usize, refresh: bool) {
+        self.loading_spaces = true;
+        self.selected_location_space_ids.clear();
+        self.bookings.clear();
+        self.cancellations.clear();
+        self.notice = None;
+        self.refreshing_spaces = false;
+        self.fetch_booking_data(venue, refresh);
+    }
+
+    /// Fetches the venue's data for the booking form, reusing the cached copy while it's fresh
+    /// unless `refresh` asks for the venue's current data.
+    fn fetch_booking_data(&self, venue: usize, refresh: bool) {
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        // A timed reload that fails keeps what's on screen instead.
+        let fall_back = !self.refreshing_spaces;
+        thread::spawn(move || {
+            let skedda = Skedda::new(&config);
+            let event = match skedda.get_cached_booking_data(refresh) {
+                Ok((webs_data, fetched_at)) => AppEvent::BookingDataLoaded(
+                    venue,
+                    Box::new(webs_data),
+                    skedda.session_expiry(),
+                    fetched_at,
+                ),
+                Err(err) if !fall_back => AppEvent::RequestFailed(venue, err),
+                Err(err) => match Skedda::last_known_booking_data(&config, err) {
+                    Ok((webs_data, fetched_at)) => {
+                        let _ = sender.send(Event::App(AppEvent::Offline(venue, fetched_at)));
+                        AppEvent::BookingDataLoaded(
+                            venue,
+                            Box::new(webs_data),
+                            skedda.session_expiry(),
+                            fetched_at,
+                        )
+                    }
+                    Err(err) => AppEvent::RequestFailed(venue, err),
+                },
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    /// Fills the booking form from freshly fetched venue data, unless the user has moved on
+    /// to another venue since.
+    fn booking_data_loaded(
+        &mut self,
+        venue: usize,
+        webs_data: &WebsResponse,
+        expires_at: Option<SystemTime>,
+        fetched_at: SystemTime,
+    ) {
+        let Some(location_name) = self
+            .selected_location
+            .clone()
+            .filter(|_| self.selected_venue == Some(venue))
+        else {
+            return;
+        };
+        let refresh = std::mem::take(&mut self.refreshing_spaces);
+        self.loading_spaces = false;
+        self.outage = None;
+        self.outage_backoff.reset();
+        let venue_spaces = Skedda::space_details(webs_data);
+        let mut location_space_ids = Skedda::location_space_ids(webs_data, &location_name);
+        location_space_ids.retain(|space_id| venue_spaces.contains_key(space_id));
+        // Each type of space together, in the venue's order within a type.
+        location_space_ids.sort_by_key(|space_id| {
+            let kind = venue_spaces[space_id].kind.as_deref();
+            (kind.is_none(), kind.map(str::to_lowercase))
+        });
+        let role = Skedda::role(webs_data);
+        self.venue_id = webs_data.venue.first().and_then(|venue| venue.id.clone());
+        self.members = Skedda::member_names(webs_data);
+        self.venue_spaces = venue_spaces;
+        self.selected_location_space_ids = location_space_ids;
+        self.bookings = Skedda::bookings(webs_data);
+        let age = SystemTime::now()
+            .duration_since(fetched_at)
+            .unwrap_or_default();
+        self.booking_data_at = Some(Instant::now() - age);
+        self.my_bookings[venue] = Skedda::my_bookings(webs_data, &self.venues[venue].locations);
+        self.my_bookings_at = self.booking_data_at;
+        self.sessions[venue].expires_at = expires_at;
+        if !refresh {
+            self.opening = None;
+        }
+        self.update_opening();
+        if refresh {
+            self.toasts.push(Severity::Info, "Availability refreshed");
+            let last = self.selected_location_space_ids.len().checked_sub(1);
+            self.space_list_state.select(
+                self.space_list_state
+                    .selected()
+                    .zip(last)
+                    .map(|(selected, last)| selected.min(last)),
+            );
+            return;
+        }
+        self.booking_increment = Skedda::booking_increment(webs_data);
+        self.roles[venue] = role;
+        if self.venues[venue].admin && role == Some(Role::Member) {
+            warn!(
+                venue = self.venues[venue].name,
+                "admin is set but the account isn't an admin here"
+            );
+            self.notice =
+                Some("Admin features are off: this account isn't an admin here".to_string());
+        }
+        self.reset_booking_form();
+        if std::mem::take(&mut self.quick_booking) {
+            self.fill_quick_booking();
+        }
+        self.prefetch_space_images();
+    }
+
+    /// Empties the booking form after its data couldn't be fetched, and schedules a retry
+    /// when Skedda is down.
+    fn request_failed(&mut self, venue: usize, err: SkeddaError) {
+        if self.selected_venue != Some(venue) || self.selected_location.is_none() {
+            return;
+        }
+        if std::mem::take(&mut self.refreshing_spaces) {
+            // What's on screen is a little old but still useful; the next refresh may work.
+            warn!("failed to refresh spaces: {err}");
+            self.toasts.push(
+                Severity::Warning,
+                "Couldn't refresh availability; showing what's here",
+            );
+            return;
+        }
+        self.loading_spaces = false;
+        self.quick_booking = false;
+        self.outage = None;
+        self.venue_spaces.clear();
+        self.selected_location_space_ids.clear();
+        self.bookings.clear();
+        self.booking_increment = None;
+        self.opening = None;
+        error!(
+            location = self.selected_location.as_deref(),
+            "failed to load spaces: {err}"
+        );
+        match err {
+            SkeddaError::Outage(outage) => {
+                let retry_after = self
+                    .outage_backoff
+                    .next_delay()
+                    .max(outage.retry_after.unwrap_or_default());
+                self.outage = Some((outage.to_string(), Instant::now() + retry_after));
+            }
+            // Retrying on a timer would only look more like a bot.
+            SkeddaError::Challenge(challenge) => self.notice = Some(challenge.to_string()),
+            SkeddaError::Other(_) => {}
+            // Each of these says what to do about it.
+            err => self.notice = Some(err.to_string()),
+        }
+        self.reset_booking_form();
+    }
+
+    /// Puts the grid cursor on the current time, or the planned start when the form was opened
+    /// from the planning board, and clears any half-made booking.
+    fn reset_booking_form(&mut self) {
+        self.booking_start = None;
+        self.booking_end = None;
+        self.booking_for = None;
+        self.overlap_confirmed = None;
+        let start = self.planned_start.take().unwrap_or_else(clock::now);
+        self.grid = TimeGrid::new(start.date(), &self.grid_config, self.booking_increment);
+        self.grid_slot = self.grid.slot_at(start).unwrap_or(0);
+        self.grid_anchor = None;
+        let rows = self.space_rows();
+        match rows
+            .iter()
+            .find(|row| matches!(row, SpaceRow::Space(_)))
+            .or(rows.first())
+        {
+            Some(row) => self.highlight_space_row(row),
+            None => {
+                self.highlighted_group = None;
+                self.space_list_state.select(None);
+            }
+        }
+        self.initial_draft = self.current_draft();
+        self.saved_draft = None;
+        self.offered_draft = self.initial_draft.as_ref().and_then(|initial| {
+            self.drafts
+                .as_ref()?
+                .find(&initial.venue, &initial.location, clock::now())
+        });
+        if let Some(draft) = &self.offered_draft {
+            let space = self
+                .venue_spaces
+                .get(&draft.space_id)
+                .map_or("a space", |space| space.name.as_str());
+            self.notice = Some(format!(
+                "You left a draft: {space} {} {}–{} · D to resume it",
+                draft.start.weekday(),
+                grid::format_time(draft.start.time()),
+                grid::format_time(draft.end.time())
+            ));
+        }
+    }
+
+    /// The booking the form would make, for saving as a draft.
+    fn current_draft(&self) -> Option<BookingDraft> {
+        let venue = self.selected_venue?;
+        let (space_id, start, end) = self.selected_window()?;
+        Some(BookingDraft {
+            venue: self.venues[venue].key(),
+            location: self.selected_location.clone()?,
+            space_id: space_id.to_string(),
+            start,
+            end,
+            booked_for: self.booking_for.clone(),
+        })
+    }
+
+    /// Saves the open form as a draft once it's been changed from how it loaded.
+    fn autosave_draft(&mut self) {
+        if self.current_view != ViewState::BookingForm || self.loading_spaces {
+            return;
+        }
+        let Some(draft) = self.current_draft() else {
+            return;
+        };
+        if Some(&draft) == self.initial_draft.as_ref() || Some(&draft) == self.saved_draft.as_ref()
+        {
+            return;
+        }
+        let Some(drafts) = &self.drafts else {
+            return;
+        };
+        match drafts.save(&draft, clock::now()) {
+            Ok(()) => {
+                // Editing the form replaces the draft that was on offer.
+                if self.offered_draft.take().is_some() {
+                    self.notice = None;
+                }
+                self.saved_draft = Some(draft);
+            }
+            Err(err) => warn!("failed to save the booking draft: {err:#}"),
+        }
+    }
+
+    /// Puts the form back how the offered draft left it.
+    fn resume_draft(&mut self) {
+        let Some(draft) = self.offered_draft.take() else {
+            return;
+        };
+        if let Err(reason) = self.select_window(&draft.space_id, draft.start, draft.end) {
+            self.notice = Some(format!("Can't resume the draft: {reason}"));
+            return;
+        }
+        self.booking_for = draft.booked_for.clone().filter(|_| self.is_admin());
+        self.notice = Some("Resumed your draft".to_string());
+        self.saved_draft = Some(draft);
+    }
+
+    /// Highlights the space and selects `start..end` in the grid, moving it to that day.
+    fn select_window(
+        &mut self,
+        space_id: &str,
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+    ) -> Result<(), &'static str> {
+        let index = self
+            .selected_location_space_ids
+            .iter()
+            .position(|id| id == space_id)
+            .ok_or("the space isn't at this location any more")?;
+        let grid = TimeGrid::new(start.date(), &self.grid_config, self.booking_increment);
+        let (Some(first), Some(last)) = (
+            grid.slot_at(start),
+            grid.slot_at(end - time::Duration::MINUTE),
+        ) else {
+            return Err("it's outside the grid's hours");
+        };
+        self.grid = grid;
+        if let Some(kind) = self.space_group(space_id) {
+            self.collapsed_groups.remove(&kind);
+        }
+        self.highlight_space_row(&SpaceRow::Space(index));
+        self.grid_anchor = (first != last).then_some(first);
+        self.grid_slot = last;
+        self.booking_start = (start != self.grid.slot_start(first)).then_some(start);
+        self.booking_end = (end != self.grid.slot_end(last)).then_some(end);
+        Ok(())
+    }
+
+    /// Fills in the quick booking: from the next half hour, in the preferred space if it's
+    /// free, or else the first space that is.
+    fn fill_quick_booking(&mut self) {
+        let start = grid::next_half_hour(clock::now());
+        let end = start + time::Duration::minutes(self.quick_book.minutes.into());
+        let is_free =
+            |space_id: &&String| availability::is_free(&self.bookings, space_id, start, end);
+        let preferred = self.quick_book.space.as_deref().and_then(|name| {
+            self.selected_location_space_ids
+                .iter()
+                .find(|space_id| self.venue_spaces[*space_id].name.eq_ignore_ascii_case(name))
+        });
+        let window = format!(
+            "{}–{}",
+            grid::format_time(start.time()),
+            grid::format_time(end.time())
+        );
+        let Some(space_id) = preferred
+            .filter(is_free)
+            .or_else(|| self.selected_location_space_ids.iter().find(is_free))
+            .cloned()
+        else {
+            self.notice = Some(format!("Nothing here is free {window}"));
+            return;
+        };
+        self.notice = Some(match self.select_window(&space_id, start, end) {
+            Ok(()) => format!(
+                "Quick book: {} {window} · Enter to book",
+                self.venue_spaces[&space_id].name
+            ),
+            Err(reason) => format!("Can't quick-book {window}: {reason}"),
+        });
+    }
+
+    /// The heading a space is listed under, when the location's spaces are grouped by type.
+    fn space_group(&self, space_id: &str) -> Option<String> {
+        let kinds: HashSet<Option<&str>> = self
+            .selected_location_space_ids
+            .iter()
+            .map(|space_id| self.venue_spaces[space_id].kind.as_deref())
+            .collect();
+        if kinds.len() < 2 {
+            return None;
+        }
+        let kind = self.venue_spaces.get(space_id)?.kind.as_deref();
+        Some(kind.unwrap_or(OTHER_SPACES).to_string())
+    }
+
+    /// The booking form's space list: the location's spaces under a heading for each type,
+    /// leaving out those in collapsed groups. A location with one type of space, or none
+    /// set, is listed without headings.
+    pub fn space_rows(&self) -> Vec<SpaceRow> {
+        let mut rows = Vec::new();
+        for (index, space_id) in self.selected_location_space_ids.iter().enumerate() {
+            let Some(kind) = self.space_group(space_id) else {
+                rows.push(SpaceRow::Space(index));
+                continue;
+            };
+            let collapsed = self.collapsed_groups.contains(&kind);
+            match rows.iter_mut().rev().find_map(|row| match row {
+                SpaceRow::Group {
+                    kind: group,
+                    spaces,
+                    ..
+                } => Some((group, spaces)),
+                SpaceRow::Space(_) => None,
+            }) {
+                Some((group, spaces)) if *group == kind => *spaces += 1,
+                _ => rows.push(SpaceRow::Group {
+                    kind,
+                    spaces: 1,
+                    collapsed,
+                }),
+            }
+            if !collapsed {
+                rows.push(SpaceRow::Space(index));
+            }
+        }
+        rows
+    }
+
+    /// The row of the space list under the cursor: the highlighted heading or space, or the
+    /// heading of a highlighted space whose group is collapsed.
+    pub fn space_row(&self) -> Option<usize> {
+        let rows = self.space_rows();
+        let heading = |kind: &str| {
+            rows.iter()
+                .position(|row| matches!(row, SpaceRow::Group { kind: group, .. } if group == kind))
+        };
+        if let Some(kind) = &self.highlighted_group {
+            return heading(kind);
+        }
+        let selected = self.space_list_state.selected()?;
+        rows.iter()
+            .position(|row| *row == SpaceRow::Space(selected))
+            .or_else(|| {
+                let space_id = self.selected_location_space_ids.get(selected)?;
+                heading(&self.space_group(space_id)?)
+            })
+    }
+
+    fn highlight_space_row(&mut self, row: &SpaceRow) {
+        match row {
+            SpaceRow::Group { kind, .. } => {
+                self.highlighted_group = Some(kind.clone());
+                self.space_list_state.select(None);
+            }
+            SpaceRow::Space(index) => {
+                self.highlighted_group = None;
+                self.space_list_state.select(Some(*index));
+                self.load_space_image();
+            }
+        }
+    }
+
+    /// Folds away or unfolds the group under the cursor, or the highlighted space's group,
+    /// leaving the cursor on its heading.
+    fn toggle_space_group(&mut self) {
+        let Some(kind) = self.highlighted_group.clone().or_else(|| {
+            let space_id = self
+                .selected_location_space_ids
+                .get(self.space_list_state.selected()?)?;
+            self.space_group(space_id)
+        }) else {
+            return;
+        };
+        if !self.collapsed_groups.remove(&kind) {
+            self.collapsed_groups.insert(kind.clone());
+        }
+        self.highlighted_group = Some(kind);
+        self.space_list_state.select(None);
+    }
+
+    /// The space under the cursor in the booking form.
+    pub fn highlighted_space(&self) -> Option<&SpaceDetails> {
+        let space_id = self
+            .selected_location_space_ids
+            .get(self.space_list_state.selected()?)?;
+        self.venue_spaces.get(space_id)
+    }
+
+    fn extends_selection(key_event: KeyEvent) -> bool {
+        key_event.modifiers.contains(KeyModifiers::SHIFT)
+            || matches!(key_event.code, KeyCode::Char('H' | 'L'))
+    }
+
+    /// Moves the grid cursor one slot, growing or shrinking the selection from its anchor when
+    /// `extend` is set and starting over from a single slot otherwise.
+    fn move_grid_cursor(&mut self, step: isize, extend: bool) {
+        if extend {
+            self.grid_anchor.get_or_insert(self.grid_slot);
+        } else {
+            self.grid_anchor = None;
+            self.booking_start = None;
+            self.booking_end = None;
+        }
+        self.grid_slot = self
+            .grid_slot
+            .saturating_add_signed(step)
+            .min(self.grid.slots.saturating_sub(1));
+        self.notice = None;
+    }
+
+    /// Moves the selected window a day, or its start or end a booking increment, for the
+    /// focused date and time field.
+    fn adjust_form_field(&mut self, step: i64) {
+        let (Some(field), Some((space_id, start, end))) = (
+            self.form_field,
+            self.selected_window()
+                .map(|(space_id, start, end)| (space_id.to_string(), start, end)),
+        ) else {
+            return;
+        };
+        let increment = time::Duration::minutes(
+            self.booking_increment
+                .unwrap_or(self.grid.slot_minutes)
+                .into(),
+        );
+        let (start, end) = match field {
+            FormField::Date => (
+                start + time::Duration::days(step),
+                end + time::Duration::days(step),
+            ),
+            FormField::Start => (
+                start + increment * step as i32,
+                end + increment * step as i32,
+            ),
+            FormField::Duration => (
+                start,
+                (end + increment * step as i32).max(start + increment),
+            ),
+            FormField::Title => return,
+        };
+        self.notice = match self.select_window(&space_id, start, end) {
+            Ok(()) => None,
+            Err(reason) => Some(format!("Can't move there: {reason}")),
+        };
+    }
+
+    /// The selected slots: from the anchor to the cursor, or just the cursor.
+    pub fn selected_slots(&self) -> RangeInclusive<usize> {
+        let anchor = self.grid_anchor.unwrap_or(self.grid_slot);
+        anchor.min(self.grid_slot)..=anchor.max(self.grid_slot)
+    }
+
+    /// Cancels the marked bookings in the background, reporting each one's outcome.
+    fn cancel_marked_bookings(&mut self, message: Option<String>) {
+        let Some(venue) = self.selected_venue else {
+            return;
+        };
+        let mut booking_ids: Vec<String> = self.marked_bookings.iter().cloned().collect();
+        booking_ids.sort();
+        self.cancelling = true;
+        self.notice = Some(format!("Cancelling {} bookings…", booking_ids.len()));
+
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let event = match Skedda::new(&config).cancel_bookings(&booking_ids, message.as_deref())
+            {
+                Ok(results) => AppEvent::BookingsCancelled(
+                    results
+                        .into_iter()
+                        .map(|(id, result)| (id, result.map_err(|err| err.to_string())))
+                        .collect(),
+                ),
+                Err(err) => AppEvent::CancellationFailed(err.to_string()),
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    /// Asks whether to cancel the booking under the my bookings list's cursor.
+    fn ask_to_cancel(&mut self) {
+        let Some((venue, booking)) = self
+            .my_bookings_state
+            .selected()
+            .and_then(|selected| self.upcoming_bookings().get(selected).copied())
+        else {
+            return;
+        };
+        if self.cancelling {
+            self.toasts
+                .push(Severity::Info, "Wait for the cancellation on its way");
+        } else if let Some(notice) = self.offline_notice(venue) {
+            self.toasts.push(Severity::Warning, notice);
+        } else if booking.id.is_none() {
+            self.toasts.push(
+                Severity::Warning,
+                "Skedda didn't say which booking this is, so it can't be cancelled here",
+            );
+        } else {
+            self.cancel_pending = Some((venue, booking.clone()));
+        }
+    }
+
+    /// Cancels the booking the user just said yes to, in the background.
+    fn cancel_my_booking(&mut self) {
+        let Some((venue, booking)) = self.cancel_pending.take() else {
+            return;
+        };
+        let Some(id) = booking.id.clone() else {
+            return;
+        };
+        self.cancelling = true;
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let result = Skedda::new(&config)
+                .cancel_booking(&id)
+                .map_err(|err| err.to_string());
+            let _ = sender.send(Event::App(AppEvent::MyBookingCancelled(
+                venue, booking, result,
+            )));
+        });
+    }
+
+    fn my_booking_cancelled(
+        &mut self,
+        venue: usize,
+        booking: &MyBooking,
+        result: Result<(), String>,
+    ) {
+        self.cancelling = false;
+        let place = self.booking_place(venue, booking);
+        let when = format!(
+            "{} {}–{}",
+            booking.start.weekday(),
+            grid::format_time(booking.start.time()),
+            grid::format_time(booking.end.time())
+        );
+        match result {
+            Ok(()) => {
+                info!(
+                    venue = self.venues[venue].name,
+                    booking = booking.id,
+                    "booking cancelled"
+                );
+                if self.hooks.command(HookEvent::Cancelled).is_some() {
+                    self.spawn_hook(
+                        HookEvent::Cancelled,
+                        HookBooking {
+                            id: booking.id.clone().unwrap_or_default(),
+                            venue: self.venues[venue].name.clone(),
+                            location: booking.location.clone(),
+                            space: booking.space.clone(),
+                            start: booking.start,
+                            end: booking.end,
+                            title: booking.title.clone(),
+                        },
+                    );
+                }
+                self.my_bookings[venue].retain(|mine| mine.id != booking.id);
+                if self.selected_venue == Some(venue) {
+                    self.bookings.retain(|other| other.id != booking.id);
+                }
+                self.toasts
+                    .push(Severity::Success, format!("Cancelled {place} {when}"));
+            }
+            Err(err) => {
+                error!(
+                    venue = self.venues[venue].name,
+                    booking = booking.id,
+                    "failed to cancel booking: {err}"
+                );
+                self.toasts.push(
+                    Severity::Error,
+                    format!("Couldn't cancel {place} {when}: {err}"),
+                );
+            }
+        }
+    }
+
+    /// Runs the hook for the event, if one is configured, for a booking at the venue.
+    fn run_hook(&self, event: HookEvent, venue: usize, booking: &Booking) {
+        if self.hooks.command(event).is_none() {
+            return;
+        }
+        let space = booking
+            .space_ids
+            .iter()
+            .map(|space_id| {
+                self.venue_spaces
+                    .get(space_id)
+                    .map_or(space_id.as_str(), |space| space.name.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.spawn_hook(
+            event,
+            HookBooking {
+                id: booking.id.clone().unwrap_or_default(),
+                venue: self.venues[venue].name.clone(),
+                location: self
+                    .selected_location
+                    .clone()
+                    .filter(|_| self.selected_venue == Some(venue)),
+                space,
+                start: booking.start,
+                end: booking.end,
+                title: booking.title.clone(),
+            },
+        );
+    }
+
+    /// Runs the cancelled hook for each of the selected venue's bookings with the given ids.
+    fn run_cancelled_hooks(&self, venue: usize, cancelled: &HashSet<&str>) {
+        if self.selected_venue != Some(venue) {
+            return;
+        }
+        for booking in &self.bookings {
+            if booking
+                .id
+                .as_deref()
+                .is_some_and(|id| cancelled.contains(id))
+            {
+                self.run_hook(HookEvent::Cancelled, venue, booking);
+            }
+        }
+    }
+
+    /// Runs the hook in the background, logging it if it fails.
+    fn spawn_hook(&self, event: HookEvent, booking: HookBooking) {
+        let config = self.hooks.clone();
+        thread::spawn(move || {
+            if let Err(err) = hooks::run(&config, event, &booking) {
+                warn!("{err:#}");
+            }
+        });
+    }
+
+    /// Everyone's bookings of the highlighted space on the grid's day, or in the week from it,
+    /// in start order.
+    pub fn space_bookings(&self) -> Vec<&Booking> {
+        let Some(space_id) = self
+            .space_list_state
+            .selected()
+            .and_then(|selected| self.selected_location_space_ids.get(selected))
+        else {
+            return Vec::new();
+        };
+        let days = if self.space_bookings_week { 7 } else { 1 };
+        let start = self.grid.date.midnight();
+        let end = start + time::Duration::days(days);
+        let mut bookings: Vec<_> = self
+            .bookings
+            .iter()
+            .filter(|booking| {
+                booking.start < end && booking.end > start && booking.space_ids.contains(space_id)
+            })
+            .collect();
+        bookings.sort_by_key(|booking| booking.start);
+        bookings
+    }
+
+    /// The highlighted space's id and the time window selected in the grid.
+    pub fn selected_window(&self) -> Option<(&str, PrimitiveDateTime, PrimitiveDateTime)> {
+        let space_id = self
+            .selected_location_space_ids
+            .get(self.space_list_state.selected()?)?;
+        let (first, last) = self.selected_slots().into_inner();
+        (last < self.grid.slots).then(|| {
+            // A typed start time shifts the whole window off the slot edges.
+            let slot_start = self.grid.slot_start(first);
+            let start = self
+                .booking_start
+                .filter(|start| self.grid.slot_at(*start) == Some(first))
+                .unwrap_or(slot_start);
+            let end = self
+                .booking_end
+                .filter(|end| self.grid.slot_at(*end - time::Duration::MINUTE) == Some(last))
+                .unwrap_or_else(|| self.grid.slot_end(last) + (start - slot_start));
+            (space_id.as_str(), start, end)
+        })
+    }
+
+    /// Why the selected window can't be booked, if it can't: it takes in a slot that has
+    /// passed, it runs into another booking of the space, or it breaks the space's rules.
+    pub fn rule_violation(&self) -> Option<String> {
+        let space = self.highlighted_space()?;
+        let (space_id, start, end) = self.selected_window()?;
+        let now = clock::now();
+        if self.grid.slot_end(*self.selected_slots().start()) <= now {
+            return Some("That time has passed".to_string());
+        }
+        if let Some(booking) = availability::conflict(&self.bookings, space_id, start, end) {
+            return Some(format!(
+                "{} is booked {}–{}",
+                space.name,
+                grid::format_time(booking.start.time()),
+                grid::format_time(booking.end.time())
+            ));
+        }
+        space.check(start, end, now).err()
+    }
+
+    /// When the highlighted space is taken for the selected window and nothing else of its
+    /// kind at the location is free to book then, looks for one at the other locations in
+    /// view and shows `reason` with what's found. Returns whether it did.
+    fn look_elsewhere(&mut self, reason: &str) -> bool {
+        let (Some(venue), Some(location), Some(space)) = (
+            self.selected_venue,
+            self.selected_location.clone(),
+            self.highlighted_space(),
+        ) else {
+            return false;
+        };
+        let Some((space_id, start, end)) = self.selected_window() else {
+            return false;
+        };
+        if availability::conflict(&self.bookings, space_id, start, end).is_none() {
+            return false;
+        }
+        let kind = space.kind.clone().filter(|kind| !kind.trim().is_empty());
+        let meeting_room = availability::is_meeting_room(&space.name);
+        let matches = move |label: &str| match &kind {
+            Some(kind) => availability::is_kind(label, kind),
+            None => availability::is_meeting_room(label) == meeting_room,
+        };
+        let now = clock::now();
+        let free_here = self.selected_location_space_ids.iter().any(|space_id| {
+            let space = &self.venue_spaces[space_id];
+            matches(space.kind.as_deref().unwrap_or(&space.name))
+                && availability::is_free(&self.bookings, space_id, start, end)
+                && space.check(start, end, now).is_ok()
+        });
+        if free_here {
+            return false;
+        }
+        let kind = match &space.kind {
+            Some(kind) if !kind.trim().is_empty() => kind.to_lowercase(),
+            _ if meeting_room => "meeting room".to_string(),
+            _ => "space".to_string(),
+        };
+
+        let window = (start, end);
+        let searched = self.alternatives.as_ref().is_some_and(|alternatives| {
+            (
+                alternatives.venue,
+                &alternatives.location,
+                alternatives.window,
+                &alternatives.kind,
+            ) == (venue, &location, window, &kind)
+        });
+        if !searched {
+            let venues: Vec<usize> = match self.active_venue {
+                Some(venue) => vec![venue],
+                None => (0..self.venues.len()).collect(),
+            };
+            self.alternatives = Some(Alternatives {
+                venue,
+                location,
+                window,
+                kind,
+                reason: String::new(),
+                locations: Vec::new(),
+                pending: venues.iter().copied().collect(),
+            });
+            self.survey_locations(
+                venues,
+                move |venue, config, summary| {
+                    availability::overview(venue, &config.locations, summary, window, &matches)
+                },
+                move |venue, result| AppEvent::AlternativesLoaded(venue, window, result),
+            );
+        }
+        let Some(alternatives) = &mut self.alternatives else {
+            return false;
+        };
+        alternatives.reason = reason.to_string();
+        self.notice = Some(alternatives.describe());
+        true
+    }
+
+    /// Which of my own bookings the selected window overlaps, the first time it's asked
+    /// about that window. Admins booking for a member aren't asked.
+    fn my_clash(&mut self) -> Option<String> {
+        if self.booking_for.is_some() {
+            return None;
+        }
+        let (space_id, start, end) = self.selected_window()?;
+        let window = (space_id.to_string(), start, end);
+        if self.overlap_confirmed.as_ref() == Some(&window) {
+            return None;
+        }
+        let (venue, booking) = availability::my_clash(&self.my_bookings, start, end)?;
+        let reason = format!(
+            "You're booked at {} {}–{}",
+            self.booking_place(venue, booking),
+            grid::format_time(booking.start.time()),
+            grid::format_time(booking.end.time())
+        );
+        self.overlap_confirmed = Some(window);
+        Some(reason)
+    }
+
+    /// The name of the configured window running from `start` to `end`, if there is one.
+    pub fn window_name(&self, start: PrimitiveDateTime, end: PrimitiveDateTime) -> Option<&str> {
+        self.windows
+            .iter()
+            .find(|&&(_, from, to)| {
+                start.date() == end.date() && (from, to) == (start.time(), end.time())
+            })
+            .map(|(name, _, _)| name.as_str())
+    }
+
+    /// The preset bound to `key` for the venue being booked.
+    fn preset(&self, key: char) -> Option<&Preset> {
+        self.presets
+            .get(self.selected_venue?)?
+            .iter()
+            .find(|preset| preset.key == key)
+    }
+
+    /// Selects a preset's window, snapped to the venue's booking increment.
+    fn apply_preset(&mut self, key: char) {
+        let Some(preset) = self.preset(key).cloned() else {
+            return;
+        };
+        let increment = self.booking_increment.unwrap_or(self.grid.slot_minutes);
+        let last_slot = self.grid.slots.saturating_sub(1);
+        let time = |value: &str, keyword, edge: PrimitiveDateTime| {
+            if value == keyword {
+                Some(edge)
+            } else {
+                grid::parse_time(value)
+                    .map(|time| PrimitiveDateTime::new(self.grid.date, grid::snap(time, increment)))
+            }
+        };
+        let (Some(start), Some(end)) = (
+            time(&preset.start, "open", self.grid.slot_start(0)),
+            time(&preset.end, "close", self.grid.slot_end(last_slot)),
+        ) else {
+            return;
+        };
+        let (Some(first), Some(last)) = (
+            self.grid.slot_at(start),
+            self.grid
+                .slot_at(end - time::Duration::MINUTE)
+                .or((end > self.grid.slot_end(last_slot)).then_some(last_slot)),
+        ) else {
+            self.notice = Some(format!("{} is outside the grid", preset.name));
+            return;
+        };
+        if end <= start {
+            self.notice = Some(format!("{} ends before it starts", preset.name));
+            return;
+        }
+        let end = end.min(self.grid.slot_end(last));
+        self.grid_anchor = Some(first);
+        self.grid_slot = last;
+        self.booking_start = (start != self.grid.slot_start(first)).then_some(start);
+        self.booking_end = (end != self.grid.slot_end(last)).then_some(end);
+        self.notice = Some(format!(
+            "{}: {}–{}",
+            preset.name,
+            grid::format_time(start.time()),
+            grid::format_time(end.time())
+        ));
+    }
+
+    /// The request for the booking selected in the form, or `None` with a notice saying why
+    /// it can't be made.
+    fn booking_request(&mut self) -> Option<BookingRequest> {
+        let (space_id, start, end) = self
+            .selected_window()
+            .map(|(space_id, start, end)| (space_id.to_string(), start, end))?;
+        self.request_for(&space_id, start, end)
+    }
+
+    /// The request to book the space for the window, for the member being booked for if
+    /// there is one.
+    fn request_for(
+        &mut self,
+        space_id: &str,
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+    ) -> Option<BookingRequest> {
+        let space_id = space_id.to_string();
+        let Some(venue_id) = &self.venue_id else {
+            self.notice =
+                Some("The venue data doesn't say which venue this is; r to reload".into());
+            return None;
+        };
+        let title = Some(self.booking_title.text().trim()).filter(|title| !title.is_empty());
+        let request = BookingRequest::new(venue_id, &[space_id], title, start, end);
+        let venueuser = match &self.booking_for {
+            None => None,
+            Some(member) => {
+                let Some(id) = self.members.iter().find_map(|(id, name)| {
+                    (name.eq_ignore_ascii_case(member) || id == member).then(|| id.clone())
+                }) else {
+                    self.notice = Some(format!("No member called {member} here"));
+                    return None;
+                };
+                Some(id)
+            }
+        };
+        Some(BookingRequest {
+            venueuser,
+            ..request
+        })
+    }
+
+    /// Sends the booking in the background. One that failed without a clear answer is first
+    /// looked for at the venue, so sending it again can't book it twice.
+    fn submit_booking(&mut self, request: BookingRequest) {
+        let Some(venue) = self.selected_venue else {
+            return;
+        };
+        let recheck = self.unconfirmed_booking.as_ref() == Some(&request);
+        self.submitting = Some(request.clone());
+        self.notice = Some("Booking…".to_string());
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let event = match send_booking(&config, &request, recheck) {
+                Ok(created) => AppEvent::BookingCreated(venue, created),
+                Err(err) => AppEvent::BookingFailed(venue, err),
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    /// Shows the confirmation once Skedda has made the booking, if the form is still open,
+    /// and puts the booking in the grid and my bookings either way.
+    fn booking_created(&mut self, venue: usize, created: CreatedBooking) {
+        let Some(request) = self.submitting.take() else {
+            return;
+        };
+        self.unconfirmed_booking = None;
+        self.booking_title.clear();
+        info!(
+            venue = self.venues[venue].name,
+            booking = created.id,
+            "booking created"
+        );
+        let space = request
+            .spaces
+            .iter()
+            .map(|space_id| {
+                self.venue_spaces
+                    .get(space_id)
+                    .map_or(space_id.as_str(), |space| space.name.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.toasts.push(
+            Severity::Success,
+            format!(
+                "Booked {space} {}–{}",
+                grid::format_time(request.start.time()),
+                grid::format_time(request.end.time())
+            ),
+        );
+        self.load_my_bookings(venue);
+        if !self.notify.channels(NotifyEvent::Booked).is_empty() {
+            let place = self
+                .selected_location
+                .clone()
+                .filter(|_| self.selected_venue == Some(venue))
+                .unwrap_or_else(|| self.venues[venue].name.clone());
+            let message = Message::booked(&space, &place, request.start, request.end);
+            let config = self.notify.clone();
+            thread::spawn(move || {
+                if let Err(err) = notify::notify(&config, NotifyEvent::Booked, &message) {
+                    warn!("{err:#}");
+                }
+            });
+        }
+        let booking = Booking {
+            id: Some(created.id.clone()),
+            holder: None,
+            venueuser: request.venueuser.clone(),
+            title: request.title.clone(),
+            space_ids: request.spaces.clone(),
+            start: request.start,
+            end: request.end,
+        };
+        self.run_hook(HookEvent::Booked, venue, &booking);
+        if self.selected_venue != Some(venue) {
+            return;
+        }
+        self.bookings.push(booking);
+        if self.current_view == ViewState::BookingForm {
+            self.notice = None;
+            self.created_booking = Some(created);
+            self.navigate(Nav::Booked);
+        }
+    }
+
+    fn booking_failed(&mut self, venue: usize, err: SkeddaError) {
+        let Some(request) = self.submitting.take() else {
+            return;
+        };
+        error!(venue = self.venues[venue].name, "failed to book: {err}");
+        let notice = match &err {
+            // A clear no: nothing was booked, and the grid is out of date.
+            SkeddaError::BookingConflict(_) => {
+                self.toasts
+                    .push(Severity::Error, "That time was just taken");
+                format!("Couldn't book: {err} · r to refresh")
+            }
+            _ => {
+                self.unconfirmed_booking = Some(request);
+                self.toasts.push(Severity::Error, "Booking failed");
+                format!("Couldn't book: {err}")
+            }
+        };
+        if self.current_view == ViewState::BookingForm && self.selected_venue == Some(venue) {
+            self.notice = Some(notice);
+        }
+    }
+
+    /// Marks the highlighted space to be booked along with the one under the cursor when
+    /// the form is submitted, or unmarks it.
+    fn toggle_marked_space(&mut self) {
+        let Some(space_id) = self
+            .space_list_state
+            .selected()
+            .and_then(|selected| self.selected_location_space_ids.get(selected))
+            .cloned()
+        else {
+            return;
+        };
+        match self.marked_spaces.iter().position(|id| *id == space_id) {
+            Some(position) => {
+                self.marked_spaces.remove(position);
+            }
+            None => self.marked_spaces.push(space_id),
+        }
+    }
+
+    /// Every space and window the booking form would book: the highlighted space and the
+    /// marked ones, on each day the window is repeated over, day by day.
+    pub fn batch_windows(&self) -> Vec<(String, PrimitiveDateTime, PrimitiveDateTime)> {
+        let Some((space_id, start, end)) = self.selected_window() else {
+            return Vec::new();
+        };
+        let spaces: Vec<&str> = std::iter::once(space_id)
+            .chain(
+                self.marked_spaces
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|id| *id != space_id),
+            )
+            .collect();
+        (0..i64::from(self.repeat_days))
+            .flat_map(|day| {
+                let shift = time::Duration::days(day);
+                spaces
+                    .iter()
+                    .map(move |space| (space.to_string(), start + shift, end + shift))
+            })
+            .collect()
+    }
+
+    /// Why a booking of the batch can't be made and is left out, if it can't.
+    fn batch_skip_reason(
+        &self,
+        space_id: &str,
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+    ) -> Option<String> {
+        if let Some(space) = self.venue_spaces.get(space_id) {
+            if let Err(reason) = space.check(start, end, clock::now()) {
+                return Some(reason);
+            }
+        }
+        availability::conflict(&self.bookings, space_id, start, end)
+            .map(|_| "Already booked".to_string())
+    }
+
+    /// Sends each booking of the batch the form describes, leaving out any that can't be
+    /// made. Refuses when none can.
+    fn submit_batch(&mut self) -> bool {
+        let Some(venue) = self.selected_venue else {
+            return false;
+        };
+        let mut requests = Vec::new();
+        for (space_id, start, end) in self.batch_windows() {
+            let Some(request) = self.request_for(&space_id, start, end) else {
+                return false;
+            };
+            let space = self
+                .venue_spaces
+                .get(&space_id)
+                .map_or(space_id.clone(), |space| space.name.clone());
+            let skip = self.batch_skip_reason(&space_id, start, end);
+            requests.push((request, space, skip));
+        }
+        let batch = Batch::new(venue, requests);
+        let pending = batch.pending();
+        if pending.is_empty() {
+            let reason = batch.items.iter().find_map(|item| match &item.state {
+                ItemState::Skipped(reason) => Some(reason.clone()),
+                _ => None,
+            });
+            self.notice = Some(format!(
+                "None of the {} bookings can be made: {}",
+                batch.items.len(),
+                reason.unwrap_or_default()
+            ));
+            return false;
+        }
+        self.batch = Some(batch);
+        self.batch_state.select(Some(0));
+        self.send_batch(venue, pending, false);
+        true
+    }
+
+    /// Sends the batch's bookings one after another in the background, reporting each. A
+    /// retry first looks for each booking at the venue, in case the failure hid a success.
+    fn send_batch(&mut self, venue: usize, requests: Vec<(usize, BookingRequest)>, recheck: bool) {
+        self.notice = Some(format!("Booking {}…", requests.len()));
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            for (index, request) in requests {
+                let result = send_booking(&config, &request, recheck);
+                let event =
+                    AppEvent::BatchItemSent(venue, index, result.map_err(|err| err.to_string()));
+                let _ = sender.send(Event::App(event));
+            }
+        });
+    }
+
+    /// Records how one booking of the batch went, and once they've all been sent, shows how
+    /// the batch went if the booking form is still open.
+    fn batch_item_sent(
+        &mut self,
+        venue: usize,
+        index: usize,
+        result: Result<CreatedBooking, String>,
+    ) {
+        let Some(batch) = self.batch.as_mut().filter(|batch| batch.venue == venue) else {
+            return;
+        };
+        let booked = match &result {
+            Ok(created) => {
+                info!(
+                    venue = self.venues[venue].name,
+                    booking = created.id,
+                    "booking created"
+                );
+                batch.items.get(index).map(|item| Booking {
+                    id: Some(created.id.clone()),
+                    holder: None,
+                    venueuser: item.request.venueuser.clone(),
+                    title: item.request.title.clone(),
+                    space_ids: item.request.spaces.clone(),
+                    start: item.request.start,
+                    end: item.request.end,
+                })
+            }
+            Err(err) => {
+                error!(venue = self.venues[venue].name, "failed to book: {err}");
+                None
+            }
+        };
+        batch.settle(index, result);
+        let tally = batch.tally();
+        let total = batch.items.len();
+        if let Some(booking) = booked {
+            self.run_hook(HookEvent::Booked, venue, &booking);
+            if self.selected_venue == Some(venue) {
+                self.bookings.push(booking);
+            }
+        }
+        if tally.pending > 0 {
+            self.notice = Some(format!("Booking… {} to go", tally.pending));
+            return;
+        }
+        self.notice = None;
+        self.load_my_bookings(venue);
+        if tally.failed == 0 && tally.skipped == 0 {
+            self.toasts.push(
+                Severity::Success,
+                format!("Made all {} bookings", tally.booked),
+            );
+        } else {
+            self.toasts.push(
+                Severity::Warning,
+                format!("Made {} of {total} bookings", tally.booked),
+            );
+        }
+        match self.current_view {
+            ViewState::BookingForm => self.navigate(Nav::BatchSent),
+            ViewState::BatchResults => {}
+            // Nowhere to show how it went; the toast and the audit log have it.
+            _ => self.batch = None,
+        }
+    }
+
+    /// Sends the batch's failed bookings again.
+    fn retry_batch(&mut self) {
+        let Some(batch) = self.batch.as_mut().filter(|batch| batch.is_settled()) else {
+            return;
+        };
+        let venue = batch.venue;
+        let requests = batch.retry_failed();
+        if requests.is_empty() {
+            self.notice = Some("Nothing failed to retry".to_string());
+            return;
+        }
+        self.send_batch(venue, requests, true);
+    }
+
+    /// Cancels every booking the batch made, undoing it.
+    fn roll_back_batch(&mut self) {
+        let Some(batch) = self.batch.as_mut().filter(|batch| batch.is_settled()) else {
+            return;
+        };
+        let booking_ids = batch.booked_ids();
+        if booking_ids.is_empty() {
+            self.notice = Some("Nothing was booked to roll back".to_string());
+            return;
+        }
+        batch.rolling_back = true;
+        let venue = batch.venue;
+        self.notice = Some(format!("Cancelling {} bookings…", booking_ids.len()));
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let event = match Skedda::new(&config).cancel_bookings(&booking_ids, None) {
+                Ok(results) => AppEvent::BatchRolledBack(
+                    venue,
+                    results
+                        .into_iter()
+                        .map(|(id, result)| (id, result.map_err(|err| err.to_string())))
+                        .collect(),
+                ),
+                Err(err) => AppEvent::BatchRollbackFailed(venue, err.to_string()),
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    fn batch_rolled_back(&mut self, venue: usize, results: &[(String, Result<(), String>)]) {
+        let Some(batch) = self.batch.as_mut().filter(|batch| batch.venue == venue) else {
+            return;
+        };
+        batch.rolled_back(results);
+        let cancelled: HashSet<&str> = results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(id, _)| id.as_str())
+            .collect();
+        self.run_cancelled_hooks(venue, &cancelled);
+        self.bookings.retain(|booking| {
+            booking
+                .id
+                .as_deref()
+                .is_none_or(|id| !cancelled.contains(id))
+        });
+        let failed = results.len() - cancelled.len();
+        self.notice = Some(match failed {
+            0 => "Rolled back: cancelled every booking made".to_string(),
+            failed => format!("{failed} bookings couldn't be cancelled; u to try again"),
+        });
+        self.load_my_bookings(venue);
+    }
+
+    /// The receipt for the booking on the confirmation screen.
+    pub fn receipt(&self) -> Option<Receipt> {
+        let venue = self.selected_venue?;
+        let (space_id, start, end) = self.selected_window()?;
+        Some(Receipt {
+            venue: self.venues[venue].name.clone(),
+            location: self.selected_location.clone()?,
+            space: self
+                .venue_spaces
+                .get(space_id)
+                .map_or(space_id, |space| space.name.as_str())
+                .to_string(),
+            start,
+            end,
+            booked_for: self.booking_for.clone(),
+            booking_id: self
+                .created_booking
+                .as_ref()
+                .map(|created| created.id.clone()),
+            price: self
+                .created_booking
+                .as_ref()
+                .and_then(|created| created.price)
+                .filter(|&price| price > 0.0)
+                .map(|price| format!("{price:.2}")),
+        })
+    }
+
+    fn export_receipt(&mut self, format: receipt::Format) {
+        let result = self
+            .receipt()
+            .context("no booking to write a receipt for")
+            .and_then(|receipt| {
+                let paths = Paths::resolve().context("could not determine the data directory")?;
+                receipt.export(format, &paths.receipts_dir())
+            });
+        self.notice = Some(match result {
+            Ok(path) => format!("Saved receipt to {}", path.display()),
+            Err(err) => {
+                error!("failed to export receipt: {err:#}");
+                format!("Couldn't save the receipt: {err:#}")
+            }
+        });
+    }
+
+    /// Whether admin features are available at the venue being booked: `admin` is set for
+    /// the venue and the venue data doesn't say the account is only a member.
+    pub fn is_admin(&self) -> bool {
+        self.selected_venue.is_some_and(|venue| {
+            self.venues[venue].admin && self.roles[venue] != Some(Role::Member)
+        })
+    }
+
+    fn submit_prompt(&mut self) {
+        match self.prompt.take() {
+            Some((Prompt::StartTime, input)) => self.apply_time_input(&input),
+            Some((Prompt::BookFor, input)) => {
+                let member = input.trim();
+                self.booking_for = (!member.is_empty()).then(|| member.to_string());
+            }
+            Some((Prompt::CancelMessage, input)) => {
+                let message = input.trim();
+                self.cancel_marked_bookings((!message.is_empty()).then(|| message.to_string()));
+            }
+            None => {}
+        }
+    }
+
+    /// Moves the cursor to the typed start time, snapped to the venue's booking increment,
+    /// and says so when the time had to be adjusted.
+    fn apply_time_input(&mut self, input: &str) {
+        let Some(time) = grid::parse_time(input) else {
+            self.notice = Some(format!("Couldn't read \"{input}\" as a time"));
+            return;
+        };
+        let increment = self.booking_increment.unwrap_or(self.grid.slot_minutes);
+        let snapped = grid::snap(time, increment);
+        let start = PrimitiveDateTime::new(self.grid.date, snapped);
+        let Some(slot) = self.grid.slot_at(start) else {
+            self.notice = Some(format!(
+                "{} is outside the grid",
+                grid::format_time(snapped)
+            ));
+            return;
+        };
+        self.grid_slot = slot;
+        self.grid_anchor = None;
+        self.booking_end = None;
+        self.booking_start = (start != self.grid.slot_start(slot)).then_some(start);
+        self.notice = (snapped != time).then(|| {
+            format!(
+                "Adjusted to {} (bookings start every {increment} min)",
+                grid::format_time(snapped)
+            )
+        });
+    }
+
+    /// Works out when the next day at the location becomes bookable, saying so once the last
+    /// one has.
+    fn update_opening(&mut self) {
+        let now = clock::now();
+        if let Some(opened) = self.opening.take_if(|opening| opening.at <= now) {
+            self.toasts.push(Severity::Info, opened.describe(now));
+        }
+        self.opening = openings::next(
+            self.selected_location_space_ids
+                .iter()
+                .filter_map(|space_id| self.venue_spaces.get(space_id)),
+            now,
+        );
+    }
+
+    /// Downloads and decodes the highlighted space's photo in the background, ahead of any
+    /// photos still waiting to be prefetched.
+    fn load_space_image(&mut self) {
+        let Some(image_url) = self
+            .highlighted_space()
+            .and_then(|space| space.image_url.clone())
+        else {
+            return;
+        };
+        self.load_space_images(vec![image_url], true);
+    }
+
+    /// Starts on the photos of every space at the location, in list order, so moving through
+    /// the list shows each one without waiting on it. Details other than the photo come with
+    /// the venue data.
+    fn prefetch_space_images(&mut self) {
+        let image_urls = self
+            .selected_location_space_ids
+            .iter()
+            .filter_map(|space_id| self.venue_spaces.get(space_id)?.image_url.clone())
+            .collect();
+        self.load_space_images(image_urls, false);
+    }
+
+    /// Downloads and decodes photos in the background, a few at a time, skipping those
+    /// already loaded or on their way. With `first`, they go ahead of those still waiting,
+    /// and are moved up if they're among them. Photos waiting from another venue are dropped.
+    fn load_space_images(&mut self, image_urls: Vec<String>, first: bool) {
+        let Some(venue) = self.selected_venue else {
+            return;
+        };
+        let mut queue = self
+            .image_queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let space_images = &mut self.space_images;
+        queue.waiting.retain(|(queued, _, image_url)| {
+            if *queued != venue {
+                space_images.remove(image_url);
+            }
+            *queued == venue
+        });
+        for image_url in image_urls {
+            let waiting = queue
+                .waiting
+                .iter()
+                .position(|(_, _, queued)| *queued == image_url);
+            let next = match waiting {
+                Some(position) if first => queue.waiting.remove(position),
+                Some(_) => None,
+                None if self.space_images.contains_key(&image_url) => None,
+                None => {
+                    self.space_images.insert(image_url.clone(), None);
+                    Some((venue, self.venues[venue].clone(), image_url))
+                }
+            };
+            match next {
+                Some(next) if first => queue.waiting.push_front(next),
+                Some(next) => queue.waiting.push_back(next),
+                None => {}
+            }
+        }
+
+        let workers = PREFETCH_CONCURRENCY
+            .saturating_sub(queue.workers)
+            .min(queue.waiting.len());
+        queue.workers += workers;
+        drop(queue);
+        for _ in 0..workers {
+            let queue = Arc::clone(&self.image_queue);
+            let sender = self.events.sender();
+            thread::spawn(move || {
+                loop {
+                    let next = {
+                        let mut queue = queue.lock().unwrap_or_else(PoisonError::into_inner);
+                        let next = queue.waiting.pop_front();
+                        if next.is_none() {
+                            queue.workers -= 1;
+                        }
+                        next
+                    };
+                    let Some((venue, config, image_url)) = next else {
+                        break;
+                    };
+                    let image = Skedda::new(&config)
+                        .fetch_image(&image_url)
+                        .map_err(|err| err.to_string())
+                        .and_then(|bytes| {
+                            image::load_from_memory(&bytes).map_err(|err| err.to_string())
+                        })
+                        .map(|image| image.thumbnail(THUMBNAIL_SIZE.0, THUMBNAIL_SIZE.1));
+                    let event = match image {
+                        Ok(image) => AppEvent::SpaceImageLoaded(venue, image_url, image),
+                        Err(err) => AppEvent::SpaceImageFailed(venue, image_url, err),
+                    };
+                    if sender.send(Event::App(event)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+
+    /// Forgets a photo still marked as on its way, so it's fetched again when next wanted.
+    fn forget_space_image(&mut self, image_url: &str) {
+        if self
+            .space_images
+            .get(image_url)
+            .is_some_and(Option::is_none)
+        {
+            self.space_images.remove(image_url);
+        }
+    }
+
+    /// Handles the tick event of the terminal.
+    ///
+    /// The tick event is where you can update the state of your application with any logic that
+    /// needs to be updated at a fixed frame rate. E.g. polling a server, updating an animation.
+    /// Runs whatever periodic work is due. Spinners read the tick count when drawn.
+    pub fn tick(&mut self) {
+        self.scheduler.advance();
+        self.toasts.tick();
+        if self.scheduler.every(HOUSEKEEPING_INTERVAL) {
+            for venue in 0..self.venues.len() {
+                self.refresh_session_if_expiring(venue);
+                self.keep_session_alive(venue);
+            }
+            self.warm_up_connection();
+            self.retry_after_outage();
+            if self
+                .opening
+                .as_ref()
+                .is_some_and(|opening| opening.at <= clock::now())
+            {
+                self.update_opening();
+            }
+        }
+        if self.scheduler.every(BOOKING_DATA_REFRESH) {
+            self.refresh_booking_data();
+        }
+        if self.scheduler.every(MY_BOOKINGS_REFRESH) {
+            for venue in 0..self.venues.len() {
+                if self.sessions[venue].expires_at.is_some()
+                    && self.may_poll(venue)
+                    && polling::try_spend()
+                {
+                    self.load_my_bookings(venue);
+                }
+            }
+        }
+    }
+
+    /// Whether the venue's poll floor lets a timed reload fetch from it yet.
+    fn may_poll(&self, venue: usize) -> bool {
+        Skedda::new(&self.venues[venue]).may_poll()
+    }
+
+    /// Fetches the account's own bookings at the venue in the background.
+    fn load_my_bookings(&self, venue: usize) {
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let webs = Skedda::new(&config)
+                .get_cached_booking_data(true)
+                .or_else(|err| {
+                    let last_known = Skedda::last_known_booking_data(&config, err);
+                    if let Ok((_, fetched_at)) = &last_known {
+                        let _ = sender.send(Event::App(AppEvent::Offline(venue, *fetched_at)));
+                    }
+                    last_known
+                });
+            match webs {
+                Ok((webs, fetched_at)) => {
+                    let mine = Skedda::my_bookings(&webs, &config.locations);
+                    let _ = sender.send(Event::App(AppEvent::MyBookingsLoaded(
+                        venue, mine, fetched_at,
+                    )));
+                }
+                // The countdown keeps what it had; the next refresh may work.
+                Err(err) => warn!(venue = config.name, "failed to load my bookings: {err}"),
+            }
+        });
+    }
+
+    /// The account's next booking, or the one under way, across the venues in view.
+    pub fn next_booking(&self) -> Option<(usize, &MyBooking)> {
+        let now = clock::now();
+        self.my_bookings
+            .iter()
+            .enumerate()
+            .filter(|(venue, _)| self.active_venue.is_none_or(|active| active == *venue))
+            .filter_map(|(venue, bookings)| {
+                let next = bookings.iter().find(|booking| booking.end > now)?;
+                Some((venue, next))
+            })
+            .min_by_key(|(_, booking)| booking.start)
+    }
+
+    /// A message about one venue, prefixed with its name when there are several.
+    fn for_venue(&self, venue: usize, message: &str) -> String {
+        if self.venues.len() > 1 {
+            format!("{}: {message}", self.venues[venue].name)
+        } else {
+            message.to_string()
+        }
+    }
+
+    /// The highlighted space's bookings on each day of the month view's month.
+    pub fn calendar_month(&self) -> HashMap<Date, CalendarDay> {
+        let (Some(venue), Some(space_id), Some(space)) = (
+            self.selected_venue,
+            self.space_list_state
+                .selected()
+                .and_then(|selected| self.selected_location_space_ids.get(selected)),
+            self.highlighted_space(),
+        ) else {
+            return HashMap::new();
+        };
+        calendar::month(
+            self.calendar_date,
+            &self.bookings,
+            space_id,
+            &self.my_bookings[venue],
+            &space.name,
+        )
+    }
+
+    /// The highlighted space's slots over the week view's week, a row for each slot with a
+    /// column for each day from Monday.
+    pub fn calendar_week(&self) -> Vec<[calendar::Slot; 7]> {
+        let (Some(venue), Some(space_id), Some(space)) = (
+            self.selected_venue,
+            self.space_list_state
+                .selected()
+                .and_then(|selected| self.selected_location_space_ids.get(selected)),
+            self.highlighted_space(),
+        ) else {
+            return Vec::new();
+        };
+        calendar::week_slots(
+            TimeGrid {
+                date: self.calendar_date,
+                ..self.grid
+            },
+            &self.bookings,
+            space_id,
+            &self.my_bookings[venue],
+            &space.name,
+        )
+    }
+
+    /// Where one of my bookings is, e.g. "Boardroom @ Decatur", naming the venue when there
+    /// are several and the space has no location.
+    pub fn booking_place(&self, venue: usize, booking: &MyBooking) -> String {
+        match (&booking.location, self.venues.len() > 1) {
+            (Some(location), _) => format!("{} @ {location}", booking.space),
+            (None, true) => format!("{} @ {}", booking.space, self.venues[venue].name),
+            (None, false) => booking.space.clone(),
+        }
+    }
+
+    /// Re-fetches what the current view shows, at the user's request: the booking form's
+    /// venue data, the overview, or my bookings for the planning board and status bar.
+    fn refresh_view(&mut self) {
+        if self
+            .manual_refresh_at
+            .is_some_and(|at| at.elapsed() < MANUAL_REFRESH_DEBOUNCE)
+        {
+            self.toasts
+                .push(Severity::Info, "Just refreshed; try again in a few seconds");
+            return;
+        }
+        match self.current_view {
+            ViewState::BookingForm
+            | ViewState::SpaceBookings
+            | ViewState::MonthCalendar
+            | ViewState::Calendar => {
+                let Some(venue) = self.selected_venue else {
+                    return;
+                };
+                if self.loading_spaces || self.refreshing_spaces {
+                    return;
+                }
+                if self.outage.is_some() {
+                    self.load_spaces(venue, true);
+                } else {
+                    self.refreshing_spaces = true;
+                    self.fetch_booking_data(venue, true);
+                }
+            }
+            ViewState::Overview => {
+                if !self.overview_pending.is_empty() {
+                    return;
+                }
+                self.load_overview(self.overview_go_now);
+            }
+            ViewState::LocationSelection | ViewState::WeekPlan | ViewState::MyBookings => {
+                for venue in 0..self.venues.len() {
+                    if self.active_venue.is_none_or(|active| active == venue)
+                        && self.sessions[venue].expires_at.is_some()
+                    {
+                        self.load_my_bookings(venue);
+                    }
+                }
+            }
+            ViewState::Confirmation | ViewState::VenuePicker | ViewState::BatchResults => return,
+        }
+        self.manual_refresh_at = Some(Instant::now());
+    }
+
+    /// When the data shown in place of a venue that can't be reached was fetched: the current
+    /// venue's, or else the oldest of any.
+    pub fn offline_since(&self) -> Option<SystemTime> {
+        match self.current_venue() {
+            Some(venue) => self.offline[venue],
+            None => self.offline.iter().flatten().min().copied(),
+        }
+    }
+
+    /// How long ago the current view's data arrived, for views that fetch any.
+    pub fn data_age(&self) -> Option<Duration> {
+        let at = match self.current_view {
+            ViewState::BookingForm
+            | ViewState::SpaceBookings
+            | ViewState::MonthCalendar
+            | ViewState::Calendar
+                if !self.loading_spaces =>
+            {
+                self.booking_data_at
+            }
+            ViewState::Overview if self.overview_pending.is_empty() => self.overview_at,
+            ViewState::WeekPlan | ViewState::MyBookings => self.my_bookings_at,
+            _ => None,
+        };
+        at.map(|at| at.elapsed())
+    }
+
+    /// Re-fetches the open booking form's data, keeping the cursor and any half-made booking.
+    fn refresh_booking_data(&mut self) {
+        if self.current_view != ViewState::BookingForm
+            || self.loading_spaces
+            || self.outage.is_some()
+        {
+            return;
+        }
+        if let (Some(venue), Some(_)) = (self.selected_venue, &self.selected_location) {
+            if !self.may_poll(venue) || !polling::try_spend() {
+                return;
+            }
+            self.refreshing_spaces = true;
+            self.fetch_booking_data(venue, true);
+        }
+    }
+
+    /// Reloads the booking form once the wait after an outage is up.
+    fn retry_after_outage(&mut self) {
+        if self.current_view != ViewState::BookingForm
+            || self
+                .outage
+                .as_ref()
+                .is_none_or(|(_, retry_at)| Instant::now() < *retry_at)
+            || !polling::try_spend()
+        {
+            return;
+        }
+        if let (Some(venue), Some(_)) = (self.selected_venue, &self.selected_location) {
+            self.load_spaces(venue, true);
+        }
+    }
+
+    /// Keeps a connection open to the highlighted venue while the location list is up, so
+    /// picking a location only waits on the request itself, not on TCP and TLS setup. Once the
+    /// user stops pressing keys the connection is left to close.
+    fn warm_up_connection(&mut self) {
+        if self.current_view != ViewState::LocationSelection {
+            return;
+        }
+        let Some(venue) = self.current_venue() else {
+            return;
+        };
+        let last_input = self.last_input;
+        let session = &mut self.sessions[venue];
+        if session.warmed_at.is_some_and(|warmed_at| {
+            warmed_at > last_input || warmed_at.elapsed() < WARM_UP_INTERVAL
+        }) || !polling::try_spend()
+        {
+            return;
+        }
+
+        session.warmed_at = Some(Instant::now());
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            if let Err(err) = Skedda::new(&config).warm_up() {
+                debug!("failed to warm up connection: {err}");
+            }
+        });
+    }
+
+    /// Refreshes the venue's session in the background before it expires, rather than letting a
+    /// booking fail halfway through.
+    fn refresh_session_if_expiring(&mut self, venue: usize) {
+        let session = &mut self.sessions[venue];
+        let Some(expires_at) = session.expires_at else {
+            return;
+        };
+        if session.refreshing
+            || SessionHealth::of(expires_at) == SessionHealth::Valid
+            || session
+                .refresh_retry_at
+                .is_some_and(|retry_at| Instant::now() < retry_at)
+            || !polling::try_spend()
+        {
+            return;
+        }
+
+        session.refreshing = true;
+        session.refresh_retry_at = Some(Instant::now() + polling::jitter(SESSION_REFRESH_RETRY));
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let event = match Skedda::new(&config).refresh_session() {
+                Ok(expires_at) => AppEvent::SessionRefreshed(venue, expires_at),
+                Err(err) => AppEvent::SessionRefreshFailed(venue, err.to_string()),
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    /// Touches the venue's session once it has gone unused for the keep-alive interval, so it
+    /// doesn't lapse while the TUI sits open.
+    fn keep_session_alive(&mut self, venue: usize) {
+        let Some(interval) = self.keep_alive else {
+            return;
+        };
+        let session = &mut self.sessions[venue];
+        // Nothing to keep until there's a session, and the clock starts once there is.
+        if !session.loaded || session.expires_at.is_none() || session.refreshing {
+            return;
+        }
+        let kept_alive_at = *session.kept_alive_at.get_or_insert_with(Instant::now);
+        if kept_alive_at.elapsed() < interval || !polling::try_spend() {
+            return;
+        }
+
+        session.kept_alive_at = Some(Instant::now());
+        let sender = self.events.sender();
+        let config = self.venues[venue].clone();
+        thread::spawn(move || {
+            let event = match Skedda::new(&config).keep_alive() {
+                Ok(expires_at) => AppEvent::SessionKeptAlive(venue, expires_at),
+                Err(err) => AppEvent::KeepAliveFailed(venue, err.to_string()),
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    /// Notes that the venue's session was just used, which keeps it going as well as a
+    /// keep-alive would.
+    fn session_used(&mut self, venue: usize) {
+        if let Some(session) = self.sessions.get_mut(venue) {
+            if session.kept_alive_at.is_some() {
+                session.kept_alive_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Notes data from the venue fetched at `fetched_at`, which used its session unless it's
+    /// what was shown while the venue couldn't be reached. Anything newer means it's back.
+    fn venue_reached(&mut self, venue: usize, fetched_at: SystemTime) {
+        match self.offline[venue] {
+            Some(since) if fetched_at > since => {
+                self.offline[venue] = None;
+                self.toasts
+                    .push(Severity::Info, self.for_venue(venue, "Back online"));
+            }
+            Some(_) => return,
+            None => {}
+        }
+        self.session_used(venue);
+    }
+
+    /// Why nothing can be booked or cancelled at the venue right now, when it can't be
+    /// reached.
+    fn offline_notice(&self, venue: usize) -> Option<String> {
+        self.offline[venue]?;
+        Some(self.for_venue(
+            venue,
+            "Can't reach the venue, so booking and cancelling are off",
+        ))
+    }
+
+    /// Set running to false to quit the application.
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    /// Renders the user interface.
+    pub fn render(&mut self, frame: &mut ratatui::Frame) {
+        ui::render(self, frame);
+    }
+}
+
+/// Makes the booking, first looking for it at the venue when `recheck` says an earlier
+/// attempt may have made it after all.
+fn send_booking(
+    config: &VenueConfig,
+    request: &BookingRequest,
+    recheck: bool,
+) -> Result<CreatedBooking, SkeddaError> {
+    let skedda = Skedda::new(config);
+    if recheck {
+        if let Some(created) = skedda.find_booking(request)? {
+            return Ok(created);
+        }
     }
+    skedda.create_booking(request)
 }
@@ -0,0 +1,203 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::OnceLock,
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::warn;
+
+use crate::{
+    models::{BookingRequest, CreatedBooking},
+    skedda::SkeddaError,
+};
+
+/// Who acts when no one says otherwise, e.g. in tests.
+const DEFAULT_ACTOR: &str = "syres";
+
+static ACTOR: OnceLock<&'static str> = OnceLock::new();
+
+/// Names the part of syres whose actions are recorded this run, e.g. `tui` or `daemon`.
+pub fn init(actor: &'static str) {
+    let _ = ACTOR.set(actor);
+}
+
+/// A fresh id tying together the entries of one request, such as a batch of cancellations.
+pub fn request_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Ok,
+    Failed,
+}
+
+/// One line of the audit log: something syres did to a booking, or tried to.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    /// When it finished, in UTC.
+    pub at: String,
+    pub actor: &'static str,
+    /// The venue's key, as for its session and cache.
+    pub venue: String,
+    pub request_id: String,
+    pub action: Action,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking_id: Option<String>,
+    /// What was sent, for bookings made.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking: Option<BookingRequest>,
+    pub outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    fn new<T>(
+        action: Action,
+        venue: &str,
+        request_id: &str,
+        result: &Result<T, SkeddaError>,
+    ) -> Self {
+        Self {
+            at: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+            actor: ACTOR.get().copied().unwrap_or(DEFAULT_ACTOR),
+            venue: venue.to_string(),
+            request_id: request_id.to_string(),
+            action,
+            booking_id: None,
+            booking: None,
+            outcome: match result {
+                Ok(_) => Outcome::Ok,
+                Err(_) => Outcome::Failed,
+            },
+            error: result.as_ref().err().map(SkeddaError::to_string),
+        }
+    }
+
+    /// A booking made, or an attempt at one.
+    pub fn booking(
+        venue: &str,
+        request_id: &str,
+        request: &BookingRequest,
+        result: &Result<CreatedBooking, SkeddaError>,
+    ) -> Self {
+        Self {
+            booking_id: result.as_ref().ok().map(|created| created.id.clone()),
+            booking: Some(request.clone()),
+            ..Self::new(Action::Create, venue, request_id, result)
+        }
+    }
+
+    /// A booking cancelled, or an attempt at it.
+    pub fn cancellation(
+        venue: &str,
+        request_id: &str,
+        booking_id: &str,
+        result: &Result<(), SkeddaError>,
+    ) -> Self {
+        Self {
+            booking_id: Some(booking_id.to_string()),
+            ..Self::new(Action::Cancel, venue, request_id, result)
+        }
+    }
+}
+
+/// The audit log, one JSON object per line, only ever appended to.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Appends the entry. A log that can't be written is reported but never stops the action
+    /// it records.
+    pub fn record(&self, entry: &AuditEntry) {
+        if let Err(err) = self.append(entry) {
+            warn!(path = %self.path.display(), "failed to write audit log: {err:#}");
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(&line))
+            .with_context(|| format!("appending to {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn appends_one_line_per_action() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        let request = BookingRequest::new(
+            "1",
+            &["103".to_string()],
+            None,
+            datetime!(2024-05-01 14:00),
+            datetime!(2024-05-01 15:00),
+        );
+        let created = Ok(CreatedBooking {
+            id: "9005".to_string(),
+            price: None,
+        });
+        log.record(&AuditEntry::booking("example", "a1", &request, &created));
+        log.record(&AuditEntry::cancellation(
+            "example",
+            "b2",
+            "9005",
+            &Err(SkeddaError::Other(
+                "Failed to cancel booking 9005".to_string(),
+            )),
+        ));
+
+        let text = fs::read_to_string(&log.path).unwrap();
+        let entries: Vec<Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["action"], "create");
+        assert_eq!(entries[0]["outcome"], "ok");
+        assert_eq!(entries[0]["booking_id"], "9005");
+        assert_eq!(entries[0]["booking"]["start"], "2024-05-01T14:00:00");
+        assert_eq!(entries[0]["actor"], DEFAULT_ACTOR);
+        assert!(entries[0].get("error").is_none());
+        assert_eq!(entries[1]["action"], "cancel");
+        assert_eq!(entries[1]["outcome"], "failed");
+        assert_eq!(entries[1]["error"], "Failed to cancel booking 9005");
+        assert!(OffsetDateTime::parse(entries[1]["at"].as_str().unwrap(), &Rfc3339).is_ok());
+    }
+}
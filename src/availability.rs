@@ -0,0 +1,720 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use time::{Duration, PrimitiveDateTime};
+
+use crate::{
+    grid,
+    models::{Booking, WebsSummary},
+    rules::BookingRules,
+    skedda::MyBooking,
+};
+
+/// How many of a location's spaces are free for a window of time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationAvailability {
+    pub venue: usize,
+    pub location: String,
+    pub spaces: usize,
+    pub free: usize,
+    /// Free spaces of the kind being looked for, such as meeting rooms.
+    pub free_matching: usize,
+}
+
+/// Whether no booking of the space overlaps `start..end`.
+pub fn is_free(
+    bookings: &[Booking],
+    space_id: &str,
+    start: PrimitiveDateTime,
+    end: PrimitiveDateTime,
+) -> bool {
+    conflict(bookings, space_id, start, end).is_none()
+}
+
+/// The earliest booking of the space that overlaps `start..end`, if any does.
+pub fn conflict<'a>(
+    bookings: &'a [Booking],
+    space_id: &str,
+    start: PrimitiveDateTime,
+    end: PrimitiveDateTime,
+) -> Option<&'a Booking> {
+    bookings
+        .iter()
+        .filter(|booking| {
+            booking.start < end
+                && booking.end > start
+                && booking.space_ids.iter().any(|id| id == space_id)
+        })
+        .min_by_key(|booking| booking.start)
+}
+
+/// The stretches of `range` in which the space is free and a booking could start at `now`:
+/// between the space's bookings, no sooner than the rules' notice, starting no further ahead
+/// than they allow, and on the venue's booking increment in minutes when it has one.
+///
+/// The rules' longest booking doesn't cut a stretch short; a booking in it just can't run its
+/// whole length.
+pub fn free_slots(
+    space_id: &str,
+    range: Range<PrimitiveDateTime>,
+    bookings: &[Booking],
+    rules: &BookingRules,
+    increment: Option<u32>,
+    now: PrimitiveDateTime,
+) -> Vec<Range<PrimitiveDateTime>> {
+    let from = range
+        .start
+        .max(now + rules.min_notice.unwrap_or(Duration::ZERO));
+    let latest_start = rules.max_advance.map(|advance| now + advance);
+    let mut taken: Vec<(PrimitiveDateTime, PrimitiveDateTime)> = bookings
+        .iter()
+        .filter(|booking| {
+            booking.start < range.end
+                && booking.end > from
+                && booking.space_ids.iter().any(|id| id == space_id)
+        })
+        .map(|booking| (booking.start, booking.end))
+        .collect();
+    taken.sort();
+
+    let mut gaps = Vec::new();
+    let mut cursor = from;
+    for (start, end) in taken {
+        if start > cursor {
+            gaps.push(cursor..start);
+        }
+        cursor = cursor.max(end);
+    }
+    if cursor < range.end {
+        gaps.push(cursor..range.end);
+    }
+
+    let step = increment
+        .filter(|&minutes| minutes > 0)
+        .map(|minutes| Duration::minutes(minutes.into()));
+    gaps.into_iter()
+        .filter_map(|gap| {
+            let (start, end) = match step {
+                Some(step) => (align(gap.start, step, true), align(gap.end, step, false)),
+                None => (gap.start, gap.end),
+            };
+            (start < end && latest_start.is_none_or(|latest| start <= latest)).then_some(start..end)
+        })
+        .collect()
+}
+
+/// `time` moved onto the last multiple of `step` since midnight at or before it, or with `up`
+/// the first at or after it.
+fn align(time: PrimitiveDateTime, step: Duration, up: bool) -> PrimitiveDateTime {
+    let midnight = time.date().midnight();
+    let since = time - midnight;
+    let steps = since.whole_seconds() / step.whole_seconds();
+    let down = midnight + step * steps as i32;
+    if up && down < time { down + step } else { down }
+}
+
+/// The first of my own bookings, at any venue, that overlaps `start..end`: booking it would
+/// put me in two places at once.
+pub fn my_clash(
+    my_bookings: &[Vec<MyBooking>],
+    start: PrimitiveDateTime,
+    end: PrimitiveDateTime,
+) -> Option<(usize, &MyBooking)> {
+    my_bookings
+        .iter()
+        .enumerate()
+        .flat_map(|(venue, bookings)| bookings.iter().map(move |booking| (venue, booking)))
+        .filter(|(_, booking)| booking.start < end && booking.end > start)
+        .min_by_key(|(_, booking)| booking.start)
+}
+
+/// Whether a space is a room for meetings rather than a desk or office, going by its type
+/// or, when the venue doesn't set types, its name.
+pub fn is_meeting_room(label: &str) -> bool {
+    let label = label.to_ascii_lowercase();
+    ["conference", "meeting", "board"]
+        .iter()
+        .any(|word| label.contains(word))
+}
+
+/// Whether a space's type, or its name when it has none, mentions `kind`. An empty `kind`
+/// matches every space.
+pub fn is_kind(label: &str, kind: &str) -> bool {
+    label
+        .to_ascii_lowercase()
+        .contains(&kind.to_ascii_lowercase())
+}
+
+/// Availability of each of the venue's locations for all of `start..end`, from one `/webs`
+/// response, counting the free spaces whose type or name `matches` picks out separately.
+pub fn overview(
+    venue: usize,
+    locations: &[String],
+    webs_data: &WebsSummary,
+    (start, end): (PrimitiveDateTime, PrimitiveDateTime),
+    matches: impl Fn(&str) -> bool,
+) -> Vec<LocationAvailability> {
+    let labels: HashMap<&str, &str> = webs_data
+        .spaces
+        .iter()
+        .map(|space| (space.id.as_ref(), space.label()))
+        .collect();
+    let is_free = |space_id: &str| {
+        !webs_data.bookings.iter().any(|booking| {
+            booking.start < end
+                && booking.end > start
+                && booking.space_ids.iter().any(|id| id == space_id)
+        })
+    };
+    locations
+        .iter()
+        .map(|location| {
+            let space_ids: Vec<&str> = webs_data
+                .location_space_ids(location)
+                .iter()
+                .map(|space_id| space_id.as_ref())
+                .filter(|space_id| labels.contains_key(space_id))
+                .collect();
+            let free: Vec<&str> = space_ids
+                .iter()
+                .copied()
+                .filter(|space_id| is_free(space_id))
+                .collect();
+            LocationAvailability {
+                venue,
+                location: location.clone(),
+                spaces: space_ids.len(),
+                free: free.len(),
+                free_matching: free
+                    .iter()
+                    .filter(|space_id| matches(labels[*space_id]))
+                    .count(),
+            }
+        })
+        .collect()
+}
+
+/// How many other locations a taken space's alternatives name.
+const ALTERNATIVES_SHOWN: usize = 3;
+
+/// The other locations with a space like a taken one free for the same window, gathered as
+/// the venues in view are surveyed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternatives {
+    /// The venue and location whose space is taken.
+    pub venue: usize,
+    pub location: String,
+    pub window: (PrimitiveDateTime, PrimitiveDateTime),
+    /// What kind of space is looked for, as the venue calls it.
+    pub kind: String,
+    /// Why the space can't be booked.
+    pub reason: String,
+    /// Locations with such a space free, the most free first and the taken space's venue
+    /// ahead of others.
+    pub locations: Vec<LocationAvailability>,
+    /// Venues still being surveyed.
+    pub pending: HashSet<usize>,
+}
+
+impl Alternatives {
+    /// Takes in the survey of one venue's locations, keeping those with a matching space free
+    /// other than the taken space's own.
+    pub fn add(&mut self, venue: usize, locations: Vec<LocationAvailability>) {
+        self.pending.remove(&venue);
+        let (home, location) = (self.venue, &self.location);
+        self.locations.extend(locations.into_iter().filter(|row| {
+            row.free_matching > 0
+                && !(row.venue == home && row.location.eq_ignore_ascii_case(location))
+        }));
+        self.locations
+            .sort_by_key(|row| (Reverse(row.free_matching), row.venue != home, row.venue));
+    }
+
+    /// "Boardroom is booked 09:00–10:00 · Conference room free 09:00–10:00 at Midtown (2),
+    /// Old Fourth Ward (1)", naming the first few locations, or saying they're still being
+    /// looked at or that there are none.
+    pub fn describe(&self) -> String {
+        let (start, end) = self.window;
+        let window = format!(
+            "{}–{}",
+            grid::format_time(start.time()),
+            grid::format_time(end.time())
+        );
+        if self.locations.is_empty() {
+            return if self.pending.is_empty() {
+                format!("{} · No {} free {window} elsewhere", self.reason, self.kind)
+            } else {
+                format!("{} · Looking at other locations…", self.reason)
+            };
+        }
+        let mut kind = self.kind.clone();
+        if let Some(first) = kind.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        let shown: Vec<String> = self
+            .locations
+            .iter()
+            .take(ALTERNATIVES_SHOWN)
+            .map(|row| format!("{} ({})", row.location, row.free_matching))
+            .collect();
+        let more = match self.locations.len().saturating_sub(ALTERNATIVES_SHOWN) {
+            0 => String::new(),
+            more => format!(" and {more} more"),
+        };
+        format!(
+            "{} · {kind} free {window} at {}{more}",
+            self.reason,
+            shown.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{
+        Time,
+        macros::{datetime, time},
+    };
+
+    use super::*;
+    use crate::fixtures;
+
+    fn locations(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn counts_free_spaces_in_the_small_venue() {
+        let body = fixtures::webs("small_venue.json");
+        let webs = WebsSummary::parse(&body).unwrap();
+        let window = (datetime!(2024-05-01 09:30), datetime!(2024-05-01 10:00));
+
+        let result = overview(
+            0,
+            &locations(&["Decatur", "Midtown"]),
+            &webs,
+            window,
+            is_meeting_room,
+        );
+        let counts: Vec<(&str, usize, usize, usize)> = result
+            .iter()
+            .map(|row| {
+                (
+                    row.location.as_str(),
+                    row.spaces,
+                    row.free,
+                    row.free_matching,
+                )
+            })
+            .collect();
+        // Desk 1 and the boardroom are taken; Midtown is free until the afternoon workshop.
+        assert_eq!(counts, [("Decatur", 4, 2, 0), ("Midtown", 2, 2, 1)]);
+    }
+
+    #[test]
+    fn ranks_other_locations_with_the_kind_free() {
+        let row = |venue, location: &str, free_matching| LocationAvailability {
+            venue,
+            location: location.to_string(),
+            spaces: 4,
+            free: free_matching + 1,
+            free_matching,
+        };
+        let mut alternatives = Alternatives {
+            venue: 0,
+            location: "Decatur".to_string(),
+            window: (datetime!(2024-05-01 9:00), datetime!(2024-05-01 10:00)),
+            kind: "conference room".to_string(),
+            reason: "Boardroom is booked 09:00–10:00".to_string(),
+            locations: Vec::new(),
+            pending: HashSet::from([0, 1]),
+        };
+        assert_eq!(
+            alternatives.describe(),
+            "Boardroom is booked 09:00–10:00 · Looking at other locations…"
+        );
+
+        // The taken space's own location and those with nothing like it free are left out.
+        alternatives.add(1, vec![row(1, "Buckhead", 1), row(1, "Westside", 0)]);
+        alternatives.add(
+            0,
+            vec![
+                row(0, "Decatur", 2),
+                row(0, "Midtown", 1),
+                row(0, "Old Fourth Ward", 2),
+                row(0, "Inman Park", 1),
+            ],
+        );
+        assert!(alternatives.pending.is_empty());
+        assert_eq!(
+            alternatives.describe(),
+            "Boardroom is booked 09:00–10:00 · Conference room free 09:00–10:00 at \
+             Old Fourth Ward (2), Midtown (1), Inman Park (1) and 1 more"
+        );
+
+        alternatives.locations.clear();
+        assert_eq!(
+            alternatives.describe(),
+            "Boardroom is booked 09:00–10:00 · No conference room free 09:00–10:00 elsewhere"
+        );
+    }
+
+    #[test]
+    fn multi_space_bookings_block_every_space() {
+        let body = fixtures::webs("small_venue.json");
+        let webs = WebsSummary::parse(&body).unwrap();
+        let window = (datetime!(2024-05-01 14:00), datetime!(2024-05-01 15:00));
+
+        let result = overview(0, &locations(&["Midtown"]), &webs, window, |_| true);
+        assert_eq!((result[0].free, result[0].free_matching), (0, 0));
+    }
+
+    #[test]
+    fn finds_the_booking_in_the_way() {
+        let webs = fixtures::webs_response("small_venue.json");
+        let booking = conflict(
+            &webs.bookings,
+            "103",
+            datetime!(2024-05-01 8:30),
+            datetime!(2024-05-01 11:00),
+        )
+        .unwrap();
+        assert_eq!(booking.start, datetime!(2024-05-01 9:00));
+        assert!(
+            conflict(
+                &webs.bookings,
+                "102",
+                datetime!(2024-05-01 8:30),
+                datetime!(2024-05-01 11:00)
+            )
+            .is_none()
+        );
+    }
+
+    fn booking(space: &str, start: PrimitiveDateTime, end: PrimitiveDateTime) -> Booking {
+        Booking {
+            id: None,
+            holder: None,
+            venueuser: None,
+            title: None,
+            space_ids: vec![space.to_string()],
+            start,
+            end,
+        }
+    }
+
+    /// A day's worth of free stretches, asked for well before the day.
+    fn day_slots(bookings: &[Booking], increment: Option<u32>) -> Vec<(Time, Time)> {
+        free_slots(
+            "101",
+            datetime!(2024-05-01 8:00)..datetime!(2024-05-01 18:00),
+            bookings,
+            &BookingRules::default(),
+            increment,
+            datetime!(2024-04-30 12:00),
+        )
+        .into_iter()
+        .map(|slot| (slot.start.time(), slot.end.time()))
+        .collect()
+    }
+
+    #[test]
+    fn free_slots_are_the_gaps_between_bookings() {
+        let bookings = [
+            booking(
+                "101",
+                datetime!(2024-05-01 9:00),
+                datetime!(2024-05-01 10:00),
+            ),
+            booking(
+                "101",
+                datetime!(2024-05-01 13:00),
+                datetime!(2024-05-01 14:30),
+            ),
+            booking(
+                "102",
+                datetime!(2024-05-01 10:00),
+                datetime!(2024-05-01 13:00),
+            ),
+        ];
+        assert_eq!(
+            day_slots(&bookings, None),
+            [
+                (time!(8:00), time!(9:00)),
+                (time!(10:00), time!(13:00)),
+                (time!(14:30), time!(18:00)),
+            ]
+        );
+    }
+
+    #[test]
+    fn free_slots_merge_overlapping_and_outlying_bookings() {
+        let bookings = [
+            booking(
+                "101",
+                datetime!(2024-05-01 7:00),
+                datetime!(2024-05-01 9:00),
+            ),
+            booking(
+                "101",
+                datetime!(2024-05-01 12:00),
+                datetime!(2024-05-01 14:00),
+            ),
+            booking(
+                "101",
+                datetime!(2024-05-01 11:00),
+                datetime!(2024-05-01 13:00),
+            ),
+            booking(
+                "101",
+                datetime!(2024-05-01 17:00),
+                datetime!(2024-05-01 20:00),
+            ),
+        ];
+        assert_eq!(
+            day_slots(&bookings, None),
+            [(time!(9:00), time!(11:00)), (time!(14:00), time!(17:00)),]
+        );
+    }
+
+    #[test]
+    fn a_fully_booked_range_has_no_free_slots() {
+        let bookings = [booking(
+            "101",
+            datetime!(2024-05-01 0:00),
+            datetime!(2024-05-02 0:00),
+        )];
+        assert!(day_slots(&bookings, None).is_empty());
+    }
+
+    #[test]
+    fn free_slots_keep_to_the_booking_increment() {
+        let bookings = [
+            booking(
+                "101",
+                datetime!(2024-05-01 9:10),
+                datetime!(2024-05-01 9:50),
+            ),
+            booking(
+                "101",
+                datetime!(2024-05-01 10:10),
+                datetime!(2024-05-01 10:20),
+            ),
+        ];
+        // 9:50-10:10 holds no whole half hour.
+        assert_eq!(
+            day_slots(&bookings, Some(30)),
+            [(time!(8:00), time!(9:00)), (time!(10:30), time!(18:00)),]
+        );
+    }
+
+    #[test]
+    fn free_slots_respect_notice_and_how_far_ahead() {
+        let rules = BookingRules::from_minutes(Some(60), Some(120), Some(24 * 60));
+        let now = datetime!(2024-05-01 9:20);
+        let slots =
+            |range: Range<PrimitiveDateTime>| free_slots("101", range, &[], &rules, Some(15), now);
+        // Two hours' notice, on the quarter hour.
+        assert_eq!(
+            slots(datetime!(2024-05-01 8:00)..datetime!(2024-05-01 18:00)),
+            [datetime!(2024-05-01 11:30)..datetime!(2024-05-01 18:00)]
+        );
+        // A day ahead at most: tomorrow afternoon is out of reach.
+        assert!(slots(datetime!(2024-05-02 13:00)..datetime!(2024-05-02 18:00)).is_empty());
+        assert_eq!(
+            slots(datetime!(2024-05-02 8:00)..datetime!(2024-05-02 18:00)),
+            [datetime!(2024-05-02 8:00)..datetime!(2024-05-02 18:00)]
+        );
+    }
+
+    #[test]
+    fn kind_matching_uses_type_then_name() {
+        let body = fixtures::webs("large_venue.json");
+        let webs = WebsSummary::parse(&body).unwrap();
+        // Before anything is booked on the first day.
+        let window = (datetime!(2024-05-01 06:00), datetime!(2024-05-01 07:00));
+
+        let result = overview(0, &locations(&["Decatur"]), &webs, window, |label| {
+            is_kind(label, "phone")
+        });
+        assert_eq!(
+            (result[0].spaces, result[0].free, result[0].free_matching),
+            (15, 15, 2)
+        );
+    }
+
+    #[test]
+    fn venue_without_tags_has_empty_locations() {
+        let body = fixtures::webs("empty_space_tags.json");
+        let webs = WebsSummary::parse(&body).unwrap();
+        let window = (datetime!(2024-05-01 09:00), datetime!(2024-05-01 10:00));
+
+        let result = overview(3, &locations(&["Decatur"]), &webs, window, |_| true);
+        assert_eq!(
+            result,
+            [LocationAvailability {
+                venue: 3,
+                location: "Decatur".to_string(),
+                spaces: 0,
+                free: 0,
+                free_matching: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn clashes_with_my_bookings_at_any_venue() {
+        let mine = |space: &str, start, end| MyBooking {
+            id: None,
+            space: space.to_string(),
+            location: None,
+            start,
+            end,
+            title: None,
+        };
+        let my_bookings = [
+            vec![mine(
+                "Desk 1",
+                datetime!(2024-05-01 13:00),
+                datetime!(2024-05-01 17:00),
+            )],
+            vec![mine(
+                "Boardroom",
+                datetime!(2024-05-01 10:00),
+                datetime!(2024-05-01 11:00),
+            )],
+        ];
+        let clash = |start, end| {
+            my_clash(&my_bookings, start, end).map(|(venue, booking)| (venue, &booking.space))
+        };
+        assert_eq!(
+            clash(datetime!(2024-05-01 9:00), datetime!(2024-05-01 14:00)),
+            Some((1, &"Boardroom".to_string()))
+        );
+        assert_eq!(
+            clash(datetime!(2024-05-01 16:30), datetime!(2024-05-01 18:00)),
+            Some((0, &"Desk 1".to_string()))
+        );
+        assert_eq!(
+            clash(datetime!(2024-05-01 11:00), datetime!(2024-05-01 13:00)),
+            None
+        );
+    }
+
+    #[test]
+    fn is_free_treats_touching_bookings_as_free() {
+        let webs = fixtures::webs_response("small_venue.json");
+        let start = datetime!(2024-05-01 10:00);
+        assert!(is_free(
+            &webs.bookings,
+            "103",
+            start,
+            datetime!(2024-05-01 11:00)
+        ));
+        assert!(!is_free(
+            &webs.bookings,
+            "103",
+            start - time::Duration::MINUTE,
+            start
+        ));
+    }
+}
+
+#[cfg(test)]
+mod properties {
+    use proptest::prelude::*;
+    use time::{Duration, macros::datetime};
+
+    use super::*;
+
+    /// Up to 40 bookings on one day, on the quarter hour, each of one or two of `spaces`.
+    fn any_bookings(spaces: Vec<String>) -> impl Strategy<Value = Vec<Booking>> {
+        let booking = (
+            0..24 * 4i64,
+            1..=16i64,
+            prop::collection::vec(prop::sample::select(spaces), 1..=2),
+        );
+        prop::collection::vec(booking, 0..40).prop_map(|bookings| {
+            bookings
+                .into_iter()
+                .map(|(quarter, quarters, space_ids)| {
+                    let start = datetime!(2024-05-01 00:00) + Duration::minutes(quarter * 15);
+                    Booking {
+                        id: None,
+                        holder: None,
+                        venueuser: None,
+                        title: None,
+                        space_ids,
+                        start,
+                        end: start + Duration::minutes(quarters * 15),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// A window starting within `days` of the fixtures' first day, up to `longest` minutes long.
+    fn any_window(
+        days: i64,
+        longest: i64,
+    ) -> impl Strategy<Value = (PrimitiveDateTime, PrimitiveDateTime)> {
+        (0..days * 24 * 60, 1..=longest).prop_map(|(from, minutes)| {
+            let start = datetime!(2024-05-01 00:00) + Duration::minutes(from);
+            (start, start + Duration::minutes(minutes))
+        })
+    }
+
+    fn spaces() -> Vec<String> {
+        (1..=5).map(|id| id.to_string()).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn a_space_is_free_exactly_when_no_booking_overlaps(
+            bookings in any_bookings(spaces()),
+            (start, end) in any_window(1, 240),
+        ) {
+            for space in &spaces() {
+                let overlapping = bookings.iter().filter(|booking| {
+                    booking.space_ids.contains(space) && booking.start < end && start < booking.end
+                });
+                prop_assert_eq!(
+                    is_free(&bookings, space, start, end),
+                    overlapping.count() == 0
+                );
+                // Shrinking the window can only free a space up.
+                if is_free(&bookings, space, start, end) {
+                    let middle: PrimitiveDateTime = start + (end - start) / 2;
+                    prop_assert!(is_free(
+                        &bookings,
+                        space,
+                        start,
+                        middle.max(start + Duration::MINUTE)
+                    ));
+                }
+            }
+        }
+
+        #[test]
+        fn overview_counts_are_nested((start, end) in any_window(7, 8 * 60)) {
+            let body = crate::fixtures::webs("large_venue.json");
+            let webs = WebsSummary::parse(&body).unwrap();
+            let locations: Vec<String> = webs.venue[0]
+                .space_presentation
+                .space_tags
+                .iter()
+                .map(|tag| tag.name.to_string())
+                .collect();
+            for row in overview(0, &locations, &webs, (start, end), is_meeting_room) {
+                prop_assert!(
+                    row.free_matching <= row.free && row.free <= row.spaces,
+                    "{:?}",
+                    row
+                );
+            }
+        }
+    }
+}
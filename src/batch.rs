@@ -0,0 +1,239 @@
+use crate::models::{BookingRequest, CreatedBooking};
+
+/// Where one booking of a batch stands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemState {
+    /// Waiting to be sent, or on its way.
+    Pending,
+    /// Left out before sending, with the reason.
+    Skipped(String),
+    Booked(CreatedBooking),
+    Failed(String),
+    /// Booked, then cancelled to roll the batch back.
+    RolledBack,
+}
+
+/// One booking of a batch: a space for a window on one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchItem {
+    pub request: BookingRequest,
+    /// The space's name, for showing.
+    pub space: String,
+    pub state: ItemState,
+    /// Why cancelling it to roll the batch back didn't work.
+    pub rollback_error: Option<String>,
+}
+
+/// Several bookings made together from the booking form, sent one at a time so each can
+/// succeed or fail on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch {
+    pub venue: usize,
+    pub items: Vec<BatchItem>,
+    /// Whether a roll back is under way.
+    pub rolling_back: bool,
+}
+
+/// How many of a batch's bookings ended each way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub booked: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub rolled_back: usize,
+    pub pending: usize,
+}
+
+impl Batch {
+    /// A batch of the requests, each with the reason to skip it, if there is one.
+    pub fn new(venue: usize, requests: Vec<(BookingRequest, String, Option<String>)>) -> Self {
+        let items = requests
+            .into_iter()
+            .map(|(request, space, skip)| BatchItem {
+                request,
+                space,
+                state: skip.map_or(ItemState::Pending, ItemState::Skipped),
+                rollback_error: None,
+            })
+            .collect();
+        Self {
+            venue,
+            items,
+            rolling_back: false,
+        }
+    }
+
+    /// The bookings still to send, by index.
+    pub fn pending(&self) -> Vec<(usize, BookingRequest)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.state == ItemState::Pending)
+            .map(|(index, item)| (index, item.request.clone()))
+            .collect()
+    }
+
+    /// Records how sending the booking at `index` went.
+    pub fn settle(&mut self, index: usize, result: Result<CreatedBooking, String>) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.state = match result {
+                Ok(created) => ItemState::Booked(created),
+                Err(err) => ItemState::Failed(err),
+            };
+        }
+    }
+
+    /// Puts the failed bookings back to be sent again, returning them.
+    pub fn retry_failed(&mut self) -> Vec<(usize, BookingRequest)> {
+        for item in &mut self.items {
+            if matches!(item.state, ItemState::Failed(_)) {
+                item.state = ItemState::Pending;
+            }
+        }
+        self.pending()
+    }
+
+    /// The ids of the bookings made, to cancel when rolling back.
+    pub fn booked_ids(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter_map(|item| match &item.state {
+                ItemState::Booked(created) => Some(created.id.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Records the outcome of cancelling each booking, by id, when rolling back.
+    pub fn rolled_back(&mut self, results: &[(String, Result<(), String>)]) {
+        self.rolling_back = false;
+        for item in &mut self.items {
+            let ItemState::Booked(created) = &item.state else {
+                continue;
+            };
+            match results.iter().find(|(id, _)| *id == created.id) {
+                Some((_, Ok(()))) => {
+                    item.state = ItemState::RolledBack;
+                    item.rollback_error = None;
+                }
+                Some((_, Err(err))) => item.rollback_error = Some(err.clone()),
+                None => {}
+            }
+        }
+    }
+
+    pub fn tally(&self) -> Tally {
+        let mut tally = Tally::default();
+        for item in &self.items {
+            match item.state {
+                ItemState::Pending => tally.pending += 1,
+                ItemState::Skipped(_) => tally.skipped += 1,
+                ItemState::Booked(_) => tally.booked += 1,
+                ItemState::Failed(_) => tally.failed += 1,
+                ItemState::RolledBack => tally.rolled_back += 1,
+            }
+        }
+        tally
+    }
+
+    /// Whether nothing is on its way to Skedda.
+    pub fn is_settled(&self) -> bool {
+        !self.rolling_back && self.tally().pending == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn batch() -> Batch {
+        let request = |space: &str| {
+            BookingRequest::new(
+                "1",
+                &[space.to_string()],
+                None,
+                datetime!(2024-05-02 9:00),
+                datetime!(2024-05-02 10:00),
+            )
+        };
+        Batch::new(
+            0,
+            vec![
+                (request("101"), "Desk 1".to_string(), None),
+                (request("102"), "Desk 2".to_string(), None),
+                (
+                    request("103"),
+                    "Boardroom".to_string(),
+                    Some("Already booked".to_string()),
+                ),
+            ],
+        )
+    }
+
+    fn created(id: &str) -> CreatedBooking {
+        CreatedBooking {
+            id: id.to_string(),
+            price: None,
+        }
+    }
+
+    #[test]
+    fn sends_all_but_the_skipped() {
+        let mut batch = batch();
+        let pending: Vec<usize> = batch
+            .pending()
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(pending, [0, 1]);
+        assert!(!batch.is_settled());
+
+        batch.settle(0, Ok(created("9001")));
+        batch.settle(1, Err("Skedda refused the booking".to_string()));
+        assert!(batch.is_settled());
+        assert_eq!(
+            batch.tally(),
+            Tally {
+                booked: 1,
+                failed: 1,
+                skipped: 1,
+                ..Tally::default()
+            }
+        );
+    }
+
+    #[test]
+    fn retries_only_failures() {
+        let mut batch = batch();
+        batch.settle(0, Ok(created("9001")));
+        batch.settle(1, Err("timed out".to_string()));
+        let retried: Vec<usize> = batch
+            .retry_failed()
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(retried, [1]);
+        assert_eq!(batch.items[1].state, ItemState::Pending);
+        assert!(matches!(batch.items[2].state, ItemState::Skipped(_)));
+    }
+
+    #[test]
+    fn rolls_back_what_was_booked() {
+        let mut batch = batch();
+        batch.settle(0, Ok(created("9001")));
+        batch.settle(1, Ok(created("9002")));
+        assert_eq!(batch.booked_ids(), ["9001", "9002"]);
+
+        batch.rolling_back = true;
+        batch.rolled_back(&[
+            ("9001".to_string(), Ok(())),
+            ("9002".to_string(), Err("not found".to_string())),
+        ]);
+        assert!(batch.is_settled());
+        assert_eq!(batch.items[0].state, ItemState::RolledBack);
+        assert_eq!(batch.items[1].rollback_error.as_deref(), Some("not found"));
+        assert_eq!(batch.booked_ids(), ["9002"]);
+    }
+}
@@ -0,0 +1,203 @@
+use color_eyre::eyre::{bail, eyre};
+use serde::Serialize;
+use time::{
+    PrimitiveDateTime, Time, format_description::BorrowedFormatItem, macros::format_description,
+};
+
+use tracing::warn;
+
+use crate::{
+    availability, clock,
+    config::{Config, NotifyEvent},
+    grid,
+    hooks::{self, HookBooking, HookEvent},
+    models::BookingRequest,
+    notify::{self, Message},
+    skedda::Skedda,
+};
+
+/// Times in the JSON output, as "2025-08-01T09:00".
+const TIME: &[BorrowedFormatItem] = format_description!("[year]-[month]-[day]T[hour]:[minute]");
+const DAY: &[BorrowedFormatItem] =
+    format_description!("[weekday repr:short] [day padding:none] [month repr:short] [year]");
+
+/// What `syres book` was asked to book.
+#[derive(Debug, Clone)]
+pub struct BookArgs {
+    pub location: String,
+    pub venue: Option<String>,
+    pub space: Option<String>,
+    pub date: String,
+    pub from: String,
+    pub till: Option<String>,
+    pub json: bool,
+}
+
+/// A booking made, as printed with `--json`.
+#[derive(Debug, Serialize)]
+struct Booked<'a> {
+    id: &'a str,
+    venue: &'a str,
+    location: &'a str,
+    space: &'a str,
+    start: String,
+    end: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    price: Option<f64>,
+}
+
+/// Books a space at the location without the TUI: the named space, or else the first one
+/// free for the whole time. Prints the booking, or fails saying why it couldn't be made.
+pub fn run(config: &Config, args: &BookArgs) -> color_eyre::Result<()> {
+    let venue = match &args.venue {
+        Some(name) => config
+            .venue(name)
+            .ok_or_else(|| eyre!("no venue named {name:?} in config"))?,
+        None => config
+            .find_location(&args.location)
+            .map(|(venue, _)| &config.venues[venue])
+            .ok_or_else(|| {
+                eyre!(
+                    "no venue in config has a location named {:?}",
+                    args.location
+                )
+            })?,
+    };
+    let location = venue
+        .locations
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(&args.location))
+        .ok_or_else(|| eyre!("{} has no location named {:?}", venue.name, args.location))?;
+
+    let now = clock::now();
+    let date =
+        grid::read_date(&args.date, now.date()).map_err(|reason| eyre!("--date: {reason}"))?;
+    let (from, till) = times(config, &args.from, args.till.as_deref())?;
+    let (start, end) = (
+        PrimitiveDateTime::new(date, from),
+        PrimitiveDateTime::new(date, till),
+    );
+    if end <= start {
+        bail!(
+            "the booking ends at {} before it starts at {}",
+            grid::format_time(till),
+            grid::format_time(from)
+        );
+    }
+    if start < now {
+        bail!("{} has already started", window(start, end));
+    }
+
+    let skedda = Skedda::new(venue);
+    let webs = skedda.get_booking_data()?;
+    let venue_id = webs
+        .venue
+        .first()
+        .and_then(|venue| venue.id.clone())
+        .ok_or_else(|| eyre!("the venue data doesn't say which venue this is"))?;
+    let spaces = Skedda::space_details(&webs);
+    let bookings = Skedda::bookings(&webs);
+    let space_ids = Skedda::location_space_ids(&webs, location);
+    let space_id = match &args.space {
+        Some(name) => {
+            let space_id = space_ids
+                .iter()
+                .find(|id| {
+                    spaces
+                        .get(*id)
+                        .is_some_and(|space| space.name.eq_ignore_ascii_case(name))
+                })
+                .ok_or_else(|| eyre!("{location} has no space named {name:?}"))?;
+            if let Some(booking) = availability::conflict(&bookings, space_id, start, end) {
+                bail!(
+                    "{name} is booked {}–{}",
+                    grid::format_time(booking.start.time()),
+                    grid::format_time(booking.end.time())
+                );
+            }
+            space_id
+        }
+        None => space_ids
+            .iter()
+            .find(|id| availability::is_free(&bookings, id, start, end))
+            .ok_or_else(|| eyre!("nothing at {location} is free {}", window(start, end)))?,
+    };
+    let space = &spaces[space_id];
+    space
+        .check(start, end, now)
+        .map_err(|reason| eyre!(reason))?;
+
+    let request = BookingRequest::new(&venue_id, std::slice::from_ref(space_id), None, start, end);
+    let created = skedda
+        .create_booking(&request)
+        .map_err(|err| eyre!("{err:#}"))?;
+    if args.json {
+        let booked = Booked {
+            id: &created.id,
+            venue: &venue.name,
+            location,
+            space: &space.name,
+            start: start.format(TIME)?,
+            end: end.format(TIME)?,
+            price: created.price,
+        };
+        println!("{}", serde_json::to_string(&booked)?);
+    } else {
+        println!(
+            "Booked {} at {location}, {} (booking {})",
+            space.name,
+            window(start, end),
+            created.id
+        );
+    }
+    // The booking stands whether or not anyone hears about it.
+    let message = Message::booked(&space.name, location, start, end);
+    if let Err(err) = notify::notify(&config.notify, NotifyEvent::Booked, &message) {
+        warn!("{err:#}");
+    }
+    let booking = HookBooking {
+        id: created.id,
+        venue: venue.name.clone(),
+        location: Some(location.clone()),
+        space: space.name.clone(),
+        start,
+        end,
+        title: None,
+    };
+    if let Err(err) = hooks::run(&config.hooks, HookEvent::Booked, &booking) {
+        warn!("{err:#}");
+    }
+    Ok(())
+}
+
+/// The start and end times asked for. `from` can name a window from config, which also gives
+/// the end unless `till` is set.
+fn times(config: &Config, from: &str, till: Option<&str>) -> color_eyre::Result<(Time, Time)> {
+    let named = config.window(from);
+    let start = match named {
+        Some((start, _)) => start,
+        None => config
+            .start_time(from)
+            .map_err(|reason| eyre!("--from {from:?}: {reason}"))?,
+    };
+    let end = match (till, named) {
+        (Some(till), _) => {
+            grid::read_time(till).map_err(|reason| eyre!("--till {till:?}: {reason}"))?
+        }
+        (None, Some((_, end))) => end,
+        (None, None) => bail!("--till is needed unless --from names a window from config"),
+    };
+    Ok((start, end))
+}
+
+/// A booking's day and times, e.g. "Fri 1 Aug 2025 09:00–12:00".
+fn window(start: PrimitiveDateTime, end: PrimitiveDateTime) -> String {
+    format!(
+        "{} {}–{}",
+        start
+            .format(DAY)
+            .unwrap_or_else(|_| start.date().to_string()),
+        grid::format_time(start.time()),
+        grid::format_time(end.time())
+    )
+}
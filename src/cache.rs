@@ -0,0 +1,307 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+use clap::ValueEnum;
+use color_eyre::eyre::{WrapErr, eyre};
+use tracing::{debug, warn};
+
+use crate::{
+    config::{CacheConfig, VenueConfig},
+    paths::Paths,
+};
+
+static CONFIG: OnceLock<CacheConfig> = OnceLock::new();
+
+/// `/webs` bodies by venue key, with when they were fetched.
+type VenueData = HashMap<String, (SystemTime, Arc<[u8]>)>;
+
+static VENUE_DATA: OnceLock<Mutex<VenueData>> = OnceLock::new();
+
+/// Sets how long venue data is reused and whether it's kept on disk. Call once at startup.
+pub fn init(config: &CacheConfig) {
+    let _ = CONFIG.set(*config);
+}
+
+/// The venue's last `/webs` body and when it was fetched, if that was recently enough to
+/// reuse. Data kept on disk under `paths` by an earlier run counts when `on_disk` is set.
+pub fn venue_data(paths: Option<&Paths>, venue_key: &str) -> Option<(Arc<[u8]>, SystemTime)> {
+    let config = CONFIG.get_or_init(CacheConfig::default);
+    let ttl = Duration::from_secs(config.venue_data_ttl_secs);
+    if let Some(fresh) = fresh_in_memory(venue_key, ttl) {
+        return Some(fresh);
+    }
+    if !config.on_disk {
+        return None;
+    }
+    let path = paths?.venue_cache(venue_key);
+    let fetched_at = fs::metadata(&path).ok()?.modified().ok()?;
+    if !is_fresh(fetched_at, ttl) {
+        return None;
+    }
+    let body: Arc<[u8]> = fs::read(&path).ok()?.into();
+    debug!(venue = venue_key, "using venue data cached on disk");
+    memory()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(venue_key.to_string(), (fetched_at, Arc::clone(&body)));
+    Some((body, fetched_at))
+}
+
+/// Keeps a `/webs` body just fetched for the venue, on disk under `paths` too when the config
+/// says so.
+pub fn keep_venue_data(paths: Option<&Paths>, venue_key: &str, body: &[u8]) {
+    memory()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(venue_key.to_string(), (SystemTime::now(), body.into()));
+    let (Some(config), Some(paths)) = (CONFIG.get(), paths) else {
+        return;
+    };
+    if config.on_disk {
+        write(&paths.venue_cache(venue_key), body, "venue data");
+    }
+    if config.offline {
+        write(&paths.offline_data(venue_key), body, "offline venue data");
+    }
+}
+
+/// The last `/webs` body kept on disk for the venue, however old, and when it was fetched:
+/// what's left to show while the venue can't be reached.
+pub fn last_known_venue_data(paths: &Paths, venue_key: &str) -> Option<(Arc<[u8]>, SystemTime)> {
+    let path = paths.offline_data(venue_key);
+    let fetched_at = fs::metadata(&path).ok()?.modified().ok()?;
+    let body: Arc<[u8]> = fs::read(&path).ok()?.into();
+    Some((body, fetched_at))
+}
+
+fn write(path: &Path, body: &[u8], what: &str) {
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, body));
+    if let Err(err) = written {
+        warn!(path = %path.display(), "failed to cache {what}: {err}");
+    }
+}
+
+/// Drops the venue's kept data, once a booking or cancellation has made its bookings out of
+/// date. The copy kept for when it can't be reached stays until the next fetch replaces it.
+pub fn forget_venue_data(paths: Option<&Paths>, venue_key: &str) {
+    memory()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(venue_key);
+    if let Some(paths) = paths {
+        let _ = fs::remove_file(paths.venue_cache(venue_key));
+    }
+}
+
+fn fresh_in_memory(venue_key: &str, ttl: Duration) -> Option<(Arc<[u8]>, SystemTime)> {
+    let memory = memory().lock().unwrap_or_else(PoisonError::into_inner);
+    let (fetched_at, body) = memory.get(venue_key)?;
+    is_fresh(*fetched_at, ttl).then(|| (Arc::clone(body), *fetched_at))
+}
+
+fn is_fresh(fetched_at: SystemTime, ttl: Duration) -> bool {
+    SystemTime::now()
+        .duration_since(fetched_at)
+        .is_ok_and(|age| age < ttl)
+}
+
+fn memory() -> &'static Mutex<VenueData> {
+    VENUE_DATA.get_or_init(Default::default)
+}
+
+/// Things syres keeps on disk between runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CacheEntry {
+    /// The last `/webs` venue data (spaces, tags, bookings).
+    Venue,
+    /// The Skedda session cookies.
+    Cookies,
+    /// The local booking history database.
+    History,
+    /// The last venue data, kept to show while the venue can't be reached.
+    Offline,
+}
+
+impl CacheEntry {
+    pub const ALL: [CacheEntry; 4] = [
+        CacheEntry::Venue,
+        CacheEntry::Cookies,
+        CacheEntry::History,
+        CacheEntry::Offline,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CacheEntry::Venue => "venue data",
+            CacheEntry::Cookies => "cookie jar",
+            CacheEntry::History => "history db",
+            CacheEntry::Offline => "offline data",
+        }
+    }
+
+    /// Where the entry is stored for one venue.
+    pub fn path(self, paths: &Paths, venue: &VenueConfig) -> PathBuf {
+        let key = venue.key();
+        match self {
+            CacheEntry::Venue => paths.venue_cache(&key),
+            CacheEntry::Cookies => paths.cookie_jar(&key),
+            CacheEntry::History => paths.history_db(&key),
+            CacheEntry::Offline => paths.offline_data(&key),
+        }
+    }
+
+    /// The directory holding the entry for every venue.
+    pub fn dir(self, paths: &Paths) -> PathBuf {
+        match self {
+            CacheEntry::Venue => paths.venue_cache_dir(),
+            CacheEntry::Cookies => paths.sessions_dir(),
+            CacheEntry::History => paths.history_dir(),
+            CacheEntry::Offline => paths.offline_dir(),
+        }
+    }
+}
+
+/// Prints what's cached for each venue, how big it is and how old it is.
+pub fn status(venues: &[VenueConfig]) -> color_eyre::Result<()> {
+    let paths = Paths::resolve().ok_or_else(|| eyre!("could not determine the cache directory"))?;
+    for venue in venues {
+        println!("{}", venue.name);
+        for entry in CacheEntry::ALL {
+            let path = entry.path(&paths, venue);
+            match usage(&path) {
+                Some((size, modified)) => println!(
+                    "  {:<12}{:>10}  {:<12}{}",
+                    entry.label(),
+                    format_size(size),
+                    modified
+                        .map(format_age)
+                        .unwrap_or_else(|| "unknown age".to_string()),
+                    path.display()
+                ),
+                None => println!(
+                    "  {:<12}{:>10}  {:<12}{}",
+                    entry.label(),
+                    "-",
+                    "not cached",
+                    path.display()
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the given entries, or everything when none are given, for one venue or all of them.
+pub fn clear(entries: &[CacheEntry], venue: Option<&VenueConfig>) -> color_eyre::Result<()> {
+    let paths = Paths::resolve().ok_or_else(|| eyre!("could not determine the cache directory"))?;
+    let entries = if entries.is_empty() {
+        &CacheEntry::ALL[..]
+    } else {
+        entries
+    };
+    for &entry in entries {
+        let path = match venue {
+            Some(venue) => entry.path(&paths, venue),
+            None => entry.dir(&paths),
+        };
+        if remove(&path)? {
+            match venue {
+                Some(venue) => println!("cleared {} for {}", entry.label(), venue.name),
+                None => println!("cleared {}", entry.label()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Total size and newest modification time of a file, or of the files in a directory.
+fn usage(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_dir() {
+        return Some((metadata.len(), metadata.modified().ok()));
+    }
+    let files: Vec<_> = fs::read_dir(path)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+    Some((
+        files.iter().map(|metadata| metadata.len()).sum(),
+        files
+            .iter()
+            .filter_map(|metadata| metadata.modified().ok())
+            .max(),
+    ))
+}
+
+fn remove(path: &Path) -> color_eyre::Result<bool> {
+    let removed = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match removed {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err).wrap_err_with(|| format!("failed to remove {}", path.display())),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+fn format_age(modified: SystemTime) -> String {
+    let Ok(age) = SystemTime::now().duration_since(modified) else {
+        return "just now".to_string();
+    };
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn venue_data_is_reused_while_fresh() {
+        let key = "cache-test.example.com";
+        assert!(fresh_in_memory(key, Duration::from_secs(60)).is_none());
+
+        keep_venue_data(None, key, b"{}");
+        let (body, _) = fresh_in_memory(key, Duration::from_secs(60)).unwrap();
+        assert_eq!(&*body, b"{}");
+        // A TTL of zero turns the cache off.
+        assert!(fresh_in_memory(key, Duration::ZERO).is_none());
+
+        forget_venue_data(None, key);
+        assert!(fresh_in_memory(key, Duration::from_secs(60)).is_none());
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use time::{Date, Duration, Month};
+
+use crate::{grid::TimeGrid, models::Booking, skedda::MyBooking};
+
+/// One day of the month view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarDay {
+    pub date: Date,
+    /// Bookings of the space that touch the day.
+    pub bookings: usize,
+    /// Whether one of them is mine.
+    pub mine: bool,
+}
+
+/// How one slot of the week view is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Free,
+    Booked,
+    /// Booked by me.
+    Mine,
+}
+
+/// The month containing `date` as weeks from Monday to Sunday; days of the neighbouring
+/// months that pad out the first and last weeks are `None`.
+pub fn weeks(date: Date) -> Vec<[Option<Date>; 7]> {
+    let first = date.replace_day(1).unwrap_or(date);
+    let lead = usize::from(first.weekday().number_days_from_monday());
+    let days = usize::from(first.month().length(first.year()));
+    let mut weeks = Vec::new();
+    let mut week = [None; 7];
+    for day in 0..days {
+        let column = (lead + day) % 7;
+        week[column] = Some(first + Duration::days(day as i64));
+        if column == 6 || day == days - 1 {
+            weeks.push(std::mem::take(&mut week));
+        }
+    }
+    weeks
+}
+
+/// How many of the space's bookings fall on each day of the month containing `date`, and
+/// whether I hold one of them.
+pub fn month(
+    date: Date,
+    bookings: &[Booking],
+    space_id: &str,
+    mine: &[MyBooking],
+    space_name: &str,
+) -> HashMap<Date, CalendarDay> {
+    let mut days = HashMap::new();
+    for day in weeks(date).into_iter().flatten().flatten() {
+        let start = day.midnight();
+        let end = start + Duration::DAY;
+        let count = bookings
+            .iter()
+            .filter(|booking| {
+                booking.start < end
+                    && booking.end > start
+                    && booking.space_ids.iter().any(|id| id == space_id)
+            })
+            .count();
+        let is_mine = mine.iter().any(|booking| {
+            booking.start < end
+                && booking.end > start
+                && booking.space.split(", ").any(|name| name == space_name)
+        });
+        if count > 0 || is_mine {
+            days.insert(
+                day,
+                CalendarDay {
+                    date: day,
+                    bookings: count,
+                    mine: is_mine,
+                },
+            );
+        }
+    }
+    days
+}
+
+/// The Monday-to-Sunday week containing `date`.
+pub fn week(date: Date) -> [Date; 7] {
+    let monday = date - Duration::days(date.weekday().number_days_from_monday().into());
+    std::array::from_fn(|day| monday + Duration::days(day as i64))
+}
+
+/// The space's slots over the week containing `grid`'s day, laid out like `grid`: a row for
+/// each slot with a column for each day from Monday.
+pub fn week_slots(
+    grid: TimeGrid,
+    bookings: &[Booking],
+    space_id: &str,
+    mine: &[MyBooking],
+    space_name: &str,
+) -> Vec<[Slot; 7]> {
+    let days = week(grid.date).map(|date| TimeGrid { date, ..grid });
+    (0..grid.slots)
+        .map(|slot| {
+            days.map(|day| {
+                let (start, end) = (day.slot_start(slot), day.slot_end(slot));
+                let is_mine = mine.iter().any(|booking| {
+                    booking.start < end
+                        && booking.end > start
+                        && booking.space.split(", ").any(|name| name == space_name)
+                });
+                match (is_mine, day.is_booked(bookings, space_id, slot)) {
+                    (true, _) => Slot::Mine,
+                    (false, true) => Slot::Booked,
+                    (false, false) => Slot::Free,
+                }
+            })
+        })
+        .collect()
+}
+
+/// `date` moved by whole months, kept within the shorter month's days.
+pub fn add_months(date: Date, months: i32) -> Date {
+    let index = date.year() * 12 + i32::from(u8::from(date.month())) - 1 + months;
+    let (year, month) = (index.div_euclid(12), (index.rem_euclid(12) + 1) as u8);
+    let Ok(month) = Month::try_from(month) else {
+        return date;
+    };
+    let day = date.day().min(month.length(year));
+    Date::from_calendar_date(year, month, day).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{date, datetime, time};
+
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn lays_out_weeks_from_monday() {
+        // May 2024 starts on a Wednesday and ends on a Friday.
+        let weeks = weeks(date!(2024 - 05 - 17));
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(weeks[0][..2], [None, None]);
+        assert_eq!(weeks[0][2], Some(date!(2024 - 05 - 01)));
+        assert_eq!(weeks[4][4], Some(date!(2024 - 05 - 31)));
+        assert_eq!(weeks[4][5..], [None, None]);
+    }
+
+    #[test]
+    fn counts_the_spaces_bookings_by_day() {
+        let webs = fixtures::webs_response("small_venue.json");
+        let mine = [MyBooking {
+            id: None,
+            space: "Boardroom".to_string(),
+            location: None,
+            start: datetime!(2024-05-03 9:00),
+            end: datetime!(2024-05-03 10:00),
+            title: None,
+        }];
+        let days = month(
+            date!(2024 - 05 - 01),
+            &webs.bookings,
+            "103",
+            &mine,
+            "Boardroom",
+        );
+        let first = &days[&date!(2024 - 05 - 01)];
+        assert!(first.bookings > 0 && !first.mine);
+        assert_eq!(
+            days[&date!(2024 - 05 - 03)],
+            CalendarDay {
+                date: date!(2024 - 05 - 03),
+                bookings: 0,
+                mine: true,
+            }
+        );
+        assert!(!days.contains_key(&date!(2024 - 05 - 20)));
+    }
+
+    #[test]
+    fn marks_the_spaces_slots_over_the_week() {
+        let webs = fixtures::webs_response("small_venue.json");
+        let mine = [MyBooking {
+            id: None,
+            space: "Boardroom".to_string(),
+            location: None,
+            start: datetime!(2024-05-03 9:00),
+            end: datetime!(2024-05-03 10:00),
+            title: None,
+        }];
+        // Wednesday 1 May, from 8:00 in hours.
+        let grid = TimeGrid {
+            date: date!(2024 - 05 - 01),
+            start: time!(8:00),
+            slot_minutes: 60,
+            slots: 4,
+        };
+        assert_eq!(week(grid.date)[0], date!(2024 - 04 - 29));
+        let slots = week_slots(grid, &webs.bookings, "103", &mine, "Boardroom");
+        assert_eq!(slots.len(), 4);
+        assert_eq!(slots[1][2], Slot::Booked);
+        assert_eq!(slots[1][4], Slot::Mine);
+        assert_eq!(slots[0][2], Slot::Free);
+        assert_eq!(slots[1][3], Slot::Free);
+    }
+
+    #[test]
+    fn moves_by_months() {
+        assert_eq!(add_months(date!(2024 - 01 - 31), 1), date!(2024 - 02 - 29));
+        assert_eq!(add_months(date!(2024 - 01 - 15), -1), date!(2023 - 12 - 15));
+        assert_eq!(add_months(date!(2024 - 11 - 30), 14), date!(2026 - 01 - 30));
+    }
+}
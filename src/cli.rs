@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Parser, Subcommand};
+
+use crate::cache::CacheEntry;
+
+/// Command line arguments.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Increase log verbosity (-v info, -vv debug, -vvv trace). Overrides RUST_LOG and config.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print diagnostics about the local setup.
+    Doctor,
+    /// Bundle sanitized logs, config and system details for a bug report.
+    Report {
+        /// Where to write the archive (defaults to `syres-report-<timestamp>.tar.gz`).
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run in the background, sending the morning digest each day.
+    Daemon {
+        /// Send the digest once now and exit, to check the channels work.
+        #[arg(long)]
+        now: bool,
+    },
+    /// Show a read-only, self-refreshing board of a location's spaces, e.g. for a wall display.
+    Status {
+        /// The location to show, as named in config.
+        #[arg(short, long)]
+        location: String,
+        /// The venue the location belongs to (by name or host); defaults to the first venue
+        /// with a location of that name.
+        #[arg(long)]
+        venue: Option<String>,
+        /// Fill the screen with the board alone, without a frame or help.
+        #[arg(long)]
+        fullscreen: bool,
+        /// Show the board as it stands at a time later today, e.g. `14:00`, `2pm` or the name
+        /// of a window from config, instead of now.
+        #[arg(long, value_name = "TIME")]
+        at: Option<String>,
+    },
+    /// Book a space without the TUI, e.g. from a cron job.
+    Book {
+        /// The location to book at, as named in config.
+        #[arg(short, long)]
+        location: String,
+        /// The venue the location belongs to (by name or host); defaults to the first venue
+        /// with a location of that name.
+        #[arg(long)]
+        venue: Option<String>,
+        /// The space to book, by name; otherwise the first one free for the whole time.
+        #[arg(long)]
+        space: Option<String>,
+        /// The day, as `2025-08-01`, `today`, `tomorrow` or a weekday such as `fri`.
+        #[arg(long, default_value = "today")]
+        date: String,
+        /// The start, e.g. `09:00` or `9am`, or the name of a window from config.
+        #[arg(long, value_name = "TIME")]
+        from: String,
+        /// The end, e.g. `12:00`; defaults to the end of the window named by `--from`.
+        #[arg(long, value_name = "TIME")]
+        till: Option<String>,
+        /// Print the booking as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inspect or clear cached data.
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Look through the bookings remembered locally.
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryAction {
+    /// Find my bookings whose title or space contains every word of the query.
+    Search {
+        /// Words to look for, e.g. `"design review"`.
+        query: String,
+        /// Only search this venue (by name or host); searches every venue when omitted.
+        #[arg(long)]
+        venue: Option<String>,
+        /// The most bookings to list from each venue.
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Save the bookings of mine Skedda sends now to the history, e.g. after setting up a
+    /// venue.
+    Import {
+        /// Only import this venue (by name or host); imports every venue when omitted.
+        #[arg(long)]
+        venue: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheAction {
+    /// Show what's cached, its size and age.
+    Status,
+    /// Delete cached data.
+    Clear {
+        /// Entries to clear; clears everything when omitted.
+        #[arg(value_enum)]
+        entries: Vec<CacheEntry>,
+        /// Only clear data for this venue (by name or host); clears every venue when omitted.
+        #[arg(long)]
+        venue: Option<String>,
+    },
+}
@@ -0,0 +1,182 @@
+use std::{
+    sync::{
+        OnceLock,
+        atomic::{AtomicI64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use time::{
+    Duration, OffsetDateTime, PrimitiveDateTime, UtcOffset, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
+use time_tz::{OffsetDateTimeExt, Tz};
+
+/// How far the local clock may drift from Skedda's before it's worth a warning: bookings
+/// timed to a window opening, and which slots count as past, go wrong beyond this.
+pub const SKEW_WARNING: Duration = Duration::seconds(30);
+
+/// An HTTP `Date` header, e.g. "Wed, 01 May 2024 09:30:00 GMT".
+const HTTP_DATE: &[BorrowedFormatItem] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+/// Stands in for a skew no response has measured yet.
+const UNKNOWN_SKEW: i64 = i64::MIN;
+
+static LOCAL_ZONE: OnceLock<Zone> = OnceLock::new();
+static SKEW_SECONDS: AtomicI64 = AtomicI64::new(UNKNOWN_SKEW);
+
+/// Where local wall-clock time is kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    /// An IANA zone such as "America/New_York", whose offset follows daylight saving.
+    Named(&'static Tz),
+    /// The same offset all year round.
+    Fixed(UtcOffset),
+}
+
+impl Zone {
+    /// Reads an IANA zone name, "UTC", or a fixed offset like "-04:00".
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("utc") || name.eq_ignore_ascii_case("z") {
+            return Some(Self::Fixed(UtcOffset::UTC));
+        }
+        if let Some(tz) = time_tz::timezones::get_by_name(name) {
+            return Some(Self::Named(tz));
+        }
+        UtcOffset::parse(
+            name,
+            format_description!("[offset_hour sign:mandatory]:[offset_minute]"),
+        )
+        .ok()
+        .map(Self::Fixed)
+    }
+
+    /// This machine's zone, falling back to its current offset when the zone has no name.
+    fn system() -> Self {
+        match time_tz::system::get_timezone() {
+            Ok(tz) => Self::Named(tz),
+            Err(_) => Self::Fixed(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC)),
+        }
+    }
+
+    /// A moment as wall-clock time in this zone, with whatever offset applies at that moment.
+    pub fn wall_clock(self, at: OffsetDateTime) -> PrimitiveDateTime {
+        let at = match self {
+            Self::Named(tz) => at.to_timezone(tz),
+            Self::Fixed(offset) => at.to_offset(offset),
+        };
+        PrimitiveDateTime::new(at.date(), at.time())
+    }
+}
+
+/// Records the configured zone, or else this machine's.
+///
+/// Call this before spawning any threads: on Unix a nameless local offset can only be read
+/// soundly while the process is single-threaded, so later lookups would fall back to UTC.
+pub fn init(configured: Option<Zone>) {
+    let _ = LOCAL_ZONE.set(configured.unwrap_or_else(Zone::system));
+}
+
+fn zone() -> Zone {
+    *LOCAL_ZONE.get_or_init(|| Zone::Fixed(UtcOffset::UTC))
+}
+
+/// The current local wall-clock time, which is what Skedda uses for booking times. The
+/// offset is worked out afresh each time, so a long-running process follows daylight saving.
+pub fn now() -> PrimitiveDateTime {
+    zone().wall_clock(OffsetDateTime::now_utc())
+}
+
+/// A moment as local wall-clock time, like [`now`].
+pub fn local(at: SystemTime) -> PrimitiveDateTime {
+    zone().wall_clock(OffsetDateTime::from(at))
+}
+
+/// Compares the local clock with the `Date` header of a server response, keeping the latest
+/// difference. Headers that don't parse are ignored.
+pub fn observe_server_date(header: &str) {
+    if let Some(server) = parse_http_date(header) {
+        let skew = OffsetDateTime::now_utc() - server;
+        SKEW_SECONDS.store(skew.whole_seconds(), Ordering::Relaxed);
+    }
+}
+
+/// How far the local clock runs ahead of Skedda's (behind, when negative), once a response
+/// has said what time Skedda thinks it is.
+pub fn skew() -> Option<Duration> {
+    match SKEW_SECONDS.load(Ordering::Relaxed) {
+        UNKNOWN_SKEW => None,
+        seconds => Some(Duration::seconds(seconds)),
+    }
+}
+
+/// The skew, when it's large enough to break bookings.
+pub fn notable_skew() -> Option<Duration> {
+    skew().filter(|skew| skew.unsigned_abs() > SKEW_WARNING.unsigned_abs())
+}
+
+/// "3m 20s fast", "45s slow" or "in step".
+pub fn describe_skew(skew: Duration) -> String {
+    let seconds = skew.whole_seconds().unsigned_abs();
+    let amount = match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, 0) => return "in step".to_string(),
+        (0, 0, s) => format!("{s}s"),
+        (0, m, 0) => format!("{m}m"),
+        (0, m, s) => format!("{m}m {s}s"),
+        (h, m, _) => format!("{h}h {m}m"),
+    };
+    let direction = if skew.is_positive() { "fast" } else { "slow" };
+    format!("{amount} {direction}")
+}
+
+fn parse_http_date(header: &str) -> Option<OffsetDateTime> {
+    PrimitiveDateTime::parse(header.trim(), HTTP_DATE)
+        .ok()
+        .map(PrimitiveDateTime::assume_utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn reads_http_dates_and_describes_skew() {
+        assert_eq!(
+            parse_http_date("Wed, 01 May 2024 09:30:05 GMT"),
+            Some(datetime!(2024-05-01 9:30:05 UTC))
+        );
+        assert_eq!(parse_http_date("2024-05-01T09:30:05Z"), None);
+
+        assert_eq!(describe_skew(Duration::ZERO), "in step");
+        assert_eq!(describe_skew(Duration::seconds(45)), "45s fast");
+        assert_eq!(describe_skew(Duration::seconds(-200)), "3m 20s slow");
+        assert_eq!(describe_skew(Duration::minutes(-5)), "5m slow");
+        assert_eq!(describe_skew(Duration::minutes(61)), "1h 1m fast");
+    }
+
+    #[test]
+    fn named_zones_follow_daylight_saving() {
+        let new_york = Zone::parse("America/New_York").unwrap();
+        assert_eq!(
+            new_york.wall_clock(datetime!(2024-03-09 14:00 UTC)),
+            datetime!(2024-03-09 9:00)
+        );
+        assert_eq!(
+            new_york.wall_clock(datetime!(2024-03-11 14:00 UTC)),
+            datetime!(2024-03-11 10:00)
+        );
+
+        let fixed = Zone::parse("-04:00").unwrap();
+        assert_eq!(
+            fixed.wall_clock(datetime!(2024-03-09 14:00 UTC)),
+            datetime!(2024-03-09 10:00)
+        );
+        assert_eq!(Zone::parse(" utc "), Some(Zone::Fixed(UtcOffset::UTC)));
+        assert_eq!(Zone::parse("Mars/Olympus_Mons"), None);
+    }
+}
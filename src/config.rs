@@ -0,0 +1,834 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    net::IpAddr,
+};
+
+use color_eyre::eyre::{WrapErr, bail};
+use reqwest::Url;
+use serde::Deserialize;
+use time::Time;
+
+use crate::{clock::Zone, grid, hooks::HookEvent, paths::Paths, polling};
+
+/// Switchyards' locations, offered when no venues are configured until the venue's own space
+/// tags can be fetched.
+pub const SWITCHYARDS_LOCATIONS: [&str; 12] = [
+    "Adair Park",
+    "Avondale Estates",
+    "Buckhead",
+    "Cabbagetown",
+    "Chamblee",
+    "Decatur",
+    "Downtown",
+    "Midtown",
+    "Old Fourth Ward",
+    "Roswell",
+    "Virginia-Highland",
+    "Westside",
+];
+
+/// User configuration, read from `config.toml` in the config directory.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The venues' timezone, e.g. `"America/New_York"` or a fixed `"-04:00"`, when it isn't
+    /// this machine's.
+    pub timezone: Option<String>,
+    pub log: LogConfig,
+    pub crash_reporting: CrashReportingConfig,
+    pub grid: GridConfig,
+    /// Times of day by name, e.g. `standup = { start = "9:00", end = "9:30" }`.
+    pub windows: BTreeMap<String, TimeWindow>,
+    /// Booking form shortcuts, unless a venue has its own.
+    pub presets: Vec<Preset>,
+    pub go_now: GoNowConfig,
+    pub network: NetworkConfig,
+    pub digest: DigestConfig,
+    pub notify: NotifyConfig,
+    pub openings: OpeningsConfig,
+    pub hooks: HooksConfig,
+    pub startup: StartupConfig,
+    pub quick_book: QuickBookConfig,
+    pub cache: CacheConfig,
+    pub venues: Vec<VenueConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            timezone: None,
+            log: LogConfig::default(),
+            crash_reporting: CrashReportingConfig::default(),
+            grid: GridConfig::default(),
+            windows: BTreeMap::new(),
+            presets: Preset::defaults(),
+            go_now: GoNowConfig::default(),
+            network: NetworkConfig::default(),
+            digest: DigestConfig::default(),
+            notify: NotifyConfig::default(),
+            openings: OpeningsConfig::default(),
+            hooks: HooksConfig::default(),
+            startup: StartupConfig::default(),
+            quick_book: QuickBookConfig::default(),
+            cache: CacheConfig::default(),
+            venues: vec![VenueConfig::switchyards()],
+        }
+    }
+}
+
+/// A Skedda-hosted space the user is a member of.
+#[derive(Debug, Clone, Deserialize)]
+pub struct VenueConfig {
+    pub name: String,
+    /// e.g. `https://switchyards.skedda.com`.
+    pub base_url: String,
+    /// Space tags to offer as locations. When none are listed, the venue's own are offered
+    /// once its data loads.
+    #[serde(default)]
+    pub locations: Vec<String>,
+    /// Replaces the global presets for this venue.
+    pub presets: Option<Vec<Preset>>,
+    /// Unlocks admin-only features, such as booking on behalf of another member.
+    #[serde(default)]
+    pub admin: bool,
+    /// Replaces `[network.limits]` for this venue.
+    pub limits: Option<LimitsConfig>,
+    /// The account to log in as when there's no saved session, or it has run out.
+    pub email: Option<String>,
+    pub password: Option<String>,
+    /// A session cookie copied from a logged-in browser, as `name=value`, in place of an
+    /// email and password.
+    pub token: Option<String>,
+    /// Whether the venue's space tags replace `locations` once its data loads: for venues
+    /// that list none, and for Switchyards out of the box, whose list is only a stand-in.
+    #[serde(skip)]
+    pub discover_locations: bool,
+}
+
+impl VenueConfig {
+    pub fn switchyards() -> Self {
+        Self {
+            name: "Switchyards".to_string(),
+            base_url: "https://switchyards.skedda.com".to_string(),
+            locations: SWITCHYARDS_LOCATIONS.map(String::from).to_vec(),
+            presets: None,
+            admin: false,
+            limits: None,
+            email: None,
+            password: None,
+            token: None,
+            discover_locations: true,
+        }
+    }
+
+    /// Identifies the venue in file names, e.g. `switchyards.skedda.com`, or `localhost_8080`
+    /// when the URL has a port.
+    pub fn key(&self) -> String {
+        Url::parse(&self.base_url)
+            .ok()
+            .and_then(|url| {
+                let host = url.host_str()?;
+                Some(match url.port() {
+                    Some(port) => format!("{host}_{port}"),
+                    None => host.to_string(),
+                })
+            })
+            .unwrap_or_else(|| {
+                self.base_url
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                    .collect()
+            })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Filter directives in `RUST_LOG` syntax, e.g. `"info"` or `"warn,syres::skedda=debug"`.
+    pub level: Option<String>,
+    /// Size at which the log file is rotated.
+    pub max_size_mb: u64,
+    /// Number of rotated files to keep.
+    pub max_files: usize,
+    /// Rotated files older than this are deleted.
+    pub max_age_days: Option<u64>,
+    /// Gzip rotated files.
+    pub compress: bool,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: None,
+            max_size_mb: 10,
+            max_files: 5,
+            max_age_days: None,
+            compress: false,
+        }
+    }
+}
+
+/// Opt-in reporting of panics and fatal errors to a Sentry-compatible endpoint.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CrashReportingConfig {
+    pub enabled: bool,
+    /// Project DSN, e.g. `https://<key>@sentry.example.com/42`.
+    pub dsn: Option<String>,
+    /// Tags reports with an environment such as `"server"` or `"laptop"`.
+    pub environment: Option<String>,
+}
+
+/// The availability grid shown in the booking form.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GridConfig {
+    /// Minutes per grid cell: 15, 30 or 60. Rounded up to the venue's booking increment.
+    pub granularity_minutes: u32,
+    /// First hour shown, e.g. `7` for 07:00.
+    pub start_hour: u8,
+    /// Hour the grid ends at, e.g. `22` for 22:00.
+    pub end_hour: u8,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            granularity_minutes: 30,
+            start_hour: 7,
+            end_hour: 22,
+        }
+    }
+}
+
+/// The "where can I go right now?" answer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GoNowConfig {
+    /// The kind of space wanted, matched against space types (or names), e.g. `"conference"`.
+    /// Empty for any space.
+    pub space_type: String,
+    /// How long the space must stay free.
+    pub hours: u8,
+    /// Locations to suggest first, in order of preference.
+    pub preferred_locations: Vec<String>,
+}
+
+impl Default for GoNowConfig {
+    fn default() -> Self {
+        Self {
+            space_type: "conference".to_string(),
+            hours: 2,
+            preferred_locations: Vec::new(),
+        }
+    }
+}
+
+/// How syres reaches venues over the network.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Resolve each venue's host once per run instead of for every new connection.
+    pub dns_cache: bool,
+    /// Fixed addresses for hosts, skipping DNS, e.g. `"app.skedda.com" = "203.0.113.7"`.
+    pub hosts: HashMap<String, IpAddr>,
+    /// Which IP version to connect over, for networks where one of them is broken.
+    pub ip_version: IpVersion,
+    /// Requests syres may make on its own in a minute, across every venue: session refreshes,
+    /// connection warm-ups and retries.
+    pub background_requests_per_minute: u32,
+    /// Touch each venue's session this often while the TUI is open, in minutes, so it
+    /// doesn't lapse through the day; off when unset.
+    pub session_keep_alive_minutes: Option<u64>,
+    /// How hard syres may lean on each venue, for the account logged in there.
+    pub limits: LimitsConfig,
+    /// How requests that fail in a way that may pass are tried again.
+    pub retry: RetryConfig,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            dns_cache: true,
+            hosts: HashMap::new(),
+            ip_version: IpVersion::Any,
+            background_requests_per_minute: polling::DEFAULT_BUDGET_PER_MINUTE,
+            session_keep_alive_minutes: None,
+            limits: LimitsConfig::default(),
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
+/// Limits on the requests syres makes to one venue, so several people sharing a server can
+/// keep it from looking aggressive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Requests waiting on an answer at once.
+    pub max_concurrent_requests: usize,
+    /// Seconds to leave between one booking and the next.
+    pub min_booking_interval_secs: u64,
+    /// The shortest time, in seconds, before data is reloaded on a timer.
+    pub min_poll_interval_secs: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: 4,
+            min_booking_interval_secs: 0,
+            min_poll_interval_secs: 60,
+        }
+    }
+}
+
+/// Retries of requests Skedda turns away for coming too often, that fail at a gateway, or
+/// that can't reach the venue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Tries in all, the first included; 1 turns retrying off.
+    pub max_attempts: u32,
+    /// The wait before the first retry, in milliseconds, doubling for each one after.
+    pub base_delay_ms: u64,
+    /// The longest wait before a retry, in seconds. A `Retry-After` longer than this isn't
+    /// waited out; the request fails and the app tries again later.
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IpVersion {
+    /// Whatever order the resolver returns.
+    Any,
+    /// Try IPv4 addresses first, falling back to IPv6.
+    PreferV4,
+    /// Try IPv6 addresses first, falling back to IPv4.
+    PreferV6,
+    /// Only connect over IPv4.
+    V4,
+    /// Only connect over IPv6.
+    V6,
+}
+
+/// The morning summary `syres daemon` sends.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    /// Local time to send it at, e.g. `"7:30"`.
+    pub time: String,
+    /// Where to send it, unless `[notify.routes]` says; the daemon has nothing to do when
+    /// neither does.
+    pub channels: Vec<Channel>,
+    /// Older spelling of `notify.webhook_url`.
+    pub webhook_url: Option<String>,
+    /// Older spelling of `notify.email`.
+    pub email: Option<String>,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            time: "7:30".to_string(),
+            channels: Vec::new(),
+            webhook_url: None,
+            email: None,
+        }
+    }
+}
+
+/// Where notifications go: each channel's settings, and the channels each event is sent to.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Receives notifications as a JSON POST, for the `webhook` channel.
+    pub webhook_url: Option<String>,
+    /// Address for the `email` channel, which hands the message to the local `sendmail`.
+    pub email: Option<String>,
+    /// The topic to publish to for the `ntfy` channel, e.g. `"https://ntfy.sh/my-desks"`.
+    pub ntfy_url: Option<String>,
+    /// The channels for each event, e.g. `booked = ["ntfy"]`.
+    pub routes: BTreeMap<NotifyEvent, Vec<Channel>>,
+}
+
+impl NotifyConfig {
+    /// The channels the event is sent to; none unless it's routed.
+    pub fn channels(&self, event: NotifyEvent) -> &[Channel] {
+        self.routes.get(&event).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Something syres can send a notification about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotifyEvent {
+    /// The daemon's morning digest.
+    Digest,
+    /// A booking made from the TUI or `syres book`.
+    Booked,
+    /// A day about to come within the venue's advance-booking window, from `syres daemon`.
+    Opening,
+}
+
+impl NotifyEvent {
+    /// The event's key in `[notify.routes]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            NotifyEvent::Digest => "digest",
+            NotifyEvent::Booked => "booked",
+            NotifyEvent::Opening => "opening",
+        }
+    }
+}
+
+/// When `syres daemon` warns that a day is about to become bookable.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct OpeningsConfig {
+    /// Minutes before the opening to send the alert.
+    pub lead_minutes: u32,
+}
+
+impl Default for OpeningsConfig {
+    fn default() -> Self {
+        Self { lead_minutes: 10 }
+    }
+}
+
+/// Shell commands run after a booking is made or cancelled, for automations of your own.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run after a booking is made, from the TUI or `syres book`.
+    pub booked: Option<String>,
+    /// Run after a booking is cancelled from the TUI.
+    pub cancelled: Option<String>,
+}
+
+impl HooksConfig {
+    /// The command for the event, unless it's unset or blank.
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        match event {
+            HookEvent::Booked => self.booked.as_deref(),
+            HookEvent::Cancelled => self.cancelled.as_deref(),
+        }
+        .filter(|command| !command.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Channel {
+    /// A desktop notification, through `notify-send` on Linux or `osascript` on macOS.
+    Desktop,
+    Webhook,
+    Email,
+    /// A push notification through an ntfy server.
+    Ntfy,
+}
+
+/// What syres opens on.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    pub view: StartupView,
+    /// My usual location, e.g. `"Decatur"`, for `view = "availability"`. The first venue
+    /// with a location of that name is used.
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartupView {
+    /// The list of locations.
+    #[default]
+    Locations,
+    /// My bookings over the week ahead.
+    MyBookings,
+    /// The booking form for `startup.location`.
+    Availability,
+    /// Which locations have free spaces right now.
+    Overview,
+    /// The venue picker, to choose a venue before its locations.
+    Venues,
+}
+
+/// How long venue data is reused before it's fetched again.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Seconds a venue's data stays fresh enough to open a location from; 0 fetches it every
+    /// time.
+    pub venue_data_ttl_secs: u64,
+    /// Keep venue data in the cache directory too, so it survives a restart.
+    pub on_disk: bool,
+    /// Keep each venue's last data on disk however old it gets, to show while the venue
+    /// can't be reached.
+    pub offline: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            venue_data_ttl_secs: 60,
+            on_disk: false,
+            offline: true,
+        }
+    }
+}
+
+/// What `b` books, from any view.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QuickBookConfig {
+    /// Where to book; `startup.location` when unset.
+    pub location: Option<String>,
+    /// The space to book when it's free, by name; otherwise the first free space.
+    pub space: Option<String>,
+    /// How long to book for, from the next half hour.
+    pub minutes: u32,
+}
+
+impl Default for QuickBookConfig {
+    fn default() -> Self {
+        Self {
+            location: None,
+            space: None,
+            minutes: 60,
+        }
+    }
+}
+
+/// A named time of day that recurs, such as a standup.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TimeWindow {
+    /// A time such as `"9:00"`.
+    pub start: String,
+    /// A time such as `"9:30"`.
+    pub end: String,
+}
+
+impl TimeWindow {
+    /// The window's start and end, when both are times and it ends after it starts.
+    pub fn times(&self) -> Option<(Time, Time)> {
+        let start = grid::parse_time(&self.start)?;
+        let end = grid::parse_time(&self.end)?;
+        (start < end).then_some((start, end))
+    }
+}
+
+/// Keys the booking form already uses, which a preset can't take.
+const RESERVED_KEYS: &[char] = &[
+    'q', 'r', 'b', 'g', 'G', 'h', 'H', 'j', 'k', 'l', 'L', 'x', 'B', 'D', 'F', 'I', 'J', 'M', 'T',
+    'W', ' ', '+', '=', '-',
+];
+
+/// A single-key shortcut in the booking form that selects a standard window.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Preset {
+    pub key: char,
+    pub name: String,
+    /// A window from `[windows]` to select, in place of `start` and `end`.
+    pub window: Option<String>,
+    /// A time such as `"9:00"`, or `"open"` for the start of the grid.
+    #[serde(default)]
+    pub start: String,
+    /// A time such as `"12:00"`, or `"close"` for the end of the grid.
+    #[serde(default)]
+    pub end: String,
+}
+
+impl Preset {
+    pub fn defaults() -> Vec<Self> {
+        [
+            ('m', "Morning", "9:00", "12:00"),
+            ('a', "Afternoon", "13:00", "17:00"),
+            ('d', "All day", "open", "close"),
+        ]
+        .into_iter()
+        .map(|(key, name, start, end)| Self {
+            key,
+            name: name.to_string(),
+            window: None,
+            start: start.to_string(),
+            end: end.to_string(),
+        })
+        .collect()
+    }
+}
+
+impl Config {
+    /// The presets offered when booking at the venue.
+    pub fn presets_for(&self, venue: &VenueConfig) -> Vec<Preset> {
+        venue
+            .presets
+            .clone()
+            .unwrap_or_else(|| self.presets.clone())
+    }
+
+    /// The named window's start and end, ignoring case.
+    pub fn window(&self, name: &str) -> Option<(Time, Time)> {
+        self.windows
+            .iter()
+            .find(|(other, _)| other.eq_ignore_ascii_case(name))
+            .and_then(|(_, window)| window.times())
+    }
+
+    /// Reads a time of day given on the command line, or the start of a named window.
+    pub fn start_time(&self, input: &str) -> Result<Time, String> {
+        if let Some((start, _)) = self.window(input) {
+            return Ok(start);
+        }
+        grid::read_time(input).map_err(|reason| {
+            format!("{reason}\n  try a time like 9, 930, 9:30, 14:15 or 2:15pm, or a window from [windows]")
+        })
+    }
+
+    /// The zone set by `timezone`, if it's set and reads as one.
+    pub fn zone(&self) -> Option<Zone> {
+        Zone::parse(self.timezone.as_deref()?)
+    }
+
+    /// Finds a configured venue by name (case-insensitively) or by its key.
+    pub fn venue(&self, name_or_key: &str) -> Option<&VenueConfig> {
+        self.venues.iter().find(|venue| {
+            venue.name.eq_ignore_ascii_case(name_or_key) || venue.key() == name_or_key
+        })
+    }
+
+    /// The index of the first venue with a location named `name`, ignoring case, and the
+    /// location's name as that venue spells it.
+    pub fn find_location(&self, name: &str) -> Option<(usize, &str)> {
+        self.venues.iter().enumerate().find_map(|(index, venue)| {
+            venue
+                .locations
+                .iter()
+                .find(|location| location.eq_ignore_ascii_case(name))
+                .map(|location| (index, location.as_str()))
+        })
+    }
+
+    /// Loads the config file, falling back to defaults when it doesn't exist.
+    pub fn load() -> color_eyre::Result<Self> {
+        let Some(path) = Paths::resolve().map(|paths| paths.config_file()) else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("failed to read config file {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .wrap_err_with(|| format!("failed to parse config file {}", path.display()))?;
+        Self::validate(config)
+    }
+
+    /// Checks the settings read from the config file, filling in what follows from them.
+    fn validate(mut config: Self) -> color_eyre::Result<Self> {
+        for venue in &mut config.venues {
+            venue.discover_locations = venue.locations.is_empty();
+        }
+        if ![15, 30, 60].contains(&config.grid.granularity_minutes) {
+            bail!(
+                "grid.granularity_minutes must be 15, 30 or 60, not {}",
+                config.grid.granularity_minutes
+            );
+        }
+        if config.grid.start_hour >= config.grid.end_hour || config.grid.end_hour > 24 {
+            bail!("grid.start_hour must be before grid.end_hour, within 0-24");
+        }
+        if !(1..=24).contains(&config.go_now.hours) {
+            bail!("go_now.hours must be between 1 and 24");
+        }
+        for (name, window) in &config.windows {
+            if window.times().is_none() {
+                bail!(
+                    "window {name:?} needs start and end times like \"9:00\", the end after the start"
+                );
+            }
+        }
+        let windows = &config.windows;
+        for preset in config.presets.iter_mut().chain(
+            config
+                .venues
+                .iter_mut()
+                .flat_map(|venue| venue.presets.iter_mut().flatten()),
+        ) {
+            if RESERVED_KEYS.contains(&preset.key) {
+                bail!(
+                    "preset {:?} can't use {:?}, which the booking form already uses",
+                    preset.name,
+                    preset.key
+                );
+            }
+            if let Some(name) = &preset.window {
+                let Some(window) = windows.get(name) else {
+                    bail!(
+                        "preset {:?} uses window {name:?}, which isn't in [windows]",
+                        preset.name
+                    );
+                };
+                if !preset.start.is_empty() || !preset.end.is_empty() {
+                    bail!(
+                        "preset {:?} takes either a window or start and end times, not both",
+                        preset.name
+                    );
+                }
+                preset.start = window.start.clone();
+                preset.end = window.end.clone();
+            }
+            let valid = |time: &str, keyword| time == keyword || grid::parse_time(time).is_some();
+            if !valid(&preset.start, "open") || !valid(&preset.end, "close") {
+                bail!(
+                    "preset {:?} needs start and end times like \"9:00\", \"open\" or \"close\"",
+                    preset.name
+                );
+            }
+        }
+        if grid::parse_time(&config.digest.time).is_none() {
+            bail!(
+                "digest.time must be a time like \"7:30\", not {:?}",
+                config.digest.time
+            );
+        }
+        let digest = &mut config.digest;
+        let notify = &mut config.notify;
+        if !digest.channels.is_empty() {
+            notify
+                .routes
+                .entry(NotifyEvent::Digest)
+                .or_insert_with(|| digest.channels.clone());
+        }
+        notify.webhook_url = notify.webhook_url.take().or(digest.webhook_url.take());
+        notify.email = notify.email.take().or(digest.email.take());
+        for (event, channels) in &notify.routes {
+            let missing = channels.iter().find_map(|channel| match channel {
+                Channel::Webhook if notify.webhook_url.is_none() => Some("notify.webhook_url"),
+                Channel::Email if notify.email.is_none() => Some("notify.email"),
+                Channel::Ntfy if notify.ntfy_url.is_none() => Some("notify.ntfy_url"),
+                _ => None,
+            });
+            if let Some(setting) = missing {
+                bail!(
+                    "{setting} is needed to send {} notifications there",
+                    event.name()
+                );
+            }
+        }
+        if let Some(url) = &notify.ntfy_url {
+            if Url::parse(url).is_err() {
+                bail!(
+                    "notify.ntfy_url must be a URL like \"https://ntfy.sh/my-topic\", not {url:?}"
+                );
+            }
+        }
+        if std::iter::once(config.network.limits)
+            .chain(config.venues.iter().filter_map(|venue| venue.limits))
+            .any(|limits| limits.max_concurrent_requests == 0)
+        {
+            bail!("max_concurrent_requests must be at least 1");
+        }
+        if config.network.retry.max_attempts == 0 {
+            bail!("network.retry.max_attempts must be at least 1");
+        }
+        if config
+            .network
+            .session_keep_alive_minutes
+            .is_some_and(|minutes| minutes < 5)
+        {
+            bail!("network.session_keep_alive_minutes must be at least 5");
+        }
+        if config.timezone.is_some() && config.zone().is_none() {
+            bail!(
+                "timezone must be a zone like \"America/New_York\", \"UTC\" or \"-04:00\", not {:?}",
+                config.timezone.as_deref().unwrap_or_default()
+            );
+        }
+        for venue in &config.venues {
+            if venue.password.is_some() && venue.email.is_none() {
+                bail!("{} has a password but no email to log in with", venue.name);
+            }
+            if venue
+                .token
+                .as_ref()
+                .is_some_and(|token| !token.contains('='))
+            {
+                bail!(
+                    "{}'s token must be a cookie written as name=value",
+                    venue.name
+                );
+            }
+        }
+        if config.venues.is_empty() {
+            config.venues.push(VenueConfig::switchyards());
+        }
+        if config.startup.view == StartupView::Availability {
+            match &config.startup.location {
+                None => bail!("startup.location is needed to start on its availability"),
+                Some(location) if config.find_location(location).is_none() => {
+                    bail!("startup.location {location:?} isn't a location of any venue")
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(location) = &config.quick_book.location {
+            if config.find_location(location).is_none() {
+                bail!("quick_book.location {location:?} isn't a location of any venue");
+            }
+        }
+        if config.quick_book.minutes == 0 {
+            bail!("quick_book.minutes must be more than 0");
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(toml: &str) -> color_eyre::Result<Config> {
+        Config::validate(toml::from_str(toml).unwrap())
+    }
+
+    #[test]
+    fn presets_cannot_take_the_booking_forms_keys() {
+        assert!(validate("").is_ok());
+
+        let err = validate(
+            r#"
+            [[presets]]
+            key = "x"
+            name = "Lunch"
+            start = "12:00"
+            end = "13:00"
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("\"Lunch\""), "{err}");
+
+        let err = validate(
+            r#"
+            [[venues]]
+            name = "Switchyards"
+            base_url = "https://switchyards.skedda.com"
+            presets = [{ key = "j", name = "Late", start = "17:00", end = "close" }]
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("\"Late\""), "{err}");
+    }
+}
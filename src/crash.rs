@@ -0,0 +1,138 @@
+use std::{
+    panic, thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use rand::Rng;
+use reqwest::Url;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config::CrashReportingConfig;
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends panics and fatal errors to a Sentry-compatible endpoint.
+#[derive(Debug, Clone)]
+pub struct CrashReporter {
+    store_url: String,
+    auth_header: String,
+    environment: Option<String>,
+}
+
+impl CrashReporter {
+    /// Builds a reporter from the config, or `None` when reporting isn't enabled.
+    pub fn from_config(config: &CrashReportingConfig) -> color_eyre::Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let Some(dsn) = &config.dsn else {
+            bail!("crash_reporting.enabled is set but crash_reporting.dsn is missing");
+        };
+        let (store_url, key) = Self::parse_dsn(dsn)?;
+        Ok(Some(Self {
+            store_url,
+            auth_header: format!(
+                "Sentry sentry_version=7, sentry_client=syres/{}, sentry_key={key}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            environment: config.environment.clone(),
+        }))
+    }
+
+    /// Splits a DSN like `https://<key>@<host>/<project>` into its store endpoint and key.
+    fn parse_dsn(dsn: &str) -> color_eyre::Result<(String, String)> {
+        let url = Url::parse(dsn).wrap_err("invalid crash_reporting.dsn")?;
+        let key = url.username();
+        if key.is_empty() {
+            bail!("crash_reporting.dsn is missing the public key");
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| eyre!("crash_reporting.dsn is missing a host"))?;
+        let port = url
+            .port()
+            .map(|port| format!(":{port}"))
+            .unwrap_or_default();
+        let path = url.path().trim_end_matches('/');
+        let (prefix, project) = path
+            .rsplit_once('/')
+            .filter(|(_, project)| !project.is_empty())
+            .ok_or_else(|| eyre!("crash_reporting.dsn is missing the project id"))?;
+        Ok((
+            format!(
+                "{}://{host}{port}{prefix}/api/{project}/store/",
+                url.scheme()
+            ),
+            key.to_string(),
+        ))
+    }
+
+    /// Reports panics in addition to whatever the current panic hook does.
+    pub fn install_panic_hook(&self) {
+        let reporter = self.clone();
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |panic_info| {
+            let message = panic_info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic_info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Box<dyn Any>".to_string());
+            let location = panic_info
+                .location()
+                .map(|location| location.to_string())
+                .unwrap_or_default();
+            reporter.send("fatal", "panic", &format!("{message} at {location}"));
+            previous(panic_info);
+        }));
+    }
+
+    /// Reports an error that ended the application.
+    pub fn capture_error(&self, err: &color_eyre::Report) {
+        self.send("error", "error", &format!("{err:#}"));
+    }
+
+    fn send(&self, level: &str, kind: &str, message: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        let event = json!({
+            "event_id": format!("{:032x}", rand::thread_rng().r#gen::<u128>()),
+            "timestamp": timestamp,
+            "level": level,
+            "platform": "native",
+            "logger": "syres",
+            "release": format!("syres@{}", env!("CARGO_PKG_VERSION")),
+            "environment": self.environment,
+            "tags": {
+                "os": std::env::consts::OS,
+                "arch": std::env::consts::ARCH,
+            },
+            "exception": {
+                "values": [{ "type": kind, "value": message }],
+            },
+        });
+
+        // The blocking client can't run inside an async context, so send from a fresh thread.
+        let reporter = self.clone();
+        let sent = thread::spawn(move || {
+            reqwest::blocking::Client::builder()
+                .timeout(SEND_TIMEOUT)
+                .build()?
+                .post(&reporter.store_url)
+                .header("X-Sentry-Auth", &reporter.auth_header)
+                .json(&event)
+                .send()?
+                .error_for_status()
+        })
+        .join();
+        match sent {
+            Ok(Ok(_)) => info!("sent crash report"),
+            Ok(Err(err)) => warn!("failed to send crash report: {err}"),
+            Err(_) => warn!("failed to send crash report"),
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    collections::HashSet,
+    thread,
+    time::{Duration, Instant},
+};
+
+use color_eyre::eyre::{bail, eyre};
+use time::{Date, PrimitiveDateTime, Time};
+use tracing::{error, info};
+
+use crate::{
+    clock,
+    config::{Config, NotifyEvent},
+    grid,
+    notify::{self, Message},
+    openings::{self, Opening},
+    skedda::{MyBooking, Skedda, SpaceDetails},
+};
+
+/// How often the daemon wakes to see whether anything is due. Waking rather than sleeping
+/// until the digest keeps it on time across suspends and clock changes.
+const WAKE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the daemon re-reads the venues' spaces for their advance-booking windows.
+const SPACES_REFRESH: Duration = Duration::from_secs(60 * 60);
+
+/// Runs until killed, sending the morning digest once a day at the configured time and a
+/// heads-up before each day comes within a venue's advance-booking window, for whichever of
+/// them are routed. With `now`, sends them once straight away and exits, to try out the
+/// channels.
+pub fn run(config: &Config, now: bool) -> color_eyre::Result<()> {
+    let digest = !config.notify.channels(NotifyEvent::Digest).is_empty();
+    let opening_alerts = !config.notify.channels(NotifyEvent::Opening).is_empty();
+    if !digest && !opening_alerts {
+        bail!(
+            "nothing to send: route the digest or openings to a channel in config, \
+             e.g. digest = [\"desktop\"] under [notify.routes]"
+        );
+    }
+    if now {
+        if digest {
+            send_digest(config)?;
+        }
+        if opening_alerts {
+            let now = clock::now();
+            for (venue, opening) in next_openings(&venue_spaces(config), now) {
+                send_opening(config, venue, &opening, now)?;
+            }
+        }
+        return Ok(());
+    }
+    let at = grid::parse_time(&config.digest.time)
+        .ok_or_else(|| eyre!("digest.time {:?} isn't a time", config.digest.time))?;
+    let lead = time::Duration::minutes(config.openings.lead_minutes.into());
+    info!(at = %grid::format_time(at), digest, opening_alerts, "daemon started");
+    // Started after today's digest time: the first one goes out tomorrow.
+    let mut last_sent = Some(clock::now().date()).filter(|_| clock::now().time() >= at);
+    let mut spaces = Vec::new();
+    let mut spaces_at: Option<Instant> = None;
+    let mut alerted = HashSet::new();
+    loop {
+        let now = clock::now();
+        if digest && due(now, at, last_sent) {
+            last_sent = Some(now.date());
+            if let Err(err) = send_digest(config) {
+                error!("failed to send the morning digest: {err:#}");
+            }
+        }
+        if opening_alerts {
+            if spaces_at.is_none_or(|at| at.elapsed() >= SPACES_REFRESH) {
+                spaces = venue_spaces(config);
+                spaces_at = Some(Instant::now());
+            }
+            for (venue, opening) in next_openings(&spaces, now) {
+                if opening.at - lead <= now && alerted.insert((venue.to_string(), opening.at)) {
+                    if let Err(err) = send_opening(config, venue, &opening, now) {
+                        error!("failed to send the booking window alert: {err:#}");
+                    }
+                }
+            }
+        }
+        thread::sleep(WAKE_INTERVAL);
+    }
+}
+
+/// Whether the digest for `now`'s day should go out.
+fn due(now: PrimitiveDateTime, at: Time, last_sent: Option<Date>) -> bool {
+    now.time() >= at && last_sent != Some(now.date())
+}
+
+fn send_digest(config: &Config) -> color_eyre::Result<()> {
+    let today = clock::now().date();
+    let venues: Vec<(&str, Result<Vec<MyBooking>, String>)> = config
+        .venues
+        .iter()
+        .map(|venue| {
+            let bookings = Skedda::new(venue)
+                .get_my_bookings(&venue.locations)
+                .map_err(|err| err.to_string());
+            (venue.name.as_str(), bookings)
+        })
+        .collect();
+    let (title, body) = digest(today, &venues);
+    notify::notify(
+        &config.notify,
+        NotifyEvent::Digest,
+        &Message { title, body },
+    )
+}
+
+/// The spaces at each venue's configured locations, for watching their booking windows.
+/// Venues that can't be reached are logged and left out until the next look.
+fn venue_spaces(config: &Config) -> Vec<(String, Vec<SpaceDetails>)> {
+    config
+        .venues
+        .iter()
+        .filter_map(|venue| {
+            let webs = match Skedda::new(venue).get_booking_data() {
+                Ok(webs) => webs,
+                Err(err) => {
+                    error!(venue = venue.name, "failed to check booking windows: {err}");
+                    return None;
+                }
+            };
+            let space_ids: HashSet<String> = venue
+                .locations
+                .iter()
+                .flat_map(|location| Skedda::location_space_ids(&webs, location))
+                .collect();
+            let mut spaces: Vec<SpaceDetails> = Skedda::space_details(&webs)
+                .into_iter()
+                .filter(|(space_id, _)| space_ids.contains(space_id))
+                .map(|(_, space)| space)
+                .collect();
+            spaces.sort_by(|a, b| a.name.cmp(&b.name));
+            Some((venue.name.clone(), spaces))
+        })
+        .collect()
+}
+
+/// Each venue's next opening after `now`, for those whose spaces have a booking window.
+fn next_openings(
+    venues: &[(String, Vec<SpaceDetails>)],
+    now: PrimitiveDateTime,
+) -> Vec<(&str, Opening)> {
+    venues
+        .iter()
+        .filter_map(|(venue, spaces)| Some((venue.as_str(), openings::next(spaces, now)?)))
+        .collect()
+}
+
+fn send_opening(
+    config: &Config,
+    venue: &str,
+    opening: &Opening,
+    now: PrimitiveDateTime,
+) -> color_eyre::Result<()> {
+    notify::notify(
+        &config.notify,
+        NotifyEvent::Opening,
+        &Message::opening(venue, opening, now),
+    )
+}
+
+/// The digest's title and body: each venue's bookings for `date`, or why they couldn't be
+/// checked.
+fn digest(date: Date, venues: &[(&str, Result<Vec<MyBooking>, String>)]) -> (String, String) {
+    let title = format!("Your bookings for {} {date}", date.weekday());
+    let mut lines = Vec::new();
+    for (venue, bookings) in venues {
+        if venues.len() > 1 {
+            lines.push(venue.to_string());
+        }
+        let today: Vec<&MyBooking> = match bookings {
+            Ok(bookings) => bookings
+                .iter()
+                .filter(|booking| booking.start.date() == date)
+                .collect(),
+            Err(err) => {
+                lines.push(format!("  Couldn't check: {err}"));
+                continue;
+            }
+        };
+        if today.is_empty() {
+            lines.push("  No bookings today".to_string());
+        }
+        for booking in today {
+            let place = match &booking.location {
+                Some(location) => format!("{} @ {location}", booking.space),
+                None => booking.space.clone(),
+            };
+            lines.push(format!(
+                "  {}–{} {place}",
+                grid::format_time(booking.start.time()),
+                grid::format_time(booking.end.time())
+            ));
+        }
+    }
+    let body = lines
+        .iter()
+        .map(|line| {
+            if venues.len() > 1 {
+                line
+            } else {
+                line.trim_start()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    (title, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{date, datetime, time};
+
+    use super::*;
+
+    fn booking(space: &str, location: Option<&str>, start: PrimitiveDateTime) -> MyBooking {
+        MyBooking {
+            id: None,
+            space: space.to_string(),
+            location: location.map(str::to_string),
+            start,
+            end: start + time::Duration::HOUR,
+            title: None,
+        }
+    }
+
+    #[test]
+    fn sends_once_a_day_from_the_set_time() {
+        let at = time!(7:30);
+        assert!(!due(datetime!(2024-05-01 7:29), at, None));
+        assert!(due(datetime!(2024-05-01 7:30), at, None));
+        assert!(!due(
+            datetime!(2024-05-01 9:00),
+            at,
+            Some(date!(2024 - 05 - 01))
+        ));
+        assert!(due(
+            datetime!(2024-05-02 7:31),
+            at,
+            Some(date!(2024 - 05 - 01))
+        ));
+    }
+
+    #[test]
+    fn lists_todays_bookings_by_venue() {
+        let venues = [
+            (
+                "Switchyards",
+                Ok(vec![
+                    booking("Boardroom", Some("Decatur"), datetime!(2024-05-01 9:00)),
+                    booking("Desk 1", None, datetime!(2024-05-02 9:00)),
+                ]),
+            ),
+            ("Other Space", Ok(Vec::new())),
+            ("Broken", Err("HTTP 503".to_string())),
+        ];
+        let (title, body) = digest(date!(2024 - 05 - 01), &venues);
+        assert_eq!(title, "Your bookings for Wednesday 2024-05-01");
+        assert_eq!(
+            body,
+            "Switchyards\n  09:00–10:00 Boardroom @ Decatur\n\
+             Other Space\n  No bookings today\n\
+             Broken\n  Couldn't check: HTTP 503"
+        );
+    }
+
+    #[test]
+    fn one_venue_needs_no_headings() {
+        let venues = [(
+            "Switchyards",
+            Ok(vec![booking("Desk 1", None, datetime!(2024-05-01 13:00))]),
+        )];
+        let (_, body) = digest(date!(2024 - 05 - 01), &venues);
+        assert_eq!(body, "13:00–14:00 Desk 1");
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    clock,
+    config::Config,
+    logging::{self, RotatingFile},
+    paths::{HOME_ENV, Paths},
+    session::{KeySource, PASSPHRASE_ENV},
+    skedda::Skedda,
+};
+
+/// Prints diagnostics about the local setup.
+pub fn run(config: &Config, verbose: u8, log_path: &Path) -> color_eyre::Result<()> {
+    println!("syres {}", env!("CARGO_PKG_VERSION"));
+
+    match Paths::resolve() {
+        Some(paths) => {
+            let config_file = paths.config_file();
+            if config_file.exists() {
+                println!("config:      {}", config_file.display());
+            } else {
+                println!(
+                    "config:      {} (not found, using defaults)",
+                    config_file.display()
+                );
+            }
+            println!("data dir:    {}", paths.data_dir.display());
+            println!("cache dir:   {}", paths.cache_dir.display());
+            println!("daemon log:  {}", paths.daemon_log_file().display());
+        }
+        None => println!("config:      unknown location, using defaults"),
+    }
+    if let Some(root) = env::var_os(HOME_ENV) {
+        println!("{HOME_ENV}:  {}", PathBuf::from(root).display());
+    }
+
+    println!("log file:    {}", log_path.display());
+    println!("log filter:  {}", logging::filter(verbose, &config.log)?);
+    println!(
+        "log limits:  {} MB x {} files{}{}",
+        config.log.max_size_mb,
+        config.log.max_files,
+        config
+            .log
+            .max_age_days
+            .map(|days| format!(", {days} days"))
+            .unwrap_or_default(),
+        if config.log.compress { ", gzipped" } else { "" },
+    );
+    println!(
+        "rotated:     {} files",
+        RotatingFile::rotated_files(log_path).len()
+    );
+    println!(
+        "session key: {}",
+        match KeySource::current() {
+            KeySource::Keychain => "OS keychain".to_string(),
+            KeySource::Passphrase => format!("passphrase from {PASSPHRASE_ENV}"),
+        }
+    );
+    println!(
+        "crash reports: {}",
+        if config.crash_reporting.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    print_clock(config);
+    Ok(())
+}
+
+/// Compares the local clock with the first venue's, since bookings timed to the second go
+/// wrong on a machine whose clock has drifted.
+fn print_clock(config: &Config) {
+    let Some(venue) = config.venues.first() else {
+        println!("clock:       no venue to compare with");
+        return;
+    };
+    if let Err(err) = Skedda::new(venue).warm_up() {
+        println!("clock:       couldn't reach {}: {err}", venue.name);
+        return;
+    }
+    match clock::skew() {
+        None => println!("clock:       {} didn't say what time it is", venue.name),
+        Some(skew) if clock::notable_skew().is_some() => println!(
+            "clock:       {} compared with {}; fix the system clock or bookings may misfire",
+            clock::describe_skew(skew),
+            venue.name
+        ),
+        Some(skew) => println!(
+            "clock:       {} with {}",
+            clock::describe_skew(skew),
+            venue.name
+        ),
+    }
+}
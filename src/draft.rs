@@ -0,0 +1,180 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+use tracing::warn;
+
+/// A half-made booking, saved as the booking form is edited so leaving it doesn't lose the
+/// work. There's at most one per location.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookingDraft {
+    /// The venue's key, as for its session and cache.
+    pub venue: String,
+    pub location: String,
+    pub space_id: String,
+    #[serde(with = "minutes")]
+    pub start: PrimitiveDateTime,
+    #[serde(with = "minutes")]
+    pub end: PrimitiveDateTime,
+    /// The member an admin was booking for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub booked_for: Option<String>,
+}
+
+/// The drafts file, one JSON list of drafts.
+#[derive(Debug, Clone)]
+pub struct DraftStore {
+    path: PathBuf,
+}
+
+impl DraftStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The draft for the location, unless its time has already started.
+    pub fn find(
+        &self,
+        venue: &str,
+        location: &str,
+        now: PrimitiveDateTime,
+    ) -> Option<BookingDraft> {
+        self.read()
+            .into_iter()
+            .find(|draft| draft.venue == venue && draft.location == location)
+            .filter(|draft| draft.start > now)
+    }
+
+    /// Saves the draft in place of any other for its location, dropping drafts that have
+    /// started by `now`.
+    pub fn save(&self, draft: &BookingDraft, now: PrimitiveDateTime) -> Result<()> {
+        let mut drafts = self.read();
+        drafts.retain(|other| {
+            other.start > now && (other.venue != draft.venue || other.location != draft.location)
+        });
+        drafts.push(draft.clone());
+        self.write(&drafts)
+    }
+
+    /// Forgets the location's draft, e.g. once it's been booked.
+    pub fn discard(&self, venue: &str, location: &str) -> Result<()> {
+        let mut drafts = self.read();
+        let len = drafts.len();
+        drafts.retain(|draft| draft.venue != venue || draft.location != location);
+        if drafts.len() == len {
+            return Ok(());
+        }
+        self.write(&drafts)
+    }
+
+    /// Every saved draft; none when the file is missing or unreadable.
+    fn read(&self) -> Vec<BookingDraft> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+            Err(err) => {
+                warn!(path = %self.path.display(), "failed to read drafts: {err}");
+                return Vec::new();
+            }
+        };
+        serde_json::from_slice(&contents).unwrap_or_else(|err| {
+            warn!(path = %self.path.display(), "discarding unreadable drafts: {err}");
+            Vec::new()
+        })
+    }
+
+    fn write(&self, drafts: &[BookingDraft]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(drafts)?)
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("replacing {}", self.path.display()))
+    }
+}
+
+/// Times as "2024-05-01T09:30"; bookings don't need seconds.
+mod minutes {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+    use time::{
+        PrimitiveDateTime, format_description::BorrowedFormatItem, macros::format_description,
+    };
+
+    const FORMAT: &[BorrowedFormatItem] =
+        format_description!("[year]-[month]-[day]T[hour]:[minute]");
+
+    pub fn serialize<S: Serializer>(
+        value: &PrimitiveDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let text = value.format(FORMAT).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PrimitiveDateTime, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        PrimitiveDateTime::parse(&text, FORMAT).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    /// An empty store in a directory of its own.
+    fn store() -> (tempfile::TempDir, DraftStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DraftStore::new(dir.path().join("drafts.json"));
+        (dir, store)
+    }
+
+    fn draft(location: &str, start: PrimitiveDateTime) -> BookingDraft {
+        BookingDraft {
+            venue: "switchyards".to_string(),
+            location: location.to_string(),
+            space_id: "101".to_string(),
+            start,
+            end: start + time::Duration::HOUR,
+            booked_for: None,
+        }
+    }
+
+    #[test]
+    fn keeps_one_draft_per_location() {
+        let (_dir, store) = store();
+        let now = datetime!(2024-05-01 8:00);
+        let decatur = draft("Decatur", datetime!(2024-05-01 9:00));
+        store.save(&decatur, now).unwrap();
+        store
+            .save(&draft("Midtown", datetime!(2024-05-01 9:00)), now)
+            .unwrap();
+        let moved = draft("Decatur", datetime!(2024-05-01 13:30));
+        store.save(&moved, now).unwrap();
+
+        assert_eq!(store.find("switchyards", "Decatur", now), Some(moved));
+        assert!(store.find("elsewhere", "Decatur", now).is_none());
+        store.discard("switchyards", "Midtown").unwrap();
+        assert!(store.find("switchyards", "Midtown", now).is_none());
+        assert!(
+            fs::read_to_string(&store.path)
+                .unwrap()
+                .contains("\"start\": \"2024-05-01T13:30\"")
+        );
+    }
+
+    #[test]
+    fn started_drafts_are_not_offered() {
+        let (_dir, store) = store();
+        let start = datetime!(2024-05-01 9:00);
+        store
+            .save(&draft("Decatur", start), datetime!(2024-05-01 8:00))
+            .unwrap();
+        assert!(store.find("switchyards", "Decatur", start).is_none());
+    }
+}
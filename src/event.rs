@@ -1,14 +1,25 @@
 use color_eyre::eyre::WrapErr;
+use image::DynamicImage;
 use ratatui::crossterm::event::{self, Event as CrosstermEvent};
 use std::{
+    collections::HashMap,
     sync::mpsc,
     thread,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
+use time::PrimitiveDateTime;
 
-const TICK_FPS: f64 = 30.0;
+use crate::{
+    availability::LocationAvailability,
+    models::{CreatedBooking, WebsResponse},
+    skedda::{MyBooking, SkeddaError, SpaceDetails},
+};
+
+/// How many times a second the app gets a tick.
+pub const TICK_FPS: f64 = 30.0;
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum Event {
     Tick,
     Crossterm(CrosstermEvent),
@@ -16,8 +27,71 @@ pub enum Event {
 }
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum AppEvent {
     Quit,
+    /// The saved session for the venue at the given index was read from disk at startup and
+    /// expires at the given time, or there is none.
+    SessionLoaded(usize, Option<SystemTime>),
+    /// The session for the venue at the given index was refreshed in the background and now
+    /// expires at the given time.
+    SessionRefreshed(usize, Option<SystemTime>),
+    /// A background session refresh for the venue at the given index failed.
+    SessionRefreshFailed(usize, String),
+    /// A keep-alive visit to the venue at the given index kept its session going; it now
+    /// expires at the given time.
+    SessionKeptAlive(usize, Option<SystemTime>),
+    /// A keep-alive visit to the venue at the given index found the session couldn't be kept.
+    KeepAliveFailed(usize, String),
+    /// Spaces and session expiry fetched for the venue at the given index.
+    VenueDataLoaded(usize, HashMap<String, SpaceDetails>, Option<SystemTime>),
+    /// Fetching data for the venue at the given index failed.
+    VenueDataFailed(usize, String),
+    /// The locations the venue at the given index tags its spaces with.
+    LocationsLoaded(usize, Vec<String>),
+    /// The venue's `/webs` data, by venue index, fetched for the booking form, the session
+    /// expiry that came with it and when it was fetched, which is earlier when it came from
+    /// the cache.
+    BookingDataLoaded(usize, Box<WebsResponse>, Option<SystemTime>, SystemTime),
+    /// A request for the booking form at the venue with the given index failed.
+    RequestFailed(usize, SkeddaError),
+    /// The logged-in account's own bookings at the venue with the given index, and when
+    /// they were fetched.
+    MyBookingsLoaded(usize, Vec<MyBooking>, SystemTime),
+    /// The venue at the given index couldn't be reached, so what's shown of it comes from
+    /// its data as last fetched, at the given time.
+    Offline(usize, SystemTime),
+    /// A space photo at the venue, by URL, downloaded and scaled down to a thumbnail.
+    SpaceImageLoaded(usize, String, DynamicImage),
+    /// Downloading or decoding the space photo at the venue and URL failed.
+    SpaceImageFailed(usize, String, String),
+    /// The outcome of cancelling each booking, by id, in a bulk cancellation.
+    BookingsCancelled(Vec<(String, Result<(), String>)>),
+    /// Availability of the venue's locations, by venue index, for the overview.
+    OverviewLoaded(usize, Vec<LocationAvailability>),
+    /// Fetching the venue at the given index for the overview failed.
+    OverviewFailed(usize, String),
+    /// Availability of the venue's locations, by venue index, for the window the booking
+    /// form couldn't book, or why the venue couldn't be checked.
+    AlternativesLoaded(
+        usize,
+        (PrimitiveDateTime, PrimitiveDateTime),
+        Result<Vec<LocationAvailability>, String>,
+    ),
+    /// The booking submitted at the venue with the given index was made.
+    BookingCreated(usize, CreatedBooking),
+    /// Booking at the venue with the given index failed, or it's unknown whether it was made.
+    BookingFailed(usize, SkeddaError),
+    /// A bulk cancellation couldn't start, e.g. because the session had expired.
+    CancellationFailed(String),
+    /// How cancelling one of my bookings at the venue with the given index went.
+    MyBookingCancelled(usize, MyBooking, Result<(), String>),
+    /// One booking of a batch, by its index, was sent to the venue with the given index.
+    BatchItemSent(usize, usize, Result<CreatedBooking, String>),
+    /// The outcome of cancelling each booking, by id, made by a batch being rolled back.
+    BatchRolledBack(usize, Vec<(String, Result<(), String>)>),
+    /// Rolling back a batch couldn't start.
+    BatchRollbackFailed(usize, String),
 }
 
 #[derive(Debug)]
@@ -26,6 +100,12 @@ pub struct EventHandler {
     receiver: mpsc::Receiver<Event>,
 }
 
+impl Default for EventHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EventHandler {
     pub fn new() -> Self {
         let (sender, receiver) = mpsc::channel();
@@ -41,6 +121,11 @@ impl EventHandler {
     pub fn send(&mut self, app_event: AppEvent) {
         let _ = self.sender.send(Event::App(app_event));
     }
+
+    /// A sender for background work to report back to the app with.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.sender.clone()
+    }
 }
 
 struct EventThread {
@@ -0,0 +1,130 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Every `/webs` payload in `tests/fixtures/webs`.
+pub const WEBS: [&str; 4] = [
+    "small_venue.json",
+    "large_venue.json",
+    "empty_space_tags.json",
+    "quirks.json",
+];
+
+/// The raw body of a `/webs` fixture.
+pub fn webs(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/webs")
+        .join(name);
+    fs::read(&path).unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()))
+}
+
+/// A `/webs` fixture parsed into the full model.
+pub fn webs_response(name: &str) -> crate::models::WebsResponse {
+    serde_json::from_slice(&webs(name))
+        .unwrap_or_else(|err| panic!("failed to parse {name}: {err}"))
+}
+
+/// A golden request body from `tests/fixtures/requests`.
+pub fn request(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/requests")
+        .join(name);
+    fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()))
+}
+
+/// A recorded response from `tests/fixtures/http`.
+pub fn http(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/http")
+        .join(name);
+    fs::read(&path).unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()))
+}
+
+/// A local HTTP server that answers each `"METHOD /path"` route with a recorded response and
+/// 404s anything else, remembering the requests it saw.
+pub struct ReplayServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl ReplayServer {
+    pub fn start(routes: &[(&str, &str)]) -> Self {
+        let routes: HashMap<String, Vec<u8>> = routes
+            .iter()
+            .map(|(route, name)| (route.to_string(), http(name)))
+            .collect();
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind replay server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let Some(route) = read_request(&stream) else {
+                    continue;
+                };
+                let response = routes
+                    .get(&route)
+                    .map_or_else(|| b"HTTP/1.1 404 Not Found\n\n".to_vec(), Clone::clone);
+                seen.lock().unwrap().push(route);
+                let _ = write_response(stream, &response);
+            }
+        });
+        Self { base_url, requests }
+    }
+
+    /// The `"METHOD /path"` of every request so far, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<String> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let route = format!("{} {}", parts.next()?, parts.next()?);
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(route)
+}
+
+/// Sends a recorded response: its head with `\r\n` line endings and the right length, then
+/// its body.
+fn write_response(mut stream: TcpStream, recorded: &[u8]) -> io::Result<()> {
+    let text = String::from_utf8_lossy(recorded);
+    let (head, body) = text.split_once("\n\n").unwrap_or((&text, ""));
+    let mut response = String::new();
+    for line in head.lines() {
+        response.push_str(line);
+        response.push_str("\r\n");
+    }
+    response.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+    stream.write_all(response.as_bytes())?;
+    stream.write_all(body.as_bytes())
+}
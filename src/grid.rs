@@ -0,0 +1,349 @@
+use time::{Date, Duration, Month, PrimitiveDateTime, Time};
+
+use crate::{availability, config::GridConfig, models::Booking};
+
+/// One day of bookable time slots, the columns of the availability grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeGrid {
+    pub date: Date,
+    /// Start of the first slot.
+    pub start: Time,
+    /// Length of each slot in minutes.
+    pub slot_minutes: u32,
+    pub slots: usize,
+}
+
+impl TimeGrid {
+    /// Lays out the day with the configured granularity, widened to a multiple of the venue's
+    /// booking increment so every cell can actually be booked.
+    pub fn new(date: Date, config: &GridConfig, booking_increment: Option<u32>) -> Self {
+        let slot_minutes = slot_minutes(config.granularity_minutes, booking_increment);
+        let minutes = (u32::from(config.end_hour) - u32::from(config.start_hour)) * 60;
+        Self {
+            date,
+            start: Time::from_hms(config.start_hour, 0, 0).unwrap_or(Time::MIDNIGHT),
+            slot_minutes,
+            slots: (minutes / slot_minutes) as usize,
+        }
+    }
+
+    pub fn slot_start(&self, slot: usize) -> PrimitiveDateTime {
+        PrimitiveDateTime::new(self.date, self.start)
+            + Duration::minutes(i64::from(self.slot_minutes) * slot as i64)
+    }
+
+    pub fn slot_end(&self, slot: usize) -> PrimitiveDateTime {
+        self.slot_start(slot + 1)
+    }
+
+    /// The slot containing `time`, if it falls within the grid.
+    pub fn slot_at(&self, time: PrimitiveDateTime) -> Option<usize> {
+        let offset = u64::try_from((time - self.slot_start(0)).whole_minutes()).ok()?;
+        let slot = (offset / u64::from(self.slot_minutes)) as usize;
+        (slot < self.slots).then_some(slot)
+    }
+
+    /// Whether any booking of the space overlaps the slot.
+    pub fn is_booked(&self, bookings: &[Booking], space_id: &str, slot: usize) -> bool {
+        !availability::is_free(
+            bookings,
+            space_id,
+            self.slot_start(slot),
+            self.slot_end(slot),
+        )
+    }
+}
+
+/// The configured granularity rounded up to a multiple of the booking increment.
+pub fn slot_minutes(granularity: u32, booking_increment: Option<u32>) -> u32 {
+    match booking_increment {
+        Some(increment) if increment > 0 => granularity.div_ceil(increment) * increment,
+        _ => granularity,
+    }
+    .max(1)
+}
+
+pub fn format_time(time: Time) -> String {
+    format!("{:02}:{:02}", time.hour(), time.minute())
+}
+
+/// Rounds a time of day to the nearest multiple of `increment` minutes.
+pub fn snap(time: Time, increment: u32) -> Time {
+    let increment = increment.max(1);
+    let minutes = u32::from(time.hour()) * 60 + u32::from(time.minute());
+    let last = (24 * 60 - 1) / increment * increment;
+    let snapped = ((minutes + increment / 2) / increment * increment).min(last);
+    Time::from_hms((snapped / 60) as u8, (snapped % 60) as u8, 0).unwrap_or(time)
+}
+
+/// The first whole or half hour at or after `now`.
+pub fn next_half_hour(now: PrimitiveDateTime) -> PrimitiveDateTime {
+    let hour = now.replace_time(Time::from_hms(now.hour(), 0, 0).unwrap_or(now.time()));
+    [0, 30, 60]
+        .into_iter()
+        .map(|minutes| hour + Duration::minutes(minutes))
+        .find(|start| *start >= now)
+        .unwrap_or(hour + Duration::HOUR)
+}
+
+/// Reads a typed time such as `9`, `930`, `9:30`, `14:15` or `2:15pm`.
+pub fn parse_time(input: &str) -> Option<Time> {
+    read_time(input).ok()
+}
+
+/// Reads a typed time like [`parse_time`], or says how it read one that isn't a time and
+/// what's wrong with it, e.g. "read as 13:00pm, but pm hours go from 1 to 12".
+pub fn read_time(input: &str) -> Result<Time, String> {
+    let input = input.trim().to_ascii_lowercase();
+    let (digits, suffix) = match input.strip_suffix("pm").or_else(|| input.strip_suffix('p')) {
+        Some(digits) => (digits, "pm"),
+        None => match input.strip_suffix("am").or_else(|| input.strip_suffix('a')) {
+            Some(digits) => (digits, "am"),
+            None => (input.as_str(), ""),
+        },
+    };
+    let digits = digits.trim();
+    let number = |text: &str, what: &str| {
+        text.parse::<u32>()
+            .map_err(|_| format!("{text:?} isn't a number of {what}"))
+    };
+    let (hour, minute) = match digits.split_once(':') {
+        Some((hour, minute)) => (number(hour, "hours")?, number(minute, "minutes")?),
+        None if digits.is_empty() => return Err("there's no time in it".to_string()),
+        None if !digits.bytes().all(|byte| byte.is_ascii_digit()) => {
+            return Err(format!("{digits:?} isn't a time"));
+        }
+        None if digits.len() <= 2 => (number(digits, "hours")?, 0),
+        None => {
+            let (hour, minute) = digits.split_at(digits.len() - 2);
+            (number(hour, "hours")?, number(minute, "minutes")?)
+        }
+    };
+    let read = format!("read as {hour}:{minute:02}{suffix}");
+    let hour = match suffix {
+        "" => hour,
+        _ if (13..24).contains(&hour) => {
+            return Err(format!(
+                "{read}, but {suffix} hours go from 1 to 12; did you mean {hour}:{minute:02}?"
+            ));
+        }
+        _ if !(1..=12).contains(&hour) => {
+            return Err(format!("{read}, but {suffix} hours go from 1 to 12"));
+        }
+        "pm" => hour % 12 + 12,
+        _ => hour % 12,
+    };
+    if hour > 23 {
+        return Err(format!("{read}, but hours go up to 23"));
+    }
+    if minute > 59 {
+        return Err(format!("{read}, but minutes go up to 59"));
+    }
+    Time::from_hms(hour as u8, minute as u8, 0).map_err(|err| format!("{read}: {err}"))
+}
+
+/// Reads a day such as `2025-08-01`, `today`, `tomorrow` or a weekday like `fri`, which is
+/// the next one from `today` on, or says what's wrong with one that isn't a day.
+pub fn read_date(input: &str, today: Date) -> Result<Date, String> {
+    let input = input.trim().to_ascii_lowercase();
+    match input.as_str() {
+        "" => return Err("there's no date in it".to_string()),
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::DAY),
+        _ => {}
+    }
+    let mut weekday = today.weekday();
+    for days in 0..7 {
+        let name = weekday.to_string().to_ascii_lowercase();
+        if input.len() >= 3 && name.starts_with(&input) {
+            return Ok(today + Duration::days(days));
+        }
+        weekday = weekday.next();
+    }
+    let parts: Vec<&str> = input.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(format!(
+            "{input:?} isn't a date; write it as YYYY-MM-DD, e.g. {today}"
+        ));
+    };
+    let number = |text: &str, what: &str| {
+        text.parse::<u32>()
+            .map_err(|_| format!("{text:?} isn't a number of {what}"))
+    };
+    let (year, month, day) = (
+        number(year, "years")?,
+        number(month, "months")?,
+        number(day, "days")?,
+    );
+    if year < 1000 {
+        return Err(format!("read the year as {year}; write all four digits"));
+    }
+    let read = format!("read as {year}-{month:02}-{day:02}");
+    let Ok(month) = Month::try_from(u8::try_from(month).unwrap_or(0)) else {
+        return Err(format!("{read}, but months go from 1 to 12"));
+    };
+    let length = month.length(year as i32);
+    if day == 0 || day > u32::from(length) {
+        return Err(format!("{read}, but {month} {year} has {length} days"));
+    }
+    Date::from_calendar_date(year as i32, month, day as u8).map_err(|err| format!("{read}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use time::macros::date;
+
+    use super::*;
+
+    /// Any time of day, to the minute.
+    fn any_time() -> impl Strategy<Value = Time> {
+        (0..24u8, 0..60u8).prop_map(|(hour, minute)| Time::from_hms(hour, minute, 0).unwrap())
+    }
+
+    /// A day's grid of any shape the config and the venue's booking increment can give it.
+    fn any_grid() -> impl Strategy<Value = TimeGrid> {
+        (0..23u8)
+            .prop_flat_map(|start_hour| {
+                (
+                    prop::sample::select(vec![15, 30, 60]),
+                    Just(start_hour),
+                    start_hour + 1..=24,
+                    prop::sample::select(vec![
+                        None,
+                        Some(5),
+                        Some(10),
+                        Some(15),
+                        Some(20),
+                        Some(45),
+                    ]),
+                )
+            })
+            .prop_map(|(granularity_minutes, start_hour, end_hour, increment)| {
+                let config = GridConfig {
+                    granularity_minutes,
+                    start_hour,
+                    end_hour,
+                };
+                TimeGrid::new(date!(2024 - 05 - 01), &config, increment)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn slots_are_bookable_and_at_least_as_wide_as_asked(
+            granularity in prop::sample::select(vec![15, 30, 60]),
+            increment in 1..=120u32,
+        ) {
+            let minutes = slot_minutes(granularity, Some(increment));
+            prop_assert_eq!(minutes % increment, 0);
+            prop_assert!(minutes >= granularity && minutes < granularity + increment);
+        }
+
+        #[test]
+        fn every_slot_has_a_positive_length_and_maps_back_to_itself(grid in any_grid()) {
+            for slot in 0..grid.slots {
+                prop_assert!(grid.slot_end(slot) > grid.slot_start(slot));
+                prop_assert_eq!(grid.slot_at(grid.slot_start(slot)), Some(slot));
+                prop_assert_eq!(
+                    grid.slot_at(grid.slot_end(slot) - Duration::MINUTE),
+                    Some(slot)
+                );
+            }
+            prop_assert_eq!(grid.slot_at(grid.slot_start(0) - Duration::MINUTE), None);
+            prop_assert_eq!(grid.slot_at(grid.slot_start(grid.slots)), None);
+        }
+
+        #[test]
+        fn snapping_lands_on_the_nearest_increment_within_the_day(
+            time in any_time(),
+            increment in 1..=120u32,
+        ) {
+            let snapped = snap(time, increment);
+            let minutes = |time: Time| i64::from(time.hour()) * 60 + i64::from(time.minute());
+
+            prop_assert_eq!(minutes(snapped) % i64::from(increment), 0);
+            let last = (24 * 60 - 1) / i64::from(increment) * i64::from(increment);
+            if minutes(time) <= last {
+                prop_assert!(
+                    (minutes(snapped) - minutes(time)).abs() <= i64::from(increment) / 2 + 1,
+                    "{} snapped to {}",
+                    time,
+                    snapped
+                );
+            }
+        }
+
+        #[test]
+        fn next_half_hour_is_the_first_one_not_past(time in any_time(), second in 0..60u8) {
+            let now = date!(2024 - 05 - 01)
+                .with_time(time)
+                .replace_second(second)
+                .unwrap();
+            let start = next_half_hour(now);
+            prop_assert!(start >= now && start - now < Duration::minutes(30));
+            prop_assert_eq!((start.minute() % 30, start.second()), (0, 0));
+        }
+
+        #[test]
+        fn formatted_times_parse_back(time in any_time()) {
+            prop_assert_eq!(parse_time(&format_time(time)), Some(time));
+            let twelve_hour = format!(
+                "{}:{:02}{}",
+                (time.hour() + 11) % 12 + 1,
+                time.minute(),
+                if time.hour() < 12 { "am" } else { "pm" }
+            );
+            prop_assert_eq!(parse_time(&twelve_hour), Some(time));
+        }
+    }
+
+    #[test]
+    fn explains_what_is_wrong_with_a_time() {
+        let reason = |input| read_time(input).unwrap_err();
+        assert_eq!(
+            reason("13pm"),
+            "read as 13:00pm, but pm hours go from 1 to 12; did you mean 13:00?"
+        );
+        assert_eq!(
+            reason("0am"),
+            "read as 0:00am, but am hours go from 1 to 12"
+        );
+        assert_eq!(reason("2460"), "read as 24:60, but hours go up to 23");
+        assert_eq!(reason("9:75"), "read as 9:75, but minutes go up to 59");
+        assert_eq!(reason("noon"), "\"noon\" isn't a time");
+        assert_eq!(reason("9:3o"), "\"3o\" isn't a number of minutes");
+        assert_eq!(reason(" pm"), "there's no time in it");
+    }
+
+    #[test]
+    fn reads_dates_and_explains_bad_ones() {
+        // A Wednesday.
+        let today = date!(2024 - 05 - 01);
+        assert_eq!(read_date("2025-08-01", today), Ok(date!(2025 - 08 - 01)));
+        assert_eq!(read_date("Tomorrow", today), Ok(date!(2024 - 05 - 02)));
+        assert_eq!(read_date("wed", today), Ok(today));
+        assert_eq!(read_date("friday", today), Ok(date!(2024 - 05 - 03)));
+        assert_eq!(read_date("mon", today), Ok(date!(2024 - 05 - 06)));
+
+        let reason = |input| read_date(input, today).unwrap_err();
+        assert_eq!(
+            reason("01/08/2025"),
+            "\"01/08/2025\" isn't a date; write it as YYYY-MM-DD, e.g. 2024-05-01"
+        );
+        assert_eq!(
+            reason("2025-13-01"),
+            "read as 2025-13-01, but months go from 1 to 12"
+        );
+        assert_eq!(
+            reason("2025-02-29"),
+            "read as 2025-02-29, but February 2025 has 28 days"
+        );
+        assert_eq!(
+            reason("25-08-01"),
+            "read the year as 25; write all four digits"
+        );
+        assert_eq!(reason("2025-aug-01"), "\"aug\" isn't a number of months");
+        assert_eq!(reason("  "), "there's no date in it");
+    }
+}
@@ -0,0 +1,273 @@
+use std::{fs, path::PathBuf};
+
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use rusqlite::{Connection, params};
+use time::{PrimitiveDateTime, format_description::BorrowedFormatItem, macros::format_description};
+use tracing::warn;
+
+use crate::{config::VenueConfig, grid, paths::Paths, skedda::Skedda};
+
+/// Times as "2024-05-01T09:30", which sort as text in time order.
+const TIME: &[BorrowedFormatItem] = format_description!("[year]-[month]-[day]T[hour]:[minute]");
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS bookings (
+        id TEXT PRIMARY KEY,
+        space TEXT NOT NULL,
+        start TEXT NOT NULL,
+        end TEXT NOT NULL,
+        title TEXT
+    );
+    CREATE VIRTUAL TABLE IF NOT EXISTS booking_text USING fts5(id UNINDEXED, title, space);
+";
+
+/// One of my bookings as remembered locally, after Skedda has long stopped sending it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub id: String,
+    /// The booked space's name, or names when the booking covers several.
+    pub space: String,
+    pub start: PrimitiveDateTime,
+    pub end: PrimitiveDateTime,
+    pub title: Option<String>,
+}
+
+/// A venue's booking history database, with the title and space of each booking indexed for
+/// full-text search.
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Adds the bookings, or updates them if they're already there. A database that can't be
+    /// written is reported but never stops the fetch that found the bookings.
+    pub fn record(&self, entries: &[HistoryEntry]) {
+        if entries.is_empty() {
+            return;
+        }
+        if let Err(err) = self.upsert(entries) {
+            warn!(path = %self.path.display(), "failed to update booking history: {err:#}");
+        }
+    }
+
+    /// The bookings whose title or space contains every word of `query`, newest first.
+    pub fn search(&self, query: &str, limit: usize) -> color_eyre::Result<Vec<HistoryEntry>> {
+        let Some(query) = match_expression(query) else {
+            return Ok(Vec::new());
+        };
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let connection = self.open()?;
+        let mut statement = connection.prepare(
+            "SELECT bookings.id, bookings.space, bookings.start, bookings.end, bookings.title
+             FROM booking_text JOIN bookings ON bookings.id = booking_text.id
+             WHERE booking_text MATCH ?1
+             ORDER BY bookings.start DESC
+             LIMIT ?2",
+        )?;
+        let rows = statement.query_map(params![query, limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+        rows.map(|row| {
+            let (id, space, start, end, title) = row?;
+            Ok(HistoryEntry {
+                id,
+                space,
+                start: PrimitiveDateTime::parse(&start, TIME)?,
+                end: PrimitiveDateTime::parse(&end, TIME)?,
+                title,
+            })
+        })
+        .collect::<color_eyre::Result<_>>()
+        .wrap_err_with(|| format!("searching {}", self.path.display()))
+    }
+
+    /// How many bookings the history holds.
+    pub fn count(&self) -> color_eyre::Result<usize> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let count: i64 = self
+            .open()?
+            .query_row("SELECT COUNT(*) FROM bookings", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    fn upsert(&self, entries: &[HistoryEntry]) -> color_eyre::Result<()> {
+        let mut connection = self.open()?;
+        let transaction = connection.transaction()?;
+        for entry in entries {
+            transaction.execute(
+                "INSERT INTO bookings (id, space, start, end, title) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (id) DO UPDATE SET
+                     space = excluded.space, start = excluded.start,
+                     end = excluded.end, title = excluded.title",
+                params![
+                    entry.id,
+                    entry.space,
+                    entry.start.format(TIME)?,
+                    entry.end.format(TIME)?,
+                    entry.title,
+                ],
+            )?;
+            transaction.execute("DELETE FROM booking_text WHERE id = ?1", [&entry.id])?;
+            transaction.execute(
+                "INSERT INTO booking_text (id, title, space) VALUES (?1, ?2, ?3)",
+                params![entry.id, entry.title, entry.space],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn open(&self) -> color_eyre::Result<Connection> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+        }
+        let connection = Connection::open(&self.path)
+            .wrap_err_with(|| format!("opening {}", self.path.display()))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(connection)
+    }
+}
+
+/// Prints the bookings in each venue's history matching `query`, newest first.
+pub fn search(venues: &[&VenueConfig], query: &str, limit: usize) -> color_eyre::Result<()> {
+    const DAY: &[BorrowedFormatItem] =
+        format_description!("[weekday repr:short] [day padding:none] [month repr:short] [year]");
+    let paths = Paths::resolve().ok_or_else(|| eyre!("could not determine the data directory"))?;
+    let mut found = false;
+    for venue in venues {
+        let entries = History::new(paths.history_db(&venue.key())).search(query, limit)?;
+        if entries.is_empty() {
+            continue;
+        }
+        found = true;
+        println!("{}", venue.name);
+        for entry in entries {
+            println!(
+                "  {:<16} {}–{}  {:<20} {}",
+                entry.start.format(DAY)?,
+                grid::format_time(entry.start.time()),
+                grid::format_time(entry.end.time()),
+                entry.space,
+                entry.title.as_deref().unwrap_or("")
+            );
+        }
+    }
+    if !found {
+        println!("no bookings in the history match {query:?}");
+    }
+    Ok(())
+}
+
+/// Loads each venue, which saves the bookings of mine Skedda sends to its history, and says
+/// how many the history gained. Venues that can't be loaded are reported and the rest carry
+/// on.
+pub fn import(venues: &[&VenueConfig]) -> color_eyre::Result<()> {
+    let paths = Paths::resolve().ok_or_else(|| eyre!("could not determine the data directory"))?;
+    let mut failed = 0;
+    for venue in venues {
+        let history = History::new(paths.history_db(&venue.key()));
+        let imported = history.count().and_then(|before| {
+            Skedda::new(venue).get_booking_data()?;
+            Ok((history.count()?, before))
+        });
+        match imported {
+            Ok((after, before)) => println!(
+                "{}: {} new, {after} bookings in the history",
+                venue.name,
+                after.saturating_sub(before)
+            ),
+            Err(err) => {
+                failed += 1;
+                println!("{}: couldn't import: {err:#}", venue.name);
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} of {} venues couldn't be imported", venues.len());
+    }
+    Ok(())
+}
+
+/// The words of a search as a full-text query that needs all of them, each quoted so
+/// punctuation in it isn't read as query syntax; `None` when there are no words.
+fn match_expression(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn entry(id: &str, title: Option<&str>, start: PrimitiveDateTime) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            space: "Boardroom".to_string(),
+            start,
+            end: start + time::Duration::HOUR,
+            title: title.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn finds_bookings_by_title_and_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("example.db"));
+        assert!(history.search("design", 10).unwrap().is_empty());
+
+        history.record(&[
+            entry("9001", Some("Design review"), datetime!(2024-05-01 9:00)),
+            entry("9002", Some("Standup"), datetime!(2024-05-02 9:00)),
+            entry("9003", None, datetime!(2024-05-03 9:00)),
+        ]);
+        // Seen again with a new title, the booking is updated rather than added twice.
+        history.record(&[entry(
+            "9002",
+            Some("Design review, part 2"),
+            datetime!(2024-05-02 9:00),
+        )]);
+
+        let ids = |query| -> Vec<String> {
+            history
+                .search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.id)
+                .collect()
+        };
+        assert_eq!(history.count().unwrap(), 3);
+        assert_eq!(ids("design review"), ["9002", "9001"]);
+        assert_eq!(ids("DESIGN \"part"), ["9002"]);
+        assert_eq!(ids("boardroom").len(), 3);
+        assert!(ids("standup").is_empty());
+        assert!(ids("  ").is_empty());
+        assert_eq!(
+            history.search("review", 1).unwrap(),
+            [entry(
+                "9002",
+                Some("Design review, part 2"),
+                datetime!(2024-05-02 9:00)
+            )]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::process::{Command, Stdio};
+
+use color_eyre::eyre::{WrapErr, bail};
+use time::{PrimitiveDateTime, format_description::BorrowedFormatItem, macros::format_description};
+use tracing::info;
+
+use crate::config::HooksConfig;
+
+/// Times handed to hooks, as "2025-08-01T09:00".
+const TIME: &[BorrowedFormatItem] = format_description!("[year]-[month]-[day]T[hour]:[minute]");
+
+/// Something that happened to a booking that a hook can run after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Booked,
+    Cancelled,
+}
+
+impl HookEvent {
+    /// The event's key in `[hooks]`, and its `SYRES_EVENT`.
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::Booked => "booked",
+            HookEvent::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// The booking a hook runs for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookBooking {
+    pub id: String,
+    pub venue: String,
+    pub location: Option<String>,
+    /// The booked space's name, or names when the booking covers several.
+    pub space: String,
+    pub start: PrimitiveDateTime,
+    pub end: PrimitiveDateTime,
+    pub title: Option<String>,
+}
+
+impl HookBooking {
+    /// The environment the hook gets, with blanks for what isn't known.
+    fn env(&self, event: HookEvent) -> Vec<(&'static str, String)> {
+        let format = |time: PrimitiveDateTime| time.format(TIME).unwrap_or_default();
+        vec![
+            ("SYRES_EVENT", event.name().to_string()),
+            ("SYRES_BOOKING_ID", self.id.clone()),
+            ("SYRES_VENUE", self.venue.clone()),
+            ("SYRES_LOCATION", self.location.clone().unwrap_or_default()),
+            ("SYRES_SPACE", self.space.clone()),
+            ("SYRES_START", format(self.start)),
+            ("SYRES_END", format(self.end)),
+            ("SYRES_TITLE", self.title.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+/// Runs the hook configured for the event, if there is one, through the shell with the
+/// booking in `SYRES_*` variables, and waits for it. Its output is dropped, since the terminal
+/// may belong to the TUI; the error says how it failed, with what it wrote to stderr.
+pub fn run(
+    config: &HooksConfig,
+    event: HookEvent,
+    booking: &HookBooking,
+) -> color_eyre::Result<()> {
+    let Some(command) = config.command(event) else {
+        return Ok(());
+    };
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let output = shell
+        .arg(command)
+        .envs(booking.env(event))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .wrap_err_with(|| format!("failed to run the {} hook", event.name()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "the {} hook exited with {}{}",
+            event.name(),
+            output.status,
+            match stderr.trim() {
+                "" => String::new(),
+                stderr => format!(": {stderr}"),
+            }
+        );
+    }
+    info!(event = event.name(), booking = booking.id, "ran hook");
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn hooks_get_the_booking_in_their_environment() {
+        let booking = HookBooking {
+            id: "9005".to_string(),
+            venue: "Switchyards".to_string(),
+            location: Some("Decatur".to_string()),
+            space: "Boardroom".to_string(),
+            start: datetime!(2025-08-01 9:00),
+            end: datetime!(2025-08-01 12:00),
+            title: None,
+        };
+        let hooks = |booked: &str| HooksConfig {
+            booked: Some(booked.to_string()),
+            cancelled: None,
+        };
+
+        run(
+            &hooks(
+                r#"test "$SYRES_EVENT $SYRES_BOOKING_ID $SYRES_SPACE @ $SYRES_LOCATION" = "booked 9005 Boardroom @ Decatur" &&
+                   test "$SYRES_START $SYRES_END" = "2025-08-01T09:00 2025-08-01T12:00" &&
+                   test -z "$SYRES_TITLE""#,
+            ),
+            HookEvent::Booked,
+            &booking,
+        )
+        .unwrap();
+        // Nothing is configured for cancellations.
+        run(&hooks("exit 1"), HookEvent::Cancelled, &booking).unwrap();
+
+        let err = run(
+            &hooks("echo no timesheet >&2; exit 3"),
+            HookEvent::Booked,
+            &booking,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the booked hook exited with exit status: 3: no timesheet"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use time::{
+    Date, PrimitiveDateTime, Time, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
+
+use crate::{grid, models::OpeningHours};
+
+const DATE: &[BorrowedFormatItem] = format_description!("[year]-[month]-[day]");
+const DAY: &[BorrowedFormatItem] =
+    format_description!("[weekday repr:short] [day padding:none] [month repr:short]");
+
+/// How a space is open on one day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hours {
+    Closed,
+    /// From one time to another; a close of 00:00 is midnight at the end of the day.
+    Open(Time, Time),
+}
+
+impl Hours {
+    /// "08:00–18:00", "08:00–24:00" or "closed".
+    pub fn describe(self) -> String {
+        match self {
+            Hours::Closed => "closed".to_string(),
+            Hours::Open(open, close) if close == Time::MIDNIGHT => {
+                format!("{}–24:00", grid::format_time(open))
+            }
+            Hours::Open(open, close) => {
+                format!("{}–{}", grid::format_time(open), grid::format_time(close))
+            }
+        }
+    }
+}
+
+/// When a space can be booked: weekly hours by day of the week, and dates whose hours differ
+/// from them, such as holidays. Days the venue data says nothing about are taken as open.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schedule {
+    /// By day of the week from Sunday.
+    weekly: [Option<Hours>; 7],
+    exceptions: HashMap<Date, Hours>,
+}
+
+impl Schedule {
+    /// The venue's opening hours, with the space's own hours in place of them for the days and
+    /// dates the space sets. Entries that can't be read are left out.
+    pub fn new(venue: &[OpeningHours], space: &[OpeningHours]) -> Self {
+        let mut schedule = Self::default();
+        for entry in venue.iter().chain(space) {
+            let hours = match (entry.start.as_deref(), entry.end.as_deref()) {
+                (None, None) => Hours::Closed,
+                (Some(start), Some(end)) => match (read_time(start), read_time(end)) {
+                    (Some(open), Some(close)) => Hours::Open(open, close),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if let Some(date) = &entry.date {
+                if let Ok(date) = Date::parse(date, DATE) {
+                    schedule.exceptions.insert(date, hours);
+                }
+                continue;
+            }
+            for &day in entry.days.iter().filter(|&&day| day < 7) {
+                schedule.weekly[usize::from(day)] = Some(hours);
+            }
+        }
+        schedule
+    }
+
+    /// The hours on `date`, or `None` when the venue data doesn't say.
+    pub fn on(&self, date: Date) -> Option<Hours> {
+        self.exceptions
+            .get(&date)
+            .copied()
+            .or_else(|| self.weekly[usize::from(date.weekday().number_days_from_sunday())])
+    }
+
+    /// Whether the whole of `start` to `end` falls within the hours on its day.
+    pub fn is_open(&self, start: PrimitiveDateTime, end: PrimitiveDateTime) -> bool {
+        match self.on(start.date()) {
+            None => true,
+            Some(Hours::Closed) => false,
+            Some(Hours::Open(open, close)) => {
+                let closes = if close == Time::MIDNIGHT {
+                    start.date().next_day().map(|day| day.midnight())
+                } else {
+                    Some(start.date().with_time(close))
+                };
+                start.time() >= open && closes.is_none_or(|closes| end <= closes)
+            }
+        }
+    }
+
+    /// Why `space` can't be booked from `start` to `end`, if it's closed then.
+    pub fn check(
+        &self,
+        space: &str,
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+    ) -> Result<(), String> {
+        if self.is_open(start, end) {
+            return Ok(());
+        }
+        let day = start
+            .format(DAY)
+            .unwrap_or_else(|_| start.date().to_string());
+        Err(match self.on(start.date()) {
+            Some(hours @ Hours::Open(..)) => {
+                format!("{space} is open {} on {day}", hours.describe())
+            }
+            _ => format!("{space} is closed on {day}"),
+        })
+    }
+}
+
+/// "08:00", with "24:00" read as midnight at the end of the day.
+fn read_time(text: &str) -> Option<Time> {
+    match text.trim() {
+        "24:00" => Some(Time::MIDNIGHT),
+        text => grid::read_time(text).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{date, datetime, time};
+
+    use super::*;
+
+    fn entry(days: &[u8], date: Option<&str>, hours: Option<(&str, &str)>) -> OpeningHours {
+        OpeningHours {
+            days: days.to_vec(),
+            date: date.map(str::to_string),
+            start: hours.map(|(start, _)| start.to_string()),
+            end: hours.map(|(_, end)| end.to_string()),
+        }
+    }
+
+    #[test]
+    fn space_hours_and_dates_override_the_venues() {
+        let venue = [
+            entry(&[1, 2, 3, 4, 5], None, Some(("07:00", "22:00"))),
+            entry(&[6], None, Some(("9:00", "13:00"))),
+            entry(&[0], None, None),
+            entry(&[], Some("2024-05-27"), None),
+            // Unreadable entries are left out.
+            entry(&[2], None, Some(("noon", "18:00"))),
+        ];
+        let space = [entry(&[1, 2, 3, 4, 5], None, Some(("08:00", "24:00")))];
+        let schedule = Schedule::new(&venue, &space);
+
+        // Wednesday, with the space's own hours.
+        assert_eq!(
+            schedule.on(date!(2024 - 05 - 01)),
+            Some(Hours::Open(time!(8:00), Time::MIDNIGHT))
+        );
+        assert!(schedule.is_open(datetime!(2024-05-01 22:00), datetime!(2024-05-02 0:00)));
+        assert_eq!(
+            schedule.check(
+                "Boardroom",
+                datetime!(2024-05-01 7:30),
+                datetime!(2024-05-01 9:00)
+            ),
+            Err("Boardroom is open 08:00–24:00 on Wed 1 May".to_string())
+        );
+        // Saturday and Sunday, from the venue.
+        assert!(schedule.is_open(datetime!(2024-05-04 9:00), datetime!(2024-05-04 13:00)));
+        assert!(!schedule.is_open(datetime!(2024-05-04 12:00), datetime!(2024-05-04 14:00)));
+        assert_eq!(
+            schedule.check(
+                "Boardroom",
+                datetime!(2024-05-05 10:00),
+                datetime!(2024-05-05 11:00)
+            ),
+            Err("Boardroom is closed on Sun 5 May".to_string())
+        );
+        // A holiday Monday.
+        assert_eq!(schedule.on(date!(2024 - 05 - 27)), Some(Hours::Closed));
+
+        let unknown = Schedule::new(&[], &[]);
+        assert_eq!(unknown.on(date!(2024 - 05 - 01)), None);
+        assert!(unknown.is_open(datetime!(2024-05-01 3:00), datetime!(2024-05-01 4:00)));
+    }
+}
@@ -0,0 +1,109 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A line of text being typed, with a cursor that moves a character at a time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    text: String,
+    /// In characters from the start, not bytes, so it never lands inside one.
+    cursor: usize,
+}
+
+impl TextInput {
+    /// The longest text taken, in characters.
+    pub const MAX_CHARS: usize = 200;
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The text before the cursor, the character under it if any, and the text after that.
+    pub fn split_at_cursor(&self) -> (&str, Option<char>, &str) {
+        let at = self.byte_offset(self.cursor);
+        let mut rest = self.text[at..].chars();
+        let under = rest.next();
+        (&self.text[..at], under, rest.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Applies an editing key, returning whether it was one. Keys it doesn't use, such as
+    /// `Tab`, `Enter` and `Esc`, are left for the caller.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+        let len = self.text.chars().count();
+        match key.code {
+            KeyCode::Char(c) if len < Self::MAX_CHARS && !c.is_control() => {
+                let at = self.byte_offset(self.cursor);
+                self.text.insert(at, c);
+                self.cursor += 1;
+            }
+            KeyCode::Char(_) => {}
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(self.byte_offset(self.cursor));
+            }
+            KeyCode::Delete if self.cursor < len => {
+                self.text.remove(self.byte_offset(self.cursor));
+            }
+            KeyCode::Backspace | KeyCode::Delete => {}
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => return false,
+        }
+        true
+    }
+
+    fn byte_offset(&self, chars: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(chars)
+            .map_or(self.text.len(), |(at, _)| at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_at_the_cursor_by_character() {
+        let mut input = TextInput::default();
+        let mut press = |code| input.handle_key(KeyEvent::from(code));
+        for c in "Café plans".chars() {
+            assert!(press(KeyCode::Char(c)));
+        }
+        for code in [
+            KeyCode::Home,
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Backspace,
+            KeyCode::Char('é'),
+            KeyCode::Char('🍰'),
+            KeyCode::Delete,
+            KeyCode::Left,
+        ] {
+            assert!(press(code));
+        }
+        assert!(!press(KeyCode::Tab));
+        assert_eq!(input.text(), "Café🍰plans");
+        assert_eq!(input.split_at_cursor(), ("Café", Some('🍰'), "plans"));
+
+        input.handle_key(KeyEvent::from(KeyCode::End));
+        assert_eq!(input.split_at_cursor(), ("Café🍰plans", None, ""));
+        // Shortcuts like Ctrl+C aren't typed.
+        assert!(!input.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        input.clear();
+        assert_eq!(input.text(), "");
+    }
+}
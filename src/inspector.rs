@@ -0,0 +1,198 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt,
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
+    time::{Duration, Instant},
+};
+
+use time::PrimitiveDateTime;
+use tracing::{debug, field, info_span};
+
+use crate::clock;
+
+/// How many calls the request inspector keeps.
+pub const RECENT_CALLS: usize = 20;
+
+/// One HTTP request a call made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTiming {
+    pub method: String,
+    pub path: String,
+    /// The response's status, or why there was none.
+    pub outcome: Result<u16, String>,
+    /// Waiting for one of the venue's request slots.
+    pub queued: Duration,
+    /// From sending the request to the response's headers. On a new connection this includes
+    /// looking up the host, connecting and the TLS handshake, which the client doesn't time
+    /// apart.
+    pub first_byte: Duration,
+}
+
+/// A call to a venue, such as fetching its data or making a booking, and the requests it
+/// took.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallTiming {
+    pub name: &'static str,
+    pub venue: String,
+    pub finished_at: PrimitiveDateTime,
+    /// From the call starting to it finishing, response bodies included.
+    pub total: Duration,
+    /// Why the call failed, or `None` when it worked.
+    pub error: Option<String>,
+    pub requests: Vec<RequestTiming>,
+}
+
+thread_local! {
+    /// The call under way on this thread, collecting its requests.
+    static CURRENT: RefCell<Option<CallTiming>> = const { RefCell::new(None) };
+}
+
+static RECENT: OnceLock<Mutex<VecDeque<CallTiming>>> = OnceLock::new();
+
+/// The names of the calls under way on any thread, oldest first.
+static IN_FLIGHT: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Runs `f` as the named call to the venue, timing it in a tracing span and keeping it for
+/// the inspector. Calls made inside another call count as part of it.
+pub fn call<T, E: fmt::Display>(
+    name: &'static str,
+    venue: &str,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    if CURRENT.with_borrow(Option::is_some) {
+        return f();
+    }
+    let span = info_span!("call", call = name, venue, total_ms = field::Empty);
+    let _entered = span.enter();
+    let started = Instant::now();
+    in_flight_calls().push(name);
+    CURRENT.set(Some(CallTiming {
+        name,
+        venue: venue.to_string(),
+        finished_at: clock::now(),
+        total: Duration::ZERO,
+        error: None,
+        requests: Vec::new(),
+    }));
+    let result = f();
+    let total = started.elapsed();
+    {
+        let mut in_flight = in_flight_calls();
+        if let Some(position) = in_flight.iter().position(|call| *call == name) {
+            in_flight.remove(position);
+        }
+    }
+    span.record("total_ms", total.as_millis() as u64);
+    if let Some(mut timing) = CURRENT.take() {
+        timing.finished_at = clock::now();
+        timing.total = total;
+        timing.error = result.as_ref().err().map(ToString::to_string);
+        debug!(
+            requests = timing.requests.len(),
+            ok = timing.error.is_none(),
+            "call finished"
+        );
+        keep(timing);
+    }
+    result
+}
+
+/// Adds a request to the call under way on this thread.
+pub fn request_sent(timing: RequestTiming) {
+    debug!(
+        method = timing.method,
+        path = timing.path,
+        status = timing.outcome.as_ref().ok(),
+        queued_ms = timing.queued.as_millis() as u64,
+        first_byte_ms = timing.first_byte.as_millis() as u64,
+        "request timing"
+    );
+    CURRENT.with_borrow_mut(|current| {
+        if let Some(call) = current {
+            call.requests.push(timing);
+        }
+    });
+}
+
+/// The calls kept for the inspector, newest first.
+pub fn recent() -> Vec<CallTiming> {
+    calls()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .rev()
+        .cloned()
+        .collect()
+}
+
+/// The most recent call to finish.
+pub fn latest() -> Option<CallTiming> {
+    calls()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .back()
+        .cloned()
+}
+
+/// The names of the calls under way, oldest first.
+pub fn in_flight() -> Vec<&'static str> {
+    in_flight_calls().clone()
+}
+
+fn in_flight_calls() -> MutexGuard<'static, Vec<&'static str>> {
+    IN_FLIGHT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn keep(timing: CallTiming) {
+    let mut calls = calls().lock().unwrap_or_else(PoisonError::into_inner);
+    if calls.len() == RECENT_CALLS {
+        calls.pop_front();
+    }
+    calls.push_back(timing);
+}
+
+fn calls() -> &'static Mutex<VecDeque<CallTiming>> {
+    RECENT.get_or_init(|| Mutex::new(VecDeque::with_capacity(RECENT_CALLS)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str) -> RequestTiming {
+        RequestTiming {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            outcome: Ok(200),
+            queued: Duration::ZERO,
+            first_byte: Duration::from_millis(5),
+        }
+    }
+
+    #[test]
+    fn calls_collect_their_requests() {
+        // Outside a call, a request has nowhere to go.
+        request_sent(request("/stray"));
+        let result: Result<(), &str> = call("inspector test", "example", || {
+            request_sent(request("/booking"));
+            assert!(in_flight().contains(&"inspector test"));
+            call("nested", "example", || {
+                request_sent(request("/webs"));
+                Ok::<_, &str>(())
+            })?;
+            Err("refused")
+        });
+        assert!(result.is_err());
+
+        let recent = recent();
+        let timing = recent
+            .iter()
+            .find(|timing| timing.name == "inspector test")
+            .unwrap();
+        assert_eq!(timing.error.as_deref(), Some("refused"));
+        assert!(!in_flight().contains(&"inspector test"));
+        assert_eq!(timing.requests, [request("/booking"), request("/webs")]);
+        assert!(!recent.iter().any(|timing| timing.name == "nested"));
+    }
+}
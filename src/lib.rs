@@ -0,0 +1,61 @@
+//! The booking engine behind the `syres` app: a client for a venue's Skedda site, the models
+//! of what it sends, and the availability, rules and opening hours worked out from them.
+//!
+//! What's re-exported here is the API other programs can build on; it only changes in a
+//! breaking way with a new minor version while syres is 0.x. The error and event enums are
+//! `#[non_exhaustive]`, so new kinds of failure and new events can be added without breaking
+//! a `match`. The modules below are public for the app and may change between any releases.
+
+pub mod app;
+pub mod audit;
+pub mod availability;
+pub mod batch;
+pub mod book;
+pub mod cache;
+pub mod calendar;
+pub mod cli;
+pub mod clock;
+pub mod config;
+pub mod crash;
+pub mod daemon;
+pub mod doctor;
+pub mod draft;
+pub mod event;
+#[cfg(test)]
+mod fixtures;
+pub mod grid;
+pub mod history;
+pub mod hooks;
+pub mod hours;
+pub mod input;
+pub mod inspector;
+pub mod list;
+pub mod logging;
+pub mod markup;
+pub mod models;
+pub mod navigation;
+pub mod network;
+pub mod notify;
+pub mod openings;
+pub mod page;
+pub mod paths;
+pub mod plan;
+pub mod polling;
+pub mod receipt;
+pub mod report;
+pub mod retry;
+pub mod rules;
+pub mod scheduler;
+pub mod session;
+pub mod skedda;
+pub mod status;
+pub mod toast;
+pub mod ui;
+
+pub use availability::LocationAvailability;
+pub use config::{Config, VenueConfig};
+pub use event::{AppEvent, Event};
+pub use hours::{Hours, Schedule};
+pub use models::{Booking, BookingRequest, CreatedBooking, WebsResponse, WebsSummary};
+pub use rules::BookingRules;
+pub use skedda::{MyBooking, Role, Skedda, SkeddaError, SpaceDetails};
@@ -0,0 +1,67 @@
+use ratatui::widgets::ListState;
+
+/// A move of the highlight within a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListMove {
+    Previous,
+    Next,
+    /// `Home` or `gg`.
+    First,
+    /// `End` or `G`.
+    Last,
+    PageUp,
+    PageDown,
+}
+
+/// Applies `step` to a list of `len` rows showing `page` of them at a time.
+pub fn select(state: &mut ListState, len: usize, page: usize, step: ListMove) {
+    state.select(target(state.selected(), len, page, step));
+}
+
+/// The row to highlight after `step`, stopping at either end rather than wrapping; `None` for
+/// an empty list. With nothing highlighted yet, every move but `Last` lands on the first row.
+pub fn target(selected: Option<usize>, len: usize, page: usize, step: ListMove) -> Option<usize> {
+    let last = len.checked_sub(1)?;
+    let Some(current) = selected.map(|selected| selected.min(last)) else {
+        return Some(if step == ListMove::Last { last } else { 0 });
+    };
+    let page = page.max(1);
+    Some(match step {
+        ListMove::Previous => current.saturating_sub(1),
+        ListMove::Next => (current + 1).min(last),
+        ListMove::First => 0,
+        ListMove::Last => last,
+        ListMove::PageUp => current.saturating_sub(page),
+        ListMove::PageDown => (current + page).min(last),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_stop_at_the_ends() {
+        assert_eq!(target(Some(0), 5, 3, ListMove::Previous), Some(0));
+        assert_eq!(target(Some(4), 5, 3, ListMove::Next), Some(4));
+        assert_eq!(target(Some(1), 5, 3, ListMove::PageUp), Some(0));
+        assert_eq!(target(Some(3), 5, 3, ListMove::PageDown), Some(4));
+    }
+
+    #[test]
+    fn jumps_and_pages() {
+        assert_eq!(target(Some(2), 50, 10, ListMove::First), Some(0));
+        assert_eq!(target(Some(2), 50, 10, ListMove::Last), Some(49));
+        assert_eq!(target(Some(2), 50, 10, ListMove::PageDown), Some(12));
+        assert_eq!(target(Some(12), 50, 10, ListMove::PageUp), Some(2));
+        assert_eq!(target(Some(12), 50, 0, ListMove::PageUp), Some(11));
+    }
+
+    #[test]
+    fn handles_empty_and_shrunk_lists() {
+        assert_eq!(target(None, 0, 10, ListMove::Last), None);
+        assert_eq!(target(None, 3, 10, ListMove::Next), Some(0));
+        assert_eq!(target(None, 3, 10, ListMove::Last), Some(2));
+        assert_eq!(target(Some(9), 3, 10, ListMove::Previous), Some(1));
+    }
+}
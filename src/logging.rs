@@ -0,0 +1,326 @@
+use std::{
+    collections::VecDeque,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::eyre::{WrapErr, eyre};
+use flate2::{Compression, write::GzEncoder};
+use tracing_subscriber::EnvFilter;
+
+use crate::{config::LogConfig, paths::Paths};
+
+const DEFAULT_DIRECTIVES: &str = "warn";
+
+/// How many lines of the log the log viewer keeps.
+pub const RECENT_LINES: usize = 500;
+
+/// The latest lines written to the log, oldest first.
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Builds the log filter.
+///
+/// Precedence, highest first: `-v` flags, `RUST_LOG`, the `log.level` config key, and finally
+/// [`DEFAULT_DIRECTIVES`]. `RUST_LOG` and the config key accept per-module directives, so
+/// `warn,syres::skedda=debug` debugs just the HTTP layer.
+pub fn filter(verbose: u8, config: &LogConfig) -> color_eyre::Result<EnvFilter> {
+    let directives = match verbose {
+        0 => env::var("RUST_LOG")
+            .ok()
+            .filter(|directives| !directives.is_empty())
+            .or_else(|| config.level.clone())
+            .unwrap_or_else(|| DEFAULT_DIRECTIVES.to_string()),
+        1 => format!("{DEFAULT_DIRECTIVES},syres=info"),
+        2 => format!("{DEFAULT_DIRECTIVES},syres=debug"),
+        _ => format!("{DEFAULT_DIRECTIVES},syres=trace"),
+    };
+    EnvFilter::try_new(&directives).wrap_err_with(|| format!("invalid log filter {directives:?}"))
+}
+
+/// Installs the global subscriber, writing to a log file since the terminal belongs to the TUI.
+/// The daemon writes to a file of its own, as it runs alongside the TUI.
+pub fn init(verbose: u8, config: &LogConfig, daemon: bool) -> color_eyre::Result<PathBuf> {
+    let path = Paths::resolve()
+        .map(|paths| {
+            if daemon {
+                paths.daemon_log_file()
+            } else {
+                paths.log_file()
+            }
+        })
+        .ok_or_else(|| eyre!("could not determine the log directory"))?;
+    let writer = KeepRecent::new(RotatingFile::open(&path, config)?);
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter(verbose, config)?)
+        .with_writer(Mutex::new(writer))
+        .with_ansi(false)
+        .init();
+    Ok(path)
+}
+
+/// The latest lines written to the log, oldest first, for the log viewer.
+pub fn recent() -> Vec<String> {
+    RECENT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// Passes what's written on, keeping the latest [`RECENT_LINES`] whole lines of it for
+/// [`recent`].
+#[derive(Debug)]
+pub struct KeepRecent<W> {
+    inner: W,
+    /// The start of a line whose end hasn't been written yet.
+    partial: String,
+}
+
+impl<W: Write> KeepRecent<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            partial: String::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for KeepRecent<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.partial
+            .push_str(&String::from_utf8_lossy(&buf[..written]));
+        if let Some(end) = self.partial.rfind('\n') {
+            let rest = self.partial.split_off(end + 1);
+            let mut recent = RECENT.lock().unwrap_or_else(PoisonError::into_inner);
+            for line in self.partial.lines().filter(|line| !line.is_empty()) {
+                if recent.len() == RECENT_LINES {
+                    recent.pop_front();
+                }
+                recent.push_back(line.to_string());
+            }
+            self.partial = rest;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A log file that rotates once it grows past a size limit.
+///
+/// Rotated files are renamed `syres.log.1`, `syres.log.2`, ... (newest first), optionally
+/// gzipped, and pruned once there are more than `max_files` of them or they are older than
+/// `max_age_days`.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+    max_age: Option<Duration>,
+    compress: bool,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, config: &LogConfig) -> color_eyre::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("failed to create log directory {}", dir.display()))?;
+        }
+        let file = Self::open_file(path)
+            .wrap_err_with(|| format!("failed to open log file {}", path.display()))?;
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let writer = Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size: config.max_size_mb.saturating_mul(1024 * 1024),
+            max_files: config.max_files,
+            max_age: config
+                .max_age_days
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+            compress: config.compress,
+        };
+        writer.prune();
+        Ok(writer)
+    }
+
+    /// Paths of the rotated files that currently exist, newest first.
+    pub fn rotated_files(path: &Path) -> Vec<PathBuf> {
+        (1..)
+            .map_while(|index| {
+                let plain = Self::rotated_path(path, index, false);
+                let gzipped = Self::rotated_path(path, index, true);
+                [plain, gzipped].into_iter().find(|p| p.exists())
+            })
+            .collect()
+    }
+
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(path: &Path, index: usize, compressed: bool) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{index}"));
+        if compressed {
+            name.push(".gz");
+        }
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated = Self::rotated_files(&self.path);
+        for (index, old) in rotated.iter().enumerate().rev() {
+            let compressed = old.extension().is_some_and(|ext| ext == "gz");
+            fs::rename(old, Self::rotated_path(&self.path, index + 2, compressed))?;
+        }
+
+        let first = Self::rotated_path(&self.path, 1, false);
+        fs::rename(&self.path, &first)?;
+        if self.compress {
+            Self::gzip(&first)?;
+        }
+
+        self.file = Self::open_file(&self.path)?;
+        self.size = 0;
+        self.prune();
+        Ok(())
+    }
+
+    fn gzip(path: &Path) -> io::Result<()> {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".gz");
+        let mut encoder =
+            GzEncoder::new(File::create(PathBuf::from(name))?, Compression::default());
+        io::copy(&mut File::open(path)?, &mut encoder)?;
+        encoder.finish()?;
+        fs::remove_file(path)
+    }
+
+    /// Deletes rotated files beyond `max_files` or older than `max_age`.
+    fn prune(&self) {
+        let now = SystemTime::now();
+        for (index, old) in Self::rotated_files(&self.path).iter().enumerate() {
+            let expired = self.max_age.is_some_and(|max_age| {
+                fs::metadata(old)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .is_some_and(|age| age > max_age)
+            });
+            if index >= self.max_files || expired {
+                let _ = fs::remove_file(old);
+            }
+        }
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    /// A log in a directory of its own that rotates past `max_size` bytes.
+    fn rotating(
+        max_size: u64,
+        max_files: usize,
+        compress: bool,
+    ) -> (tempfile::TempDir, RotatingFile) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogConfig {
+            max_files,
+            compress,
+            ..LogConfig::default()
+        };
+        let mut file = RotatingFile::open(&dir.path().join("syres.log"), &config).unwrap();
+        file.max_size = max_size;
+        (dir, file)
+    }
+
+    #[test]
+    fn keeps_whole_lines_for_the_viewer() {
+        let mut writer = KeepRecent::new(Vec::new());
+        write!(writer, "WARN first line\nINFO second").unwrap();
+        assert!(recent().ends_with(&["WARN first line".to_string()]));
+        // A line is only kept once it's finished.
+        writeln!(writer, " line").unwrap();
+        assert!(recent().ends_with(&[
+            "WARN first line".to_string(),
+            "INFO second line".to_string()
+        ]));
+        assert_eq!(writer.inner, b"WARN first line\nINFO second line\n");
+    }
+
+    #[test]
+    fn rotates_past_the_size_limit_and_keeps_max_files() {
+        let (dir, mut file) = rotating(10, 2, false);
+        for line in 1..=4 {
+            writeln!(file, "line {line}").unwrap();
+        }
+
+        let log = dir.path().join("syres.log");
+        assert_eq!(fs::read_to_string(&log).unwrap(), "line 4\n");
+        let rotated = RotatingFile::rotated_files(&log);
+        assert_eq!(
+            rotated,
+            [
+                dir.path().join("syres.log.1"),
+                dir.path().join("syres.log.2")
+            ]
+        );
+        assert_eq!(fs::read_to_string(&rotated[0]).unwrap(), "line 3\n");
+        assert_eq!(fs::read_to_string(&rotated[1]).unwrap(), "line 2\n");
+        assert!(!dir.path().join("syres.log.3").exists());
+    }
+
+    #[test]
+    fn gzips_rotated_files() {
+        let (dir, mut file) = rotating(10, 5, true);
+        writeln!(file, "line 1").unwrap();
+        writeln!(file, "line 2").unwrap();
+
+        let log = dir.path().join("syres.log");
+        let gzipped = dir.path().join("syres.log.1.gz");
+        assert_eq!(
+            RotatingFile::rotated_files(&log),
+            std::slice::from_ref(&gzipped)
+        );
+        assert!(!dir.path().join("syres.log.1").exists());
+        let mut contents = String::new();
+        GzDecoder::new(File::open(gzipped).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "line 1\n");
+        assert_eq!(fs::read_to_string(&log).unwrap(), "line 2\n");
+    }
+}
@@ -1,14 +1,116 @@
-use crate::app::App;
+use clap::Parser;
+use color_eyre::eyre::eyre;
 
-pub mod app;
-pub mod event;
-pub mod skedda;
-pub mod ui;
+use syres::{
+    app::App,
+    audit, book,
+    book::BookArgs,
+    cache,
+    cli::{CacheAction, Cli, Command, HistoryAction},
+    clock,
+    config::Config,
+    crash::CrashReporter,
+    daemon, doctor, history, logging, network, polling, report, status,
+};
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
-    let terminal = ratatui::init();
-    let result = App::new().run(terminal);
-    ratatui::restore();
+    let cli = Cli::parse();
+    let config = Config::load()?;
+    clock::init(config.zone());
+    let log_path = logging::init(
+        cli.verbose,
+        &config.log,
+        matches!(cli.command, Some(Command::Daemon { .. })),
+    )?;
+    network::init(&config.network);
+    polling::init(config.network.background_requests_per_minute);
+    cache::init(&config.cache);
+    audit::init(match cli.command {
+        None => "tui",
+        Some(Command::Daemon { .. }) => "daemon",
+        Some(_) => "cli",
+    });
+    let crash_reporter = CrashReporter::from_config(&config.crash_reporting)?;
+    if let Some(reporter) = &crash_reporter {
+        reporter.install_panic_hook();
+    }
+
+    let result = match cli.command {
+        Some(Command::Doctor) => doctor::run(&config, cli.verbose, &log_path),
+        Some(Command::Report { output }) => report::run(output, &log_path),
+        Some(Command::Daemon { now }) => daemon::run(&config, now),
+        Some(Command::Book {
+            location,
+            venue,
+            space,
+            date,
+            from,
+            till,
+            json,
+        }) => book::run(
+            &config,
+            &BookArgs {
+                location,
+                venue,
+                space,
+                date,
+                from,
+                till,
+                json,
+            },
+        ),
+        Some(Command::Status {
+            location,
+            venue,
+            fullscreen,
+            at,
+        }) => status::run(
+            &config,
+            venue.as_deref(),
+            &location,
+            fullscreen,
+            at.as_deref(),
+        ),
+        Some(Command::Cache { action }) => match action {
+            CacheAction::Status => cache::status(&config.venues),
+            CacheAction::Clear { entries, venue } => match venue {
+                Some(venue) => match config.venue(&venue) {
+                    Some(venue) => cache::clear(&entries, Some(venue)),
+                    None => Err(eyre!("no venue named {venue:?} in config")),
+                },
+                None => cache::clear(&entries, None),
+            },
+        },
+        Some(Command::History { action }) => match action {
+            HistoryAction::Search {
+                query,
+                venue,
+                limit,
+            } => match venue {
+                Some(venue) => match config.venue(&venue) {
+                    Some(venue) => history::search(&[venue], &query, limit),
+                    None => Err(eyre!("no venue named {venue:?} in config")),
+                },
+                None => history::search(&config.venues.iter().collect::<Vec<_>>(), &query, limit),
+            },
+            HistoryAction::Import { venue } => match venue {
+                Some(venue) => match config.venue(&venue) {
+                    Some(venue) => history::import(&[venue]),
+                    None => Err(eyre!("no venue named {venue:?} in config")),
+                },
+                None => history::import(&config.venues.iter().collect::<Vec<_>>()),
+            },
+        },
+        None => {
+            let terminal = ratatui::init();
+            let result = App::new(&config).run(terminal);
+            ratatui::restore();
+            result
+        }
+    };
+    if let (Err(err), Some(reporter)) = (&result, &crash_reporter) {
+        reporter.capture_error(err);
+    }
     result
 }
@@ -0,0 +1,202 @@
+use ego_tree::NodeRef;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
+use scraper::{Html, Node};
+
+/// A link found in converted markup, hyperlinked in the terminal after rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub text: String,
+    pub url: String,
+}
+
+/// Converts an HTML fragment, such as a space description, into styled terminal text.
+///
+/// Bold, italic and underline are kept, paragraphs and line breaks become lines, list
+/// items get bullets or numbers, and links are styled and returned so they can be made
+/// clickable with [`hyperlink`]. Plain text passes through unchanged.
+pub fn html_to_text(html: &str) -> (Text<'static>, Vec<Link>) {
+    let fragment = Html::parse_fragment(html);
+    let mut writer = Writer::default();
+    writer.children(fragment.tree.root(), Style::default());
+    writer.finish()
+}
+
+/// Turns each link's text into an OSC 8 hyperlink where it was drawn within `area`.
+///
+/// The whole escape sequence goes into the link's first cell and the remaining cells
+/// are skipped, so the terminal receives it in one piece. Links that were wrapped
+/// across lines stay styled but are not clickable.
+pub fn hyperlink(buf: &mut Buffer, area: Rect, links: &[Link]) {
+    for link in links {
+        let chars: Vec<String> = link.text.chars().map(String::from).collect();
+        if chars.is_empty() || chars.len() > usize::from(area.width) {
+            continue;
+        }
+        let found = (area.top()..area.bottom()).find_map(|y| {
+            (area.left()..=area.right() - chars.len() as u16)
+                .find(|&x| {
+                    chars.iter().zip(x..).all(|(ch, x)| {
+                        let cell = &buf[(x, y)];
+                        !cell.skip && cell.symbol() == ch
+                    })
+                })
+                .map(|x| (x, y))
+        });
+        let Some((x, y)) = found else {
+            continue;
+        };
+        buf[(x, y)].set_symbol(&format!(
+            "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+            link.url, link.text
+        ));
+        for x in x + 1..x + chars.len() as u16 {
+            buf[(x, y)].set_skip(true);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    lines: Vec<Line<'static>>,
+    spans: Vec<Span<'static>>,
+    links: Vec<Link>,
+    /// Open lists, innermost last: `None` for bullets, the next number for ordered ones.
+    lists: Vec<Option<usize>>,
+}
+
+impl Writer {
+    fn children(&mut self, node: NodeRef<Node>, style: Style) {
+        for child in node.children() {
+            self.node(child, style);
+        }
+    }
+
+    fn node(&mut self, node: NodeRef<Node>, style: Style) {
+        let element = match node.value() {
+            Node::Text(text) => return self.text(text, style),
+            Node::Element(element) => element,
+            _ => return self.children(node, style),
+        };
+        match element.name() {
+            "b" | "strong" => self.children(node, style.add_modifier(Modifier::BOLD)),
+            "i" | "em" => self.children(node, style.add_modifier(Modifier::ITALIC)),
+            "u" => self.children(node, style.add_modifier(Modifier::UNDERLINED)),
+            "a" => {
+                let start = self.spans.len();
+                self.children(
+                    node,
+                    style.fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+                );
+                if let Some(url) = element.attr("href") {
+                    let text: String = self.spans[start.min(self.spans.len())..]
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect();
+                    let text = text.trim();
+                    if !text.is_empty() {
+                        self.links.push(Link {
+                            text: text.to_string(),
+                            url: url.to_string(),
+                        });
+                    }
+                }
+            }
+            "br" => self.break_line(),
+            "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.end_block();
+                let style = if element.name().starts_with('h') {
+                    style.add_modifier(Modifier::BOLD)
+                } else {
+                    style
+                };
+                self.children(node, style);
+                self.end_block();
+            }
+            "ul" | "ol" => {
+                self.break_line_if_open();
+                self.lists.push((element.name() == "ol").then_some(1));
+                self.children(node, style);
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                }
+            }
+            "li" => {
+                self.break_line_if_open();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{indent}{}. ", *number - 1)
+                    }
+                    _ => format!("{indent}• "),
+                };
+                self.spans.push(Span::raw(marker));
+                self.children(node, style);
+                self.break_line_if_open();
+            }
+            "script" | "style" | "head" => {}
+            _ => self.children(node, style),
+        }
+    }
+
+    /// Appends text with HTML whitespace collapsing.
+    fn text(&mut self, text: &str, style: Style) {
+        let mut collapsed = String::with_capacity(text.len());
+        let mut at_space = self
+            .spans
+            .last()
+            .is_none_or(|span| span.content.ends_with(' '));
+        for ch in text.chars() {
+            if ch.is_whitespace() {
+                if !at_space {
+                    collapsed.push(' ');
+                    at_space = true;
+                }
+            } else {
+                collapsed.push(ch);
+                at_space = false;
+            }
+        }
+        if !collapsed.is_empty() {
+            self.spans.push(Span::styled(collapsed, style));
+        }
+    }
+
+    fn break_line(&mut self) {
+        let mut spans = std::mem::take(&mut self.spans);
+        if let Some(last) = spans.last_mut() {
+            if last.content.ends_with(' ') {
+                last.content = last.content.trim_end().to_string().into();
+            }
+        }
+        self.lines.push(Line::from(spans));
+    }
+
+    fn break_line_if_open(&mut self) {
+        if !self.spans.is_empty() {
+            self.break_line();
+        }
+    }
+
+    /// Closes the current block with a blank line, unless one is already there.
+    fn end_block(&mut self) {
+        self.break_line_if_open();
+        if self.lines.last().is_some_and(|line| line.width() > 0) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn finish(mut self) -> (Text<'static>, Vec<Link>) {
+        self.break_line_if_open();
+        while self.lines.last().is_some_and(|line| line.width() == 0) {
+            self.lines.pop();
+        }
+        (Text::from(self.lines), self.links)
+    }
+}
@@ -0,0 +1,695 @@
+use std::{borrow::Cow, fmt};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, DeserializeOwned, IgnoredAny, MapAccess, SeqAccess, Visitor},
+};
+use time::{
+    PrimitiveDateTime,
+    format_description::{BorrowedFormatItem, well_known::Iso8601},
+    macros::format_description,
+};
+
+/// A `/webs` response: everything the booking page knows about a venue, deserialized
+/// straight from the response body.
+///
+/// Fields Skedda has been seen to omit, null or reshape are optional or lenient, so one odd
+/// value doesn't fail the whole response.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WebsResponse {
+    #[serde(deserialize_with = "nullable")]
+    pub spaces: Vec<Space>,
+    #[serde(deserialize_with = "nullable")]
+    pub venue: Vec<Venue>,
+    #[serde(deserialize_with = "nullable")]
+    pub bookings: Vec<Booking>,
+    /// The venue's members; only sent to admins.
+    #[serde(alias = "venueUsers", deserialize_with = "nullable")]
+    pub venueusers: Vec<VenueUser>,
+    /// The logged-in account.
+    #[serde(
+        alias = "venueUser",
+        alias = "currentUser",
+        deserialize_with = "lenient"
+    )]
+    pub venueuser: Option<CurrentUser>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Space {
+    #[serde(deserialize_with = "id")]
+    pub id: String,
+    pub name: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub details: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub image_url: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub image: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub photo_url: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub thumbnail_url: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub space_type: Option<String>,
+    #[serde(default, rename = "type", deserialize_with = "lenient")]
+    pub type_name: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub category: Option<String>,
+    /// The longest booking allowed, in minutes.
+    #[serde(default, deserialize_with = "lenient")]
+    pub max_booking_length: Option<u32>,
+    /// How many minutes ahead of its start a booking must be made.
+    #[serde(default, deserialize_with = "lenient")]
+    pub min_booking_lead_time: Option<u32>,
+    /// How many minutes ahead a booking can be made.
+    #[serde(default, deserialize_with = "lenient")]
+    pub max_booking_lead_time: Option<u32>,
+    /// The space's own opening hours, in place of the venue's on the days they cover.
+    #[serde(default, deserialize_with = "nullable")]
+    pub opening_hours: Vec<OpeningHours>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Venue {
+    #[serde(deserialize_with = "optional_id")]
+    pub id: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub space_presentation: Option<SpacePresentation>,
+    #[serde(deserialize_with = "lenient")]
+    pub booking_increment: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub time_increment: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub minimum_booking_increment: Option<u32>,
+    #[serde(deserialize_with = "nullable")]
+    pub opening_hours: Vec<OpeningHours>,
+}
+
+/// Hours a venue or space is open: every week on `days`, or on one `date` in place of its
+/// weekly hours, such as a holiday. Without a start and end it's closed then.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OpeningHours {
+    /// Days of the week, 0 for Sunday to 6 for Saturday.
+    #[serde(deserialize_with = "nullable")]
+    pub days: Vec<u8>,
+    /// e.g. `"2024-12-25"`.
+    #[serde(deserialize_with = "lenient")]
+    pub date: Option<String>,
+    /// e.g. `"08:00"`.
+    #[serde(deserialize_with = "lenient")]
+    pub start: Option<String>,
+    /// e.g. `"18:00"`, or `"24:00"` for midnight.
+    #[serde(deserialize_with = "lenient")]
+    pub end: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SpacePresentation {
+    #[serde(deserialize_with = "nullable")]
+    pub space_tags: Vec<SpaceTag>,
+}
+
+/// A label grouping spaces; syres offers these as locations.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpaceTag {
+    pub name: String,
+    #[serde(default, deserialize_with = "ids")]
+    pub space_ids: Vec<String>,
+}
+
+/// An existing booking, in the venue's local time.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Booking {
+    #[serde(default, deserialize_with = "optional_id")]
+    pub id: Option<String>,
+    /// Who the booking belongs to, when the venue data says. Filled in from the member list.
+    #[serde(skip)]
+    pub holder: Option<String>,
+    #[serde(default, alias = "venueUser", deserialize_with = "optional_id")]
+    pub venueuser: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub title: Option<String>,
+    #[serde(rename = "spaces", deserialize_with = "ids")]
+    pub space_ids: Vec<String>,
+    #[serde(deserialize_with = "local_time")]
+    pub start: PrimitiveDateTime,
+    #[serde(deserialize_with = "local_time")]
+    pub end: PrimitiveDateTime,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VenueUser {
+    #[serde(deserialize_with = "id")]
+    pub id: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub first_name: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub last_name: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CurrentUser {
+    #[serde(deserialize_with = "optional_id")]
+    pub id: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub is_admin: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub admin: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub is_venue_admin: Option<bool>,
+    /// Role names; roles of any other shape are skipped.
+    #[serde(deserialize_with = "strings")]
+    pub roles: Vec<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub role: Option<String>,
+}
+
+/// A booking to make, in the shape `POST /bookings` takes inside its `booking` object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BookingRequest {
+    pub venue: String,
+    /// The member it's for, when an admin books for someone; otherwise the session's account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub venueuser: Option<String>,
+    pub spaces: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(serialize_with = "write_local_time")]
+    pub start: PrimitiveDateTime,
+    #[serde(serialize_with = "write_local_time")]
+    pub end: PrimitiveDateTime,
+}
+
+impl BookingRequest {
+    pub fn new(
+        venue_id: &str,
+        space_ids: &[String],
+        title: Option<&str>,
+        from: PrimitiveDateTime,
+        till: PrimitiveDateTime,
+    ) -> Self {
+        Self {
+            venue: venue_id.to_string(),
+            venueuser: None,
+            spaces: space_ids.to_vec(),
+            title: title.map(str::to_string),
+            start: from,
+            end: till,
+        }
+    }
+
+    /// The request body, with the fields in the order above.
+    pub fn body(&self) -> impl Serialize + '_ {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            booking: &'a BookingRequest,
+        }
+        Body { booking: self }
+    }
+
+    /// Whether `booking` is the one this request makes: the same spaces at the same times,
+    /// for the same member when that's known.
+    pub fn matches(&self, booking: &Booking) -> bool {
+        let mut spaces = self.spaces.clone();
+        let mut booked = booking.space_ids.clone();
+        spaces.sort();
+        booked.sort();
+        spaces == booked
+            && (self.start, self.end) == (booking.start, booking.end)
+            && self
+                .venueuser
+                .as_ref()
+                .is_none_or(|member| booking.venueuser.as_ref() == Some(member))
+    }
+}
+
+/// A booking Skedda has made.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CreatedBooking {
+    #[serde(deserialize_with = "id")]
+    pub id: String,
+    /// What the venue charges for it, when it says.
+    #[serde(default, deserialize_with = "lenient")]
+    pub price: Option<f64>,
+}
+
+/// The `POST /bookings` response.
+#[derive(Debug, Deserialize)]
+pub struct CreateBookingResponse {
+    pub booking: CreatedBooking,
+}
+
+/// Treats `null` like a missing field.
+fn nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Reads a small value, yielding `None` rather than an error when it has an unexpected shape.
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// A list of strings, skipping entries of any other shape.
+fn strings<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let values = Option::<Vec<serde_json::Value>>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .flatten()
+        .filter_map(|value| match value {
+            serde_json::Value::String(text) => Some(text),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Skedda sends ids as numbers but occasionally as strings; syres keeps them as strings.
+/// Values of any other shape are skipped rather than rejected.
+struct LenientIdVisitor;
+
+impl<'de> Visitor<'de> for LenientIdVisitor {
+    type Value = Option<Cow<'de, str>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Borrowed(value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(value.to_string())))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(value.to_string())))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(value.to_string())))
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        LenientStrVisitor.visit_seq(seq).map(|_| None)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        LenientStrVisitor.visit_map(map).map(|_| None)
+    }
+}
+
+/// Accepts any value, keeping it only when it's a string.
+struct LenientStrVisitor;
+
+impl<'de> Visitor<'de> for LenientStrVisitor {
+    type Value = Option<Cow<'de, str>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Borrowed(value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(value.to_string())))
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+}
+
+fn id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    deserializer
+        .deserialize_any(LenientIdVisitor)?
+        .map(Cow::into_owned)
+        .ok_or_else(|| de::Error::custom("expected a string or number id"))
+}
+
+fn optional_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(deserializer
+        .deserialize_any(LenientIdVisitor)?
+        .map(Cow::into_owned))
+}
+
+/// A list of ids, skipping any that aren't strings or numbers.
+fn ids<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(borrowed_ids(deserializer)?
+        .into_iter()
+        .map(Cow::into_owned)
+        .collect())
+}
+
+fn borrowed_ids<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Cow<'de, str>>, D::Error> {
+    struct Id<'a>(Option<Cow<'a, str>>);
+
+    impl<'de> Deserialize<'de> for Id<'de> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(LenientIdVisitor).map(Id)
+        }
+    }
+
+    Ok(nullable::<_, Vec<Id<'de>>>(deserializer)?
+        .into_iter()
+        .filter_map(|id| id.0)
+        .collect())
+}
+
+fn lenient_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'de, str>>, D::Error> {
+    deserializer.deserialize_any(LenientStrVisitor)
+}
+
+/// Booking times are local wall-clock times without an offset, e.g. `2024-05-01T09:00:00`.
+fn local_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PrimitiveDateTime, D::Error> {
+    let text = Cow::<str>::deserialize(deserializer)?;
+    PrimitiveDateTime::parse(&text, &Iso8601::DEFAULT).map_err(de::Error::custom)
+}
+
+/// Writes a booking time the way Skedda sends them, e.g. `2024-05-01T09:00:00`.
+fn write_local_time<S: Serializer>(
+    time: &PrimitiveDateTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    const FORMAT: &[BorrowedFormatItem] =
+        format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]");
+    let text = time.format(FORMAT).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(&text)
+}
+
+/// Just the spaces, space tags and bookings of a `/webs` response, borrowing strings from the
+/// response body where it can. The overview parses every venue this way; views that need a
+/// space's description, photo or the member list use [`WebsResponse`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WebsSummary<'a> {
+    #[serde(borrow, deserialize_with = "nullable")]
+    pub spaces: Vec<SpaceSummary<'a>>,
+    #[serde(borrow, deserialize_with = "nullable")]
+    pub venue: Vec<VenueSummary<'a>>,
+    #[serde(borrow, deserialize_with = "nullable")]
+    pub bookings: Vec<BookingSummary<'a>>,
+}
+
+impl<'a> WebsSummary<'a> {
+    pub fn parse(body: &'a [u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(body)
+    }
+
+    /// Ids of the spaces tagged with the location.
+    pub fn location_space_ids(&self, location: &str) -> &[Cow<'a, str>] {
+        self.venue
+            .first()
+            .and_then(|venue| {
+                venue
+                    .space_presentation
+                    .space_tags
+                    .iter()
+                    .find(|tag| tag.name == location)
+            })
+            .map_or(&[], |tag| tag.space_ids.as_slice())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpaceSummary<'a> {
+    #[serde(borrow, deserialize_with = "borrowed_id")]
+    pub id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(default, borrow, deserialize_with = "lenient_str")]
+    pub space_type: Option<Cow<'a, str>>,
+    #[serde(default, borrow, rename = "type", deserialize_with = "lenient_str")]
+    pub type_name: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "lenient_str")]
+    pub category: Option<Cow<'a, str>>,
+}
+
+impl SpaceSummary<'_> {
+    /// The space's type, or its name when the venue doesn't set types.
+    pub fn label(&self) -> &str {
+        [&self.space_type, &self.type_name, &self.category]
+            .into_iter()
+            .flatten()
+            .find(|kind| !kind.trim().is_empty())
+            .unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VenueSummary<'a> {
+    #[serde(borrow)]
+    pub space_presentation: SpacePresentationSummary<'a>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SpacePresentationSummary<'a> {
+    #[serde(borrow, deserialize_with = "nullable")]
+    pub space_tags: Vec<SpaceTagSummary<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpaceTagSummary<'a> {
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(default, borrow, deserialize_with = "borrowed_ids")]
+    pub space_ids: Vec<Cow<'a, str>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BookingSummary<'a> {
+    #[serde(borrow, rename = "spaces", deserialize_with = "borrowed_ids")]
+    pub space_ids: Vec<Cow<'a, str>>,
+    #[serde(deserialize_with = "local_time")]
+    pub start: PrimitiveDateTime,
+    #[serde(deserialize_with = "local_time")]
+    pub end: PrimitiveDateTime,
+}
+
+fn borrowed_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Cow<'de, str>, D::Error> {
+    deserializer
+        .deserialize_any(LenientIdVisitor)?
+        .ok_or_else(|| de::Error::custom("expected a string or number id"))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn every_fixture_parses_into_both_models() {
+        for name in fixtures::WEBS {
+            let body = fixtures::webs(name);
+            let full: WebsResponse = serde_json::from_slice(&body)
+                .unwrap_or_else(|err| panic!("{name} didn't parse: {err}"));
+            let summary = WebsSummary::parse(&body)
+                .unwrap_or_else(|err| panic!("{name} didn't parse as a summary: {err}"));
+
+            assert_eq!(full.spaces.len(), summary.spaces.len(), "{name}");
+            assert_eq!(full.bookings.len(), summary.bookings.len(), "{name}");
+            for (space, lean) in full.spaces.iter().zip(&summary.spaces) {
+                assert_eq!(space.id, lean.id, "{name}");
+                assert_eq!(space.name, lean.name, "{name}");
+            }
+            for (booking, lean) in full.bookings.iter().zip(&summary.bookings) {
+                assert_eq!(booking.space_ids, lean.space_ids, "{name}");
+                assert_eq!(
+                    (booking.start, booking.end),
+                    (lean.start, lean.end),
+                    "{name}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn large_venue_keeps_every_record() {
+        let webs = fixtures::webs_response("large_venue.json");
+        assert_eq!(webs.spaces.len(), 180);
+        assert_eq!(webs.bookings.len(), 630);
+        assert_eq!(webs.venueusers.len(), 50);
+        let tags = &webs.venue[0]
+            .space_presentation
+            .as_ref()
+            .unwrap()
+            .space_tags;
+        assert_eq!(tags.len(), 12);
+        assert!(tags.iter().all(|tag| tag.space_ids.len() == 15));
+    }
+
+    #[test]
+    fn quirks_are_read_leniently() {
+        let webs = fixtures::webs_response("quirks.json");
+
+        let desk = &webs.spaces[0];
+        assert_eq!(desk.id, "101");
+        assert_eq!(desk.name, "Desk \"A\"");
+        assert_eq!(desk.space_type, None);
+        assert_eq!(desk.type_name.as_deref(), Some("Desk"));
+        assert_eq!(desk.description, None);
+        assert_eq!(webs.spaces[1].id, "102");
+
+        let venue = &webs.venue[0];
+        assert_eq!(venue.booking_increment, None);
+        assert_eq!(venue.time_increment, Some(30));
+        let tags = &venue.space_presentation.as_ref().unwrap().space_tags;
+        assert_eq!(tags[0].space_ids, ["101", "102", "unknown"]);
+        assert!(tags[1].space_ids.is_empty());
+
+        let booking = &webs.bookings[0];
+        assert_eq!(booking.id.as_deref(), Some("b-1"));
+        assert_eq!(booking.venueuser.as_deref(), Some("3001"));
+        assert_eq!(booking.title, None);
+        assert_eq!(booking.space_ids, ["101", "102"]);
+        assert_eq!(booking.start, datetime!(2024-05-01 09:00));
+        assert_eq!(webs.bookings[1].id, None);
+
+        assert!(webs.venueusers.is_empty());
+        let user = webs.venueuser.as_ref().unwrap();
+        assert_eq!(user.is_admin, None);
+        assert_eq!(user.roles, ["Member"]);
+        assert_eq!(user.role.as_deref(), Some("Venue Manager"));
+    }
+
+    #[test]
+    fn summary_labels_fall_back_from_type_to_name() {
+        let body = fixtures::webs("quirks.json");
+        let summary = WebsSummary::parse(&body).unwrap();
+        let labels: Vec<&str> = summary.spaces.iter().map(SpaceSummary::label).collect();
+        assert_eq!(labels, ["Desk", "Meeting", "Not Tagged"]);
+        assert_eq!(summary.location_space_ids("Decatur").len(), 3);
+        assert!(summary.location_space_ids("Midtown").is_empty());
+    }
+
+    #[test]
+    fn booking_requests_match_their_golden_bodies() {
+        let one = BookingRequest::new(
+            "1",
+            &["103".to_string()],
+            None,
+            datetime!(2024-05-01 9:30),
+            datetime!(2024-05-01 10:45),
+        );
+        let for_member = BookingRequest {
+            venueuser: Some("3002".to_string()),
+            ..BookingRequest::new(
+                "1",
+                &["201".to_string(), "202".to_string()],
+                Some("Workshop"),
+                datetime!(2024-05-01 13:00),
+                datetime!(2024-05-01 17:00),
+            )
+        };
+        for (request, golden) in [
+            (one, "booking.json"),
+            (for_member, "booking_for_member.json"),
+        ] {
+            let body = serde_json::to_string_pretty(&request.body()).unwrap();
+            assert_eq!(body, fixtures::request(golden).trim_end(), "{golden}");
+        }
+    }
+
+    #[test]
+    fn booking_requests_find_the_booking_they_made() {
+        let webs = fixtures::webs_response("small_venue.json");
+        let workshop = BookingRequest::new(
+            "1",
+            &["202".to_string(), "201".to_string()],
+            None,
+            datetime!(2024-05-01 13:00),
+            datetime!(2024-05-01 17:00),
+        );
+        let found: Vec<_> = webs
+            .bookings
+            .iter()
+            .filter(|booking| workshop.matches(booking))
+            .filter_map(|booking| booking.id.as_deref())
+            .collect();
+        assert_eq!(found, ["9004"]);
+
+        let someone_else = BookingRequest {
+            venueuser: Some("3001".to_string()),
+            ..workshop
+        };
+        assert!(
+            !webs
+                .bookings
+                .iter()
+                .any(|booking| someone_else.matches(booking))
+        );
+    }
+}
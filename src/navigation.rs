@@ -0,0 +1,349 @@
+use crate::app::ViewState;
+
+/// A request to move between views, from a key press or a finished request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nav {
+    /// Esc or `q`.
+    Back,
+    /// Enter.
+    Open,
+    /// Pick which venue the location list shows.
+    Venues,
+    /// Where's free now, or with `go_now`, where a space of the wanted type is free for a while.
+    Overview { go_now: bool },
+    /// Everyone's bookings for the highlighted space.
+    SpaceBookings,
+    /// The week-ahead planning board.
+    Plan,
+    /// The list of my upcoming bookings.
+    MyBookings,
+    /// The month of the highlighted space's bookings.
+    Month,
+    /// The week of the highlighted space's bookings, slot by slot.
+    Week,
+    /// The booking submitted from the booking form was made.
+    Booked,
+    /// Every booking of a batch submitted from the booking form has been sent.
+    BatchSent,
+    /// `b`: the booking form at my usual location, filled in from the next half hour.
+    QuickBook,
+}
+
+/// Work the app does alongside a view change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Quit,
+    /// Load the location under the cursor in the location list into the booking form.
+    OpenLocation,
+    /// Load the location under the cursor in the overview into the booking form.
+    OpenOverviewLocation,
+    LoadOverview {
+        go_now: bool,
+    },
+    /// Put the venue picker's cursor on the active venue.
+    ShowVenuePicker,
+    /// Limit the location list to the venue picked.
+    SwitchVenue,
+    /// Forget the venue and location being booked.
+    ClearSelection,
+    /// Refuse to go on when the selected window breaks one of the space's booking rules.
+    CheckBookingRules,
+    /// Warn, once for each window, when the booking would overlap one of my own.
+    CheckMyBookings,
+    /// Send the selected booking to Skedda; the form stays open until it's made.
+    SubmitBooking,
+    /// Forget the location's saved draft now it's been booked.
+    DiscardDraft,
+    /// Refuse to go on while the booking submitted from the booking form is on its way.
+    AwaitSubmission,
+    /// Leave a batch's bookings as they are, once none of its requests are still out.
+    KeepBatch,
+    /// Start the space bookings list from the top of today, with nothing marked.
+    ResetSpaceBookings,
+    /// Put the month view's cursor on the booking form's day.
+    ShowMonth,
+    /// Show the day under the month view's cursor in the booking form.
+    OpenCalendarDay,
+    /// Put the week view's cursor on the booking form's day and slot.
+    ShowWeek,
+    /// Show the day under the week view's cursor in the booking form, at its slot.
+    OpenWeekSlot,
+    /// Open the quick-book location with its preferred space and window selected.
+    QuickBook,
+    /// Put the planning board's cursor on today.
+    ShowPlan,
+    /// Put the my bookings list's cursor on the soonest booking.
+    ShowMyBookings,
+    /// Open the location of the booking under the my bookings list's cursor, on its day and
+    /// at its start.
+    OpenMyBooking,
+    /// Open the location highlighted in the location list at the gap under the planning
+    /// board's cursor.
+    OpenPlannedGap,
+}
+
+/// Every view, for checking the table covers them all.
+pub const VIEWS: [ViewState; 11] = [
+    ViewState::LocationSelection,
+    ViewState::BookingForm,
+    ViewState::Confirmation,
+    ViewState::VenuePicker,
+    ViewState::SpaceBookings,
+    ViewState::Overview,
+    ViewState::WeekPlan,
+    ViewState::MyBookings,
+    ViewState::MonthCalendar,
+    ViewState::Calendar,
+    ViewState::BatchResults,
+];
+
+/// The view `nav` leads to from `view` and the effects to run on the way, or `None` when it
+/// does nothing there.
+///
+/// If an effect can't run, such as opening a location when the cursor isn't on one, the app
+/// stays where it is.
+pub fn transition(view: ViewState, nav: Nav) -> Option<(ViewState, &'static [Effect])> {
+    use ViewState::*;
+
+    Some(match (view, nav) {
+        (LocationSelection, Nav::Back) => (LocationSelection, &[Effect::Quit]),
+        (LocationSelection, Nav::Open) => (BookingForm, &[Effect::OpenLocation]),
+        (LocationSelection, Nav::Venues) => (VenuePicker, &[Effect::ShowVenuePicker]),
+        (LocationSelection, Nav::Overview { go_now: false }) => {
+            (Overview, &[Effect::LoadOverview { go_now: false }])
+        }
+        (LocationSelection, Nav::Overview { go_now: true }) => {
+            (Overview, &[Effect::LoadOverview { go_now: true }])
+        }
+        (LocationSelection, Nav::Plan) => (WeekPlan, &[Effect::ShowPlan]),
+        (LocationSelection, Nav::MyBookings) => (MyBookings, &[Effect::ShowMyBookings]),
+
+        (VenuePicker, Nav::Back) => (LocationSelection, &[]),
+        (VenuePicker, Nav::Open) => (LocationSelection, &[Effect::SwitchVenue]),
+
+        (BookingForm, Nav::Back) => (LocationSelection, &[Effect::ClearSelection]),
+        (BookingForm, Nav::Open) => (
+            BookingForm,
+            &[
+                Effect::CheckBookingRules,
+                Effect::CheckMyBookings,
+                Effect::SubmitBooking,
+            ],
+        ),
+        (BookingForm, Nav::Booked) => (Confirmation, &[Effect::DiscardDraft]),
+        (BookingForm, Nav::BatchSent) => (BatchResults, &[]),
+        (BookingForm, Nav::SpaceBookings) => (SpaceBookings, &[Effect::ResetSpaceBookings]),
+        (BookingForm, Nav::Month) => (MonthCalendar, &[Effect::ShowMonth]),
+        (BookingForm, Nav::Week) => (Calendar, &[Effect::ShowWeek]),
+
+        (Confirmation, Nav::Back | Nav::Open) => (LocationSelection, &[Effect::ClearSelection]),
+
+        (BatchResults, Nav::Back | Nav::Open) => (BookingForm, &[Effect::KeepBatch]),
+
+        (SpaceBookings, Nav::Back) => (BookingForm, &[]),
+
+        (Overview, Nav::Back) => (LocationSelection, &[]),
+        (Overview, Nav::Open) => (BookingForm, &[Effect::OpenOverviewLocation]),
+
+        (WeekPlan, Nav::Back) => (LocationSelection, &[]),
+        (WeekPlan, Nav::Open) => (BookingForm, &[Effect::OpenPlannedGap]),
+
+        (MyBookings, Nav::Back) => (LocationSelection, &[]),
+        (MyBookings, Nav::Open) => (BookingForm, &[Effect::OpenMyBooking]),
+
+        (MonthCalendar, Nav::Back) => (BookingForm, &[]),
+        (MonthCalendar, Nav::Open) => (BookingForm, &[Effect::OpenCalendarDay]),
+
+        (Calendar, Nav::Back) => (BookingForm, &[]),
+        (Calendar, Nav::Open) => (BookingForm, &[Effect::OpenWeekSlot]),
+
+        (BookingForm, Nav::QuickBook) => {
+            (BookingForm, &[Effect::AwaitSubmission, Effect::QuickBook])
+        }
+        (BatchResults, Nav::QuickBook) => (BookingForm, &[Effect::KeepBatch, Effect::QuickBook]),
+        (_, Nav::QuickBook) => (BookingForm, &[Effect::QuickBook]),
+
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const NAVS: [Nav; 13] = [
+        Nav::Back,
+        Nav::Open,
+        Nav::Venues,
+        Nav::Overview { go_now: false },
+        Nav::Overview { go_now: true },
+        Nav::SpaceBookings,
+        Nav::Plan,
+        Nav::MyBookings,
+        Nav::Month,
+        Nav::Week,
+        Nav::Booked,
+        Nav::BatchSent,
+        Nav::QuickBook,
+    ];
+
+    fn next(view: ViewState) -> impl Iterator<Item = ViewState> {
+        NAVS.into_iter()
+            .filter_map(move |nav| transition(view, nav))
+            .map(|(view, _)| view)
+    }
+
+    #[test]
+    fn every_view_is_reachable_from_the_location_list() {
+        let mut seen = HashSet::from([ViewState::LocationSelection]);
+        let mut queue = vec![ViewState::LocationSelection];
+        while let Some(view) = queue.pop() {
+            for view in next(view) {
+                if seen.insert(view) {
+                    queue.push(view);
+                }
+            }
+        }
+        assert_eq!(seen, HashSet::from(VIEWS));
+    }
+
+    #[test]
+    fn back_always_leads_to_the_location_list() {
+        for view in VIEWS {
+            let mut current = view;
+            for _ in 0..VIEWS.len() {
+                if current == ViewState::LocationSelection {
+                    break;
+                }
+                current = transition(current, Nav::Back)
+                    .unwrap_or_else(|| panic!("no way back from {current:?}"))
+                    .0;
+            }
+            assert_eq!(
+                current,
+                ViewState::LocationSelection,
+                "stuck going back from {view:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_the_location_list_quits() {
+        for view in VIEWS {
+            for nav in NAVS {
+                let quits = transition(view, nav)
+                    .is_some_and(|(_, effects)| effects.contains(&Effect::Quit));
+                assert_eq!(
+                    quits,
+                    (view, nav) == (ViewState::LocationSelection, Nav::Back),
+                    "{view:?} {nav:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn leaving_the_booking_flow_forgets_the_selection() {
+        for view in [ViewState::BookingForm, ViewState::Confirmation] {
+            let (next, effects) = transition(view, Nav::Back).unwrap();
+            assert_eq!(next, ViewState::LocationSelection);
+            assert!(effects.contains(&Effect::ClearSelection), "{view:?}");
+        }
+    }
+
+    #[test]
+    fn opening_a_location_loads_it() {
+        assert_eq!(
+            transition(ViewState::LocationSelection, Nav::Open),
+            Some((ViewState::BookingForm, &[Effect::OpenLocation][..]))
+        );
+        assert_eq!(
+            transition(ViewState::Overview, Nav::Open),
+            Some((ViewState::BookingForm, &[Effect::OpenOverviewLocation][..]))
+        );
+        assert_eq!(
+            transition(ViewState::WeekPlan, Nav::Open),
+            Some((ViewState::BookingForm, &[Effect::OpenPlannedGap][..]))
+        );
+        assert_eq!(
+            transition(ViewState::MyBookings, Nav::Open),
+            Some((ViewState::BookingForm, &[Effect::OpenMyBooking][..]))
+        );
+        assert_eq!(
+            transition(ViewState::LocationSelection, Nav::Overview { go_now: true }),
+            Some((
+                ViewState::Overview,
+                &[Effect::LoadOverview { go_now: true }][..]
+            ))
+        );
+    }
+
+    #[test]
+    fn bookings_are_confirmed_once_made() {
+        let (next, effects) = transition(ViewState::BookingForm, Nav::Open).unwrap();
+        assert_eq!(next, ViewState::BookingForm);
+        assert_eq!(effects.last(), Some(&Effect::SubmitBooking));
+        for view in VIEWS {
+            for nav in NAVS {
+                if transition(view, nav).is_some_and(|(next, _)| next == ViewState::Confirmation) {
+                    assert_eq!((view, nav), (ViewState::BookingForm, Nav::Booked));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn batch_results_are_kept_on_the_way_out() {
+        assert_eq!(
+            transition(ViewState::BookingForm, Nav::BatchSent),
+            Some((ViewState::BatchResults, &[][..]))
+        );
+        for nav in [Nav::Back, Nav::Open] {
+            assert_eq!(
+                transition(ViewState::BatchResults, nav),
+                Some((ViewState::BookingForm, &[Effect::KeepBatch][..])),
+                "{nav:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn quick_book_works_from_every_view() {
+        for view in VIEWS {
+            let (next, effects) = transition(view, Nav::QuickBook).unwrap();
+            assert_eq!(next, ViewState::BookingForm, "{view:?}");
+            assert_eq!(effects.last(), Some(&Effect::QuickBook), "{view:?}");
+        }
+    }
+
+    #[test]
+    fn quick_book_keeps_the_batch_and_waits_for_a_submission() {
+        assert_eq!(
+            transition(ViewState::BatchResults, Nav::QuickBook),
+            Some((
+                ViewState::BookingForm,
+                &[Effect::KeepBatch, Effect::QuickBook][..]
+            ))
+        );
+        assert_eq!(
+            transition(ViewState::BookingForm, Nav::QuickBook),
+            Some((
+                ViewState::BookingForm,
+                &[Effect::AwaitSubmission, Effect::QuickBook][..]
+            ))
+        );
+    }
+
+    #[test]
+    fn the_venue_picker_only_opens_from_the_location_list() {
+        for view in VIEWS {
+            assert_eq!(
+                transition(view, Nav::Venues).is_some(),
+                view == ViewState::LocationSelection,
+                "{view:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs},
+    sync::{Mutex, OnceLock, PoisonError},
+    time::Instant,
+};
+
+use reqwest::{Url, blocking::ClientBuilder};
+use tracing::{debug, warn};
+
+use crate::config::{IpVersion, LimitsConfig, NetworkConfig, RetryConfig, VenueConfig};
+
+static CONFIG: OnceLock<NetworkConfig> = OnceLock::new();
+
+/// Addresses resolved this run, by `host:port`.
+static DNS_CACHE: OnceLock<Mutex<HashMap<String, Vec<SocketAddr>>>> = OnceLock::new();
+
+/// Records the network settings that HTTP clients are built with.
+pub fn init(config: &NetworkConfig) {
+    let _ = CONFIG.set(config.clone());
+}
+
+/// The request limits for the venue: its own, or the network-wide ones.
+pub fn limits(venue: &VenueConfig) -> LimitsConfig {
+    venue
+        .limits
+        .unwrap_or_else(|| CONFIG.get_or_init(NetworkConfig::default).limits)
+}
+
+/// How requests that fail in a way that may pass are tried again.
+pub fn retry() -> RetryConfig {
+    CONFIG.get_or_init(NetworkConfig::default).retry
+}
+
+/// Points the client at `url`'s host through the configured host overrides or, with DNS
+/// caching on, the addresses already resolved for it this run, ordered or filtered by the
+/// configured IP version. Forcing a version also binds the client to it for other hosts.
+///
+/// Hosts that can't be resolved here are left to the client, so the error comes from the
+/// request that needed them.
+pub fn configure(builder: ClientBuilder, url: &str) -> ClientBuilder {
+    let config = CONFIG.get_or_init(NetworkConfig::default);
+    let builder = match config.ip_version {
+        IpVersion::V4 => builder.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        IpVersion::V6 => builder.local_address(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        _ => builder,
+    };
+    let Some((host, port)) = Url::parse(url)
+        .ok()
+        .and_then(|url| Some((url.host_str()?.to_string(), url.port_or_known_default()?)))
+    else {
+        return builder;
+    };
+
+    if let Some(&ip) = config.hosts.get(&host) {
+        debug!(host, %ip, "using configured address");
+        return builder.resolve(&host, SocketAddr::new(ip, port));
+    }
+    if !config.dns_cache && config.ip_version == IpVersion::Any {
+        return builder;
+    }
+    let Some(mut addrs) = resolve(&host, port, config.dns_cache) else {
+        return builder;
+    };
+    match config.ip_version {
+        IpVersion::Any => {}
+        IpVersion::PreferV4 => addrs.sort_by_key(|addr| !addr.is_ipv4()),
+        IpVersion::PreferV6 => addrs.sort_by_key(|addr| !addr.is_ipv6()),
+        IpVersion::V4 => addrs.retain(SocketAddr::is_ipv4),
+        IpVersion::V6 => addrs.retain(SocketAddr::is_ipv6),
+    }
+    if addrs.is_empty() {
+        warn!(host, ip_version = ?config.ip_version, "host has no addresses of that version");
+        return builder;
+    }
+    builder.resolve_to_addrs(&host, &addrs)
+}
+
+fn resolve(host: &str, port: u16, cached: bool) -> Option<Vec<SocketAddr>> {
+    let key = format!("{host}:{port}");
+    let cache = DNS_CACHE.get_or_init(Default::default);
+    if let Some(addrs) = cached
+        .then(|| {
+            cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&key)
+                .cloned()
+        })
+        .flatten()
+    {
+        return Some(addrs);
+    }
+
+    let started = Instant::now();
+    let resolved = (host, port).to_socket_addrs();
+    let lookup_ms = started.elapsed().as_millis() as u64;
+    let addrs: Vec<SocketAddr> = match resolved {
+        Ok(addrs) => addrs.collect(),
+        Err(err) => {
+            warn!(host, "failed to resolve host: {err}");
+            return None;
+        }
+    };
+    if addrs.is_empty() {
+        return None;
+    }
+    debug!(host, ?addrs, lookup_ms, "resolved host");
+    if cached {
+        cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, addrs.clone());
+    }
+    Some(addrs)
+}
@@ -0,0 +1,322 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use reqwest::blocking::Client;
+use time::{PrimitiveDateTime, format_description::BorrowedFormatItem, macros::format_description};
+use tracing::{error, info};
+
+use crate::{
+    config::{Channel, NotifyConfig, NotifyEvent},
+    grid, network,
+    openings::Opening,
+};
+
+/// How long a webhook or ntfy server gets to take a notification.
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A notification: a short title and a few lines of text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub title: String,
+    pub body: String,
+}
+
+impl Message {
+    /// "Booked Boardroom" with where and when, e.g. "Decatur, Fri 1 Aug 2025 09:00–12:00".
+    pub fn booked(
+        space: &str,
+        place: &str,
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+    ) -> Self {
+        const DAY: &[BorrowedFormatItem] = format_description!(
+            "[weekday repr:short] [day padding:none] [month repr:short] [year]"
+        );
+        Self {
+            title: format!("Booked {space}"),
+            body: format!(
+                "{place}, {} {}–{}",
+                start
+                    .format(DAY)
+                    .unwrap_or_else(|_| start.date().to_string()),
+                grid::format_time(start.time()),
+                grid::format_time(end.time())
+            ),
+        }
+    }
+
+    /// "Bookings for Fri 15 Aug open in 10m", with the venue and the spaces that open.
+    pub fn opening(venue: &str, opening: &Opening, now: PrimitiveDateTime) -> Self {
+        Self {
+            title: opening.describe(now),
+            body: format!("{venue}: {}", opening.spaces.join(", ")),
+        }
+    }
+}
+
+/// Somewhere notifications can be sent. A new kind of channel is an implementation of this
+/// and a [`Channel`] to pick it by in config.
+pub trait Notifier {
+    fn send(&self, message: &Message) -> color_eyre::Result<()>;
+}
+
+/// A desktop notification, through `notify-send` on Linux or `osascript` on macOS.
+pub struct Desktop;
+
+impl Notifier for Desktop {
+    fn send(&self, message: &Message) -> color_eyre::Result<()> {
+        let status = if cfg!(target_os = "macos") {
+            let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+            Command::new("osascript")
+                .arg("-e")
+                .arg(format!(
+                    "display notification \"{}\" with title \"{}\"",
+                    quote(&message.body),
+                    quote(&message.title)
+                ))
+                .status()
+        } else {
+            Command::new("notify-send")
+                .arg(&message.title)
+                .arg(&message.body)
+                .status()
+        }
+        .wrap_err("failed to run the desktop notifier")?;
+        if !status.success() {
+            bail!("the desktop notifier exited with {status}");
+        }
+        Ok(())
+    }
+}
+
+/// Posts `{"title", "text"}` as JSON.
+pub struct Webhook {
+    pub url: String,
+}
+
+impl Notifier for Webhook {
+    fn send(&self, message: &Message) -> color_eyre::Result<()> {
+        client(&self.url)?
+            .post(&self.url)
+            .json(&serde_json::json!({ "title": message.title, "text": message.body }))
+            .send()
+            .and_then(|response| response.error_for_status())
+            // Whoever has the URL can post as syres, so it stays out of the error and the log.
+            .map_err(reqwest::Error::without_url)
+            .wrap_err("failed to post to the webhook")?;
+        Ok(())
+    }
+}
+
+/// Hands the message to the local `sendmail`.
+pub struct Email {
+    pub to: String,
+}
+
+impl Notifier for Email {
+    fn send(&self, message: &Message) -> color_eyre::Result<()> {
+        let mut sendmail = Command::new("sendmail")
+            .arg("-t")
+            .stdin(Stdio::piped())
+            .spawn()
+            .wrap_err("failed to run sendmail")?;
+        if let Some(mut stdin) = sendmail.stdin.take() {
+            write!(
+                stdin,
+                "To: {}\nSubject: {}\nContent-Type: text/plain; charset=utf-8\n\n{}\n",
+                self.to, message.title, message.body
+            )?;
+        }
+        let status = sendmail.wait()?;
+        if !status.success() {
+            bail!("sendmail exited with {status}");
+        }
+        Ok(())
+    }
+}
+
+/// Publishes to an ntfy topic, which pushes to the phones and browsers subscribed to it.
+pub struct Ntfy {
+    /// The topic's URL, e.g. `https://ntfy.sh/my-desks`.
+    pub url: String,
+}
+
+impl Notifier for Ntfy {
+    fn send(&self, message: &Message) -> color_eyre::Result<()> {
+        client(&self.url)?
+            .post(&self.url)
+            .header("Title", &message.title)
+            .body(message.body.clone())
+            .send()
+            .and_then(|response| response.error_for_status())
+            // The topic's URL is all it takes to read or post to it.
+            .map_err(reqwest::Error::without_url)
+            .wrap_err("failed to publish to ntfy")?;
+        Ok(())
+    }
+}
+
+fn client(url: &str) -> color_eyre::Result<Client> {
+    Ok(network::configure(Client::builder(), url)
+        .timeout(SEND_TIMEOUT)
+        .build()?)
+}
+
+/// The notifier for a channel, set up from its settings.
+pub fn notifier(channel: Channel, config: &NotifyConfig) -> color_eyre::Result<Box<dyn Notifier>> {
+    let setting = |value: &Option<String>, name: &str| {
+        value
+            .clone()
+            .ok_or_else(|| eyre!("notify.{name} isn't set"))
+    };
+    Ok(match channel {
+        Channel::Desktop => Box::new(Desktop),
+        Channel::Webhook => Box::new(Webhook {
+            url: setting(&config.webhook_url, "webhook_url")?,
+        }),
+        Channel::Email => Box::new(Email {
+            to: setting(&config.email, "email")?,
+        }),
+        Channel::Ntfy => Box::new(Ntfy {
+            url: setting(&config.ntfy_url, "ntfy_url")?,
+        }),
+    })
+}
+
+/// Sends the message to each channel the event is routed to. A channel that fails is logged
+/// and doesn't stop the others; the error says how many failed.
+pub fn notify(
+    config: &NotifyConfig,
+    event: NotifyEvent,
+    message: &Message,
+) -> color_eyre::Result<()> {
+    let channels = config.channels(event);
+    let notifiers: Vec<(Channel, color_eyre::Result<Box<dyn Notifier>>)> = channels
+        .iter()
+        .map(|&channel| (channel, notifier(channel, config)))
+        .collect();
+    let failed = deliver(&notifiers, message);
+    match failed {
+        0 => {
+            info!(
+                event = event.name(),
+                channels = channels.len(),
+                "sent notification"
+            );
+            Ok(())
+        }
+        failed => bail!(
+            "{failed} of the {} channels for {} notifications failed; see the log",
+            channels.len(),
+            event.name()
+        ),
+    }
+}
+
+/// Sends the message through each notifier, returning how many failed.
+fn deliver(
+    notifiers: &[(Channel, color_eyre::Result<Box<dyn Notifier>>)],
+    message: &Message,
+) -> usize {
+    notifiers
+        .iter()
+        .filter(|(channel, notifier)| {
+            let sent = match notifier {
+                Ok(notifier) => notifier.send(message),
+                Err(err) => Err(eyre!("{err:#}")),
+            };
+            if let Err(err) = &sent {
+                error!(?channel, "failed to send notification: {err:#}");
+            }
+            sent.is_err()
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, fs, rc::Rc, sync::Mutex};
+
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::report;
+
+    /// Keeps what it's sent, or fails when it has no inbox.
+    struct Inbox(Option<Rc<RefCell<Vec<Message>>>>);
+
+    impl Notifier for Inbox {
+        fn send(&self, message: &Message) -> color_eyre::Result<()> {
+            let Some(inbox) = &self.0 else {
+                bail!("unreachable");
+            };
+            inbox.borrow_mut().push(message.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn a_failing_channel_doesnt_stop_the_others() {
+        let inbox = Rc::new(RefCell::new(Vec::new()));
+        let notifiers: Vec<(Channel, color_eyre::Result<Box<dyn Notifier>>)> = vec![
+            (Channel::Desktop, Ok(Box::new(Inbox(None)))),
+            (Channel::Webhook, Err(eyre!("notify.webhook_url isn't set"))),
+            (Channel::Ntfy, Ok(Box::new(Inbox(Some(inbox.clone()))))),
+        ];
+        let message = Message::booked(
+            "Boardroom",
+            "Decatur",
+            datetime!(2025-08-01 9:00),
+            datetime!(2025-08-01 12:00),
+        );
+        assert_eq!(message.title, "Booked Boardroom");
+        assert_eq!(message.body, "Decatur, Fri 1 Aug 2025 09:00–12:00");
+        assert_eq!(deliver(&notifiers, &message), 2);
+        assert_eq!(*inbox.borrow(), [message]);
+    }
+
+    #[test]
+    fn failed_deliveries_keep_the_url_out_of_the_log() {
+        // Nothing listens on port 1.
+        let notifiers: Vec<(Channel, color_eyre::Result<Box<dyn Notifier>>)> = vec![
+            (
+                Channel::Webhook,
+                Ok(Box::new(Webhook {
+                    url: "http://127.0.0.1:1/hooks/T000/B000/hook-secret".to_string(),
+                })),
+            ),
+            (
+                Channel::Ntfy,
+                Ok(Box::new(Ntfy {
+                    url: "http://127.0.0.1:1/topic-secret".to_string(),
+                })),
+            ),
+        ];
+        let message = Message {
+            title: "Booked Boardroom".to_string(),
+            body: "Decatur".to_string(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("syres.log");
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(Mutex::new(fs::File::create(&log_path).unwrap()))
+            .with_ansi(false)
+            .finish();
+        let failed =
+            tracing::subscriber::with_default(subscriber, || deliver(&notifiers, &message));
+        assert_eq!(failed, 2);
+
+        let log = fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("failed to post to the webhook"), "{log}");
+        assert!(log.contains("failed to publish to ntfy"), "{log}");
+        let bundled = report::read_logs(&log_path);
+        for logged in [&log, &bundled] {
+            assert!(!logged.contains("hook-secret"), "{logged}");
+            assert!(!logged.contains("topic-secret"), "{logged}");
+        }
+    }
+}
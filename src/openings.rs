@@ -0,0 +1,174 @@
+use time::{
+    Date, Duration, PrimitiveDateTime, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
+
+use crate::{grid, hours::Hours, rules, skedda::SpaceDetails};
+
+const DAY: &[BorrowedFormatItem] =
+    format_description!("[weekday repr:short] [day padding:none] [month repr:short]");
+
+/// How many days past the edge of a space's advance-booking window to look for one it's
+/// open on.
+const DAYS_TO_SEARCH: u8 = 14;
+
+/// The moment a day comes within a venue's advance-booking window, when its popular spaces
+/// are up for grabs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    /// The day that becomes bookable.
+    pub date: Date,
+    /// When its first slot can be booked.
+    pub at: PrimitiveDateTime,
+    /// The spaces that open then, by name.
+    pub spaces: Vec<String>,
+}
+
+impl Opening {
+    /// "Bookings for Fri 15 Aug open tomorrow at 00:00", "… open in 5m" within the hour, or
+    /// "… are open now" once it has passed, as seen at `now`.
+    pub fn describe(&self, now: PrimitiveDateTime) -> String {
+        let day = |date: Date| date.format(DAY).unwrap_or_else(|_| date.to_string());
+        let lead = self.at - now;
+        if lead <= Duration::ZERO {
+            return format!("Bookings for {} are open now", day(self.date));
+        }
+        let when = if lead < Duration::HOUR {
+            let minutes = (lead.whole_seconds() + 59) / 60;
+            format!("in {}", rules::format_length(Duration::minutes(minutes)))
+        } else {
+            let on = match (self.at.date() - now.date()).whole_days() {
+                0 => "today".to_string(),
+                1 => "tomorrow".to_string(),
+                _ => day(self.at.date()),
+            };
+            format!("{on} at {}", grid::format_time(self.at.time()))
+        };
+        format!("Bookings for {} open {when}", day(self.date))
+    }
+}
+
+/// The next opening among `spaces` after `now`: the soonest any of them brings a day within
+/// reach, with every space that opens then. `None` when none limits how far ahead it can be
+/// booked.
+pub fn next<'a>(
+    spaces: impl IntoIterator<Item = &'a SpaceDetails>,
+    now: PrimitiveDateTime,
+) -> Option<Opening> {
+    let mut next: Option<Opening> = None;
+    for space in spaces {
+        let Some((date, at)) = next_for(space, now) else {
+            continue;
+        };
+        match &mut next {
+            Some(opening) if (opening.at, opening.date) == (at, date) => {
+                opening.spaces.push(space.name.clone());
+            }
+            Some(opening) if opening.at <= at => {}
+            _ => {
+                next = Some(Opening {
+                    date,
+                    at,
+                    spaces: vec![space.name.clone()],
+                });
+            }
+        }
+    }
+    next
+}
+
+/// The first day the space is open on that's still out of reach at `now`, and when its first
+/// slot comes within reach.
+fn next_for(space: &SpaceDetails, now: PrimitiveDateTime) -> Option<(Date, PrimitiveDateTime)> {
+    let advance = space.rules.max_advance?;
+    let reach = now + advance;
+    let mut date = reach.date();
+    for _ in 0..DAYS_TO_SEARCH {
+        let first = match space.hours.on(date) {
+            Some(Hours::Closed) => None,
+            Some(Hours::Open(open, _)) => Some(date.with_time(open)),
+            None => Some(date.midnight()),
+        };
+        if let Some(first) = first.filter(|&first| first > reach) {
+            return Some((date, first - advance));
+        }
+        date = date.next_day()?;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::{hours::Schedule, models::OpeningHours, rules::BookingRules};
+
+    fn space(name: &str, advance_days: u32, weekdays: Option<&str>) -> SpaceDetails {
+        let hours = weekdays.map(|open| OpeningHours {
+            days: vec![1, 2, 3, 4, 5],
+            date: None,
+            start: Some(open.to_string()),
+            end: Some("18:00".to_string()),
+        });
+        let closed_weekends = OpeningHours {
+            days: vec![0, 6],
+            date: None,
+            start: None,
+            end: None,
+        };
+        SpaceDetails {
+            name: name.to_string(),
+            rules: BookingRules::from_minutes(None, None, Some(advance_days * 24 * 60)),
+            hours: match hours {
+                Some(hours) => Schedule::new(&[hours, closed_weekends], &[]),
+                None => Schedule::default(),
+            },
+            ..SpaceDetails::default()
+        }
+    }
+
+    #[test]
+    fn finds_when_the_next_day_comes_within_reach() {
+        // Wednesday 1 May; two weeks ahead reaches into Wednesday 15 May.
+        let now = datetime!(2024-05-01 9:10);
+
+        let anytime = [space("Desk 1", 14, None), space("Desk 2", 14, None)];
+        let opening = next(&anytime, now).unwrap();
+        assert_eq!(opening.at, datetime!(2024-05-02 0:00));
+        assert_eq!(opening.spaces, ["Desk 1", "Desk 2"]);
+        assert_eq!(
+            opening.describe(now),
+            "Bookings for Thu 16 May open tomorrow at 00:00"
+        );
+
+        // Open from 8:00 on weekdays, so Friday's opening skips the weekend to Monday's.
+        let boardroom = space("Boardroom", 14, Some("8:00"));
+        let friday = datetime!(2024-05-03 8:30);
+        let opening = next([&boardroom], friday).unwrap();
+        assert_eq!(opening.at, datetime!(2024-05-06 8:00));
+        assert_eq!(
+            opening.describe(friday),
+            "Bookings for Mon 20 May open Mon 6 May at 08:00"
+        );
+
+        // The soonest wins, and it counts down within the hour and says when it has passed.
+        let soon = datetime!(2024-05-01 7:55:30);
+        let opening = next([&boardroom, &anytime[0]], soon).unwrap();
+        assert_eq!(
+            (opening.at, opening.spaces.as_slice()),
+            (
+                datetime!(2024-05-01 8:00),
+                ["Boardroom".to_string()].as_slice()
+            )
+        );
+        assert_eq!(opening.describe(soon), "Bookings for Wed 15 May open in 5m");
+        assert_eq!(
+            opening.describe(datetime!(2024-05-01 8:00)),
+            "Bookings for Wed 15 May are open now"
+        );
+
+        // Spaces that can be booked any time ahead never open.
+        assert_eq!(next([&SpaceDetails::default()], now), None);
+    }
+}
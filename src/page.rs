@@ -0,0 +1,79 @@
+// Reading what syres needs out of Skedda's HTML pages. Kept free of the rest of the crate so
+// the fuzz targets in `fuzz/` can build it on its own.
+
+use scraper::{Html, Selector};
+
+/// Longest anti-forgery token accepted; real ones are a couple of hundred characters.
+const MAX_TOKEN_LEN: usize = 1024;
+
+/// The anti-forgery token from the booking page's form, if it has one that looks like a token.
+///
+/// The token is sent back as a request header, so anything that isn't a plain base64-style
+/// string is refused rather than passed on.
+pub fn csrf_token(html: &str) -> Option<String> {
+    let selector = Selector::parse("input[name='__RequestVerificationToken']").ok()?;
+    let document = Html::parse_document(html);
+    let token = document.select(&selector).next()?.value().attr("value")?;
+    is_token(token).then(|| token.to_string())
+}
+
+fn is_token(token: &str) -> bool {
+    (1..=MAX_TOKEN_LEN).contains(&token.len())
+        && token
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"-_+/=:.".contains(&byte))
+}
+
+/// The text of an HTML page's `<title>`, if the body is HTML and has one.
+pub fn title(body: &str) -> Option<String> {
+    if !body.trim_start().starts_with('<') {
+        return None;
+    }
+    let selector = Selector::parse("title").ok()?;
+    let title = Html::parse_document(body)
+        .select(&selector)
+        .next()?
+        .text()
+        .collect::<String>();
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(value: &str) -> String {
+        format!(
+            "<html><body><form><input name=\"__RequestVerificationToken\" type=\"hidden\" \
+             value=\"{value}\"></form></body></html>"
+        )
+    }
+
+    #[test]
+    fn reads_the_form_token() {
+        assert_eq!(
+            csrf_token(&form("CfDJ8Abc-123_xyz=")).as_deref(),
+            Some("CfDJ8Abc-123_xyz=")
+        );
+    }
+
+    #[test]
+    fn refuses_tokens_that_could_not_be_headers() {
+        assert_eq!(csrf_token(&form("")), None);
+        assert_eq!(csrf_token(&form("abc&#10;Set-Cookie: x=y")), None);
+        assert_eq!(csrf_token(&form("a b")), None);
+        assert_eq!(csrf_token(&form(&"a".repeat(MAX_TOKEN_LEN + 1))), None);
+        assert_eq!(csrf_token("<html>no form</html>"), None);
+    }
+
+    #[test]
+    fn titles_are_whitespace_collapsed() {
+        assert_eq!(
+            title("<html><head><title>\n  Down for\tmaintenance </title></head></html>").as_deref(),
+            Some("Down for maintenance")
+        );
+        assert_eq!(title("{\"spaces\": []}"), None);
+        assert_eq!(title("<html><title> </title></html>"), None);
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use directories::ProjectDirs;
+
+/// Environment variable that relocates every syres directory under one root.
+pub const HOME_ENV: &str = "SYRES_HOME";
+
+/// Directories syres reads from and writes to.
+///
+/// These follow the platform conventions (XDG on Linux, `~/Library` on macOS, `%APPDATA%` on
+/// Windows) unless [`HOME_ENV`] is set, in which case they are `config`, `data`, `cache` and
+/// `logs` under that directory.
+#[derive(Debug, Clone)]
+pub struct Paths {
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub log_dir: PathBuf,
+}
+
+impl Paths {
+    pub fn resolve() -> Option<Self> {
+        if let Some(root) = env::var_os(HOME_ENV).filter(|root| !root.is_empty()) {
+            return Some(Self::under(Path::new(&root)));
+        }
+
+        let dirs = ProjectDirs::from("", "", "syres")?;
+        Some(Self {
+            config_dir: dirs.config_dir().to_path_buf(),
+            data_dir: dirs.data_dir().to_path_buf(),
+            cache_dir: dirs.cache_dir().to_path_buf(),
+            log_dir: dirs
+                .state_dir()
+                .unwrap_or_else(|| dirs.data_local_dir())
+                .join("logs"),
+        })
+    }
+
+    /// The directories under one root, laid out as they are when [`HOME_ENV`] is set.
+    pub fn under(root: &Path) -> Self {
+        Self {
+            config_dir: root.join("config"),
+            data_dir: root.join("data"),
+            cache_dir: root.join("cache"),
+            log_dir: root.join("logs"),
+        }
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("config.toml")
+    }
+
+    pub fn log_file(&self) -> PathBuf {
+        self.log_dir.join("syres.log")
+    }
+
+    /// The daemon's log, kept apart from [`Self::log_file`] so the daemon and the TUI never
+    /// rotate the same file out from under each other.
+    pub fn daemon_log_file(&self) -> PathBuf {
+        self.log_dir.join("syres-daemon.log")
+    }
+
+    /// Directory holding one subdirectory of cached data per venue.
+    pub fn venue_cache_dir(&self) -> PathBuf {
+        self.cache_dir.join("venues")
+    }
+
+    pub fn venue_cache(&self, venue_key: &str) -> PathBuf {
+        self.venue_cache_dir().join(venue_key).join("webs.json")
+    }
+
+    /// Directory holding the last data fetched from each venue, to show while it can't be
+    /// reached.
+    pub fn offline_dir(&self) -> PathBuf {
+        self.cache_dir.join("offline")
+    }
+
+    pub fn offline_data(&self, venue_key: &str) -> PathBuf {
+        self.offline_dir().join(format!("{venue_key}.json"))
+    }
+
+    /// Directory holding one encrypted session file per venue.
+    pub fn sessions_dir(&self) -> PathBuf {
+        self.data_dir.join("sessions")
+    }
+
+    pub fn cookie_jar(&self, venue_key: &str) -> PathBuf {
+        self.sessions_dir().join(format!("{venue_key}.enc"))
+    }
+
+    /// Half-made bookings, saved as the booking form is edited.
+    pub fn drafts_file(&self) -> PathBuf {
+        self.data_dir.join("drafts.json")
+    }
+
+    /// Every booking made or cancelled, one JSON object per line.
+    pub fn audit_file(&self) -> PathBuf {
+        self.data_dir.join("audit.jsonl")
+    }
+
+    /// Directory booking receipts are exported to.
+    pub fn receipts_dir(&self) -> PathBuf {
+        self.data_dir.join("receipts")
+    }
+
+    /// Directory holding one history database per venue.
+    pub fn history_dir(&self) -> PathBuf {
+        self.data_dir.join("history")
+    }
+
+    pub fn history_db(&self, venue_key: &str) -> PathBuf {
+        self.history_dir().join(format!("{venue_key}.db"))
+    }
+}
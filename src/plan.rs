@@ -0,0 +1,161 @@
+use time::{Date, Duration, PrimitiveDateTime};
+
+use crate::skedda::MyBooking;
+
+/// How many days the planning board shows, starting today.
+pub const DAYS: usize = 7;
+
+/// Free time shorter than this isn't offered as a gap.
+const MIN_GAP: Duration = Duration::minutes(30);
+
+/// A row in one day of the planning board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanEntry {
+    /// One of my bookings, at the venue with the given index.
+    Booking(usize, MyBooking),
+    /// Time within the day's hours with nothing booked.
+    Gap {
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanDay {
+    pub date: Date,
+    /// Bookings and gaps, in time order.
+    pub entries: Vec<PlanEntry>,
+}
+
+/// Lays out the week from `now`: each day's bookings, with the gaps between them during
+/// `hours` (start and end hour, as in the grid config). Time already past today is no gap.
+pub fn week(
+    now: PrimitiveDateTime,
+    bookings: &[(usize, &MyBooking)],
+    hours: (u8, u8),
+) -> Vec<PlanDay> {
+    let now = now
+        .replace_second(0)
+        .unwrap_or(now)
+        .replace_nanosecond(0)
+        .unwrap_or(now);
+    (0..DAYS)
+        .filter_map(|offset| now.date().checked_add(Duration::days(offset as i64)))
+        .map(|date| {
+            let midnight = date.midnight();
+            let open = (midnight + Duration::hours(hours.0.into())).max(now);
+            let close = midnight + Duration::hours(hours.1.into());
+            let mut day: Vec<(usize, &MyBooking)> = bookings
+                .iter()
+                .filter(|(_, booking)| {
+                    booking.start < midnight + Duration::DAY && booking.end > midnight
+                })
+                .copied()
+                .collect();
+            day.sort_by_key(|(_, booking)| booking.start);
+
+            let mut entries = Vec::new();
+            let mut free_from = open;
+            let gap_until = |entries: &mut Vec<PlanEntry>, free_from, until: PrimitiveDateTime| {
+                let end = until.min(close);
+                if end - free_from >= MIN_GAP {
+                    entries.push(PlanEntry::Gap {
+                        start: free_from,
+                        end,
+                    });
+                }
+            };
+            for (venue, booking) in day {
+                gap_until(&mut entries, free_from, booking.start);
+                entries.push(PlanEntry::Booking(venue, booking.clone()));
+                free_from = free_from.max(booking.end);
+            }
+            gap_until(&mut entries, free_from, close);
+            PlanDay { date, entries }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    fn booking(start: PrimitiveDateTime, end: PrimitiveDateTime) -> MyBooking {
+        MyBooking {
+            id: None,
+            space: "Boardroom".to_string(),
+            location: Some("Decatur".to_string()),
+            start,
+            end,
+            title: None,
+        }
+    }
+
+    fn gap(start: PrimitiveDateTime, end: PrimitiveDateTime) -> PlanEntry {
+        PlanEntry::Gap { start, end }
+    }
+
+    #[test]
+    fn fills_the_day_around_my_bookings() {
+        let standup = booking(datetime!(2024-05-02 9:00), datetime!(2024-05-02 10:00));
+        let call = booking(datetime!(2024-05-02 10:15), datetime!(2024-05-02 11:00));
+        let plan = week(
+            datetime!(2024-05-01 12:00),
+            &[(0, &call), (1, &standup)],
+            (8, 18),
+        );
+
+        assert_eq!(plan.len(), DAYS);
+        assert_eq!(plan[0].date, date!(2024 - 05 - 01));
+        assert_eq!(
+            plan[0].entries,
+            [gap(
+                datetime!(2024-05-01 12:00),
+                datetime!(2024-05-01 18:00)
+            )]
+        );
+        assert_eq!(
+            plan[1].entries,
+            [
+                gap(datetime!(2024-05-02 8:00), datetime!(2024-05-02 9:00)),
+                PlanEntry::Booking(1, standup),
+                // 15 minutes between the two isn't worth offering.
+                PlanEntry::Booking(0, call),
+                gap(datetime!(2024-05-02 11:00), datetime!(2024-05-02 18:00)),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_and_out_of_hours_bookings_leave_no_gap() {
+        let long = booking(datetime!(2024-05-01 7:00), datetime!(2024-05-01 13:00));
+        let inside = booking(datetime!(2024-05-01 9:00), datetime!(2024-05-01 10:00));
+        let late = booking(datetime!(2024-05-01 17:45), datetime!(2024-05-01 20:00));
+        let plan = week(
+            datetime!(2024-05-01 6:00),
+            &[(0, &late), (0, &inside), (0, &long)],
+            (8, 18),
+        );
+        assert_eq!(
+            plan[0].entries,
+            [
+                PlanEntry::Booking(0, long),
+                PlanEntry::Booking(0, inside),
+                gap(datetime!(2024-05-01 13:00), datetime!(2024-05-01 17:45)),
+                PlanEntry::Booking(0, late),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_day_that_is_over_has_no_gaps() {
+        let plan = week(datetime!(2024-05-01 23:45), &[], (7, 24));
+        assert!(plan[0].entries.is_empty());
+        assert_eq!(
+            plan[1].entries,
+            [gap(datetime!(2024-05-02 7:00), datetime!(2024-05-03 0:00))]
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use std::{
+    collections::VecDeque,
+    sync::{Condvar, Mutex, MutexGuard, OnceLock, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use tracing::debug;
+
+use crate::config::LimitsConfig;
+
+/// Background requests allowed across every venue in any one minute, unless configured.
+pub const DEFAULT_BUDGET_PER_MINUTE: u32 = 10;
+
+static BUDGET_PER_MINUTE: OnceLock<u32> = OnceLock::new();
+
+/// When each background request in the last minute was made.
+static SPENT: Mutex<VecDeque<Instant>> = Mutex::new(VecDeque::new());
+
+/// Sets how many requests syres may make on its own in a minute, across every venue.
+pub fn init(budget_per_minute: u32) {
+    let _ = BUDGET_PER_MINUTE.set(budget_per_minute);
+}
+
+/// Claims one request from the politeness budget, or returns `false` when the last minute's
+/// budget is used up and the caller should try again later.
+///
+/// Only requests syres makes on its own count: session refreshes, connection warm-ups and
+/// retries. Requests the user asked for always go through.
+pub fn try_spend() -> bool {
+    let budget = *BUDGET_PER_MINUTE.get_or_init(|| DEFAULT_BUDGET_PER_MINUTE);
+    let now = Instant::now();
+    let mut spent = SPENT.lock().unwrap_or_else(PoisonError::into_inner);
+    while spent
+        .front()
+        .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+    {
+        spent.pop_front();
+    }
+    if spent.len() >= budget as usize {
+        return false;
+    }
+    spent.push_back(now);
+    true
+}
+
+/// `interval` moved randomly by up to a fifth either way, so repeated requests don't land on
+/// a fixed beat.
+pub fn jitter(interval: Duration) -> Duration {
+    interval.mul_f64(rand::thread_rng().gen_range(0.8..=1.2))
+}
+
+/// Waits that double after each attempt that changed nothing, up to a limit.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    attempts: u32,
+}
+
+impl Backoff {
+    pub const fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            attempts: 0,
+        }
+    }
+
+    /// The wait before the next attempt, jittered, counting this one.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self
+            .base
+            .saturating_mul(1 << self.attempts.min(16))
+            .min(self.max);
+        self.attempts = self.attempts.saturating_add(1);
+        jitter(delay)
+    }
+
+    /// Starts over from the base wait, e.g. after an attempt that worked.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+/// Holds one venue to its configured limits. Every client for the venue shares one, so the
+/// limits hold across the app's background threads.
+#[derive(Debug)]
+pub struct Limiter {
+    limits: LimitsConfig,
+    in_flight: Mutex<usize>,
+    slot_freed: Condvar,
+    /// When the last booking was sent.
+    booked_at: Mutex<Option<Instant>>,
+    /// When the venue's data was last fetched, on a timer or not.
+    fetched_at: Mutex<Option<Instant>>,
+}
+
+/// A claim on one of the venue's concurrent request slots, given back when dropped.
+pub struct Permit<'a> {
+    limiter: &'a Limiter,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *lock(&self.limiter.in_flight) -= 1;
+        self.limiter.slot_freed.notify_one();
+    }
+}
+
+impl Limiter {
+    pub fn new(limits: LimitsConfig) -> Self {
+        Self {
+            limits,
+            in_flight: Mutex::new(0),
+            slot_freed: Condvar::new(),
+            booked_at: Mutex::new(None),
+            fetched_at: Mutex::new(None),
+        }
+    }
+
+    /// Waits for a free request slot.
+    pub fn acquire(&self) -> Permit<'_> {
+        let max = self.limits.max_concurrent_requests.max(1);
+        let mut in_flight = self
+            .slot_freed
+            .wait_while(lock(&self.in_flight), |in_flight| *in_flight >= max)
+            .unwrap_or_else(PoisonError::into_inner);
+        *in_flight += 1;
+        Permit { limiter: self }
+    }
+
+    /// Waits until enough time has passed since the last booking to send another, and counts
+    /// this one.
+    pub fn wait_to_book(&self) {
+        let interval = Duration::from_secs(self.limits.min_booking_interval_secs);
+        let mut booked_at = lock(&self.booked_at);
+        if let Some(wait) = booked_at.and_then(|at| interval.checked_sub(at.elapsed())) {
+            debug!(?wait, "waiting before booking again");
+            thread::sleep(wait);
+        }
+        *booked_at = Some(Instant::now());
+    }
+
+    /// Notes that the venue's data was just fetched.
+    pub fn fetched(&self) {
+        *lock(&self.fetched_at) = Some(Instant::now());
+    }
+
+    /// Whether data reloaded on a timer may be fetched again yet.
+    pub fn poll_due(&self) -> bool {
+        let floor = Duration::from_secs(self.limits.min_poll_interval_secs);
+        lock(&self.fetched_at).is_none_or(|at| at.elapsed() >= floor)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    fn limiter(max_concurrent_requests: usize, min_booking_interval_secs: u64) -> Limiter {
+        Limiter::new(LimitsConfig {
+            max_concurrent_requests,
+            min_booking_interval_secs,
+            min_poll_interval_secs: 60,
+        })
+    }
+
+    #[test]
+    fn caps_requests_in_flight() {
+        let limiter = Arc::new(limiter(2, 0));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let threads: Vec<_> = (0..6)
+            .map(|_| {
+                let (limiter, in_flight, most) = (limiter.clone(), in_flight.clone(), most.clone());
+                thread::spawn(move || {
+                    let _permit = limiter.acquire();
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(most.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn spaces_out_bookings_and_polls() {
+        let limiter = limiter(4, 1);
+        let started = Instant::now();
+        limiter.wait_to_book();
+        limiter.wait_to_book();
+        assert!(started.elapsed() >= Duration::from_secs(1));
+
+        assert!(limiter.poll_due());
+        limiter.fetched();
+        assert!(!limiter.poll_due());
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str};
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+use crate::grid;
+
+/// Proof of a reservation, for expense reports and for venues that ask for one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub venue: String,
+    pub location: String,
+    pub space: String,
+    pub start: PrimitiveDateTime,
+    pub end: PrimitiveDateTime,
+    /// The member an admin booked for.
+    pub booked_for: Option<String>,
+    pub booking_id: Option<String>,
+    /// What the booking cost, as the venue shows it, e.g. "$25.00".
+    pub price: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Pdf,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+impl Receipt {
+    /// The receipt's fields as label and value pairs, in the order they're printed.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("Venue", self.venue.clone()),
+            ("Location", self.location.clone()),
+            ("Space", self.space.clone()),
+            (
+                "Date",
+                format!("{} {}", self.start.date().weekday(), self.start.date()),
+            ),
+            (
+                "Time",
+                format!(
+                    "{}–{}",
+                    grid::format_time(self.start.time()),
+                    grid::format_time(self.end.time())
+                ),
+            ),
+        ];
+        let optional = [
+            ("Booked for", &self.booked_for),
+            ("Booking ID", &self.booking_id),
+            ("Price", &self.price),
+        ];
+        fields.extend(
+            optional
+                .into_iter()
+                .filter_map(|(label, value)| Some((label, value.clone()?))),
+        );
+        fields
+    }
+
+    pub fn to_markdown(&self, issued: OffsetDateTime) -> String {
+        let mut markdown = String::from("# Booking receipt\n\n");
+        for (label, value) in self.fields() {
+            markdown.push_str(&format!("- **{label}:** {}\n", escape_markdown(&value)));
+        }
+        markdown.push_str(&format!("\nIssued {}\n", issued.date()));
+        markdown
+    }
+
+    /// A one-page A4 PDF in Helvetica, which every reader has, so no font is embedded.
+    pub fn to_pdf(&self, issued: OffsetDateTime) -> Vec<u8> {
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let page_id = Ref::new(3);
+        let font_id = Ref::new(4);
+        let content_id = Ref::new(5);
+        let font = Name(b"F1");
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id).kids([page_id]).count(1);
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, 595.0, 842.0))
+            .parent(page_tree_id)
+            .contents(content_id);
+        page.resources().fonts().pair(font, font_id);
+        drop(page);
+        pdf.type1_font(font_id)
+            .base_font(Name(b"Helvetica"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+        let mut content = Content::new();
+        content.begin_text();
+        content.set_font(font, 20.0);
+        content.next_line(72.0, 760.0);
+        content.show(Str(b"Booking receipt"));
+        content.set_font(font, 12.0);
+        content.set_leading(18.0);
+        content.next_line(0.0, -36.0);
+        for (label, value) in self.fields() {
+            content.show(Str(&win_ansi(&format!("{label}: {value}"))));
+            content.next_line_using_leading();
+        }
+        content.next_line_using_leading();
+        content.show(Str(&win_ansi(&format!("Issued {}", issued.date()))));
+        content.end_text();
+        pdf.stream(content_id, &content.finish());
+        pdf.finish()
+    }
+
+    /// Writes the receipt into `dir`, named after the booking's date and space, and returns
+    /// where it went.
+    pub fn export(&self, format: Format, dir: &Path) -> Result<PathBuf> {
+        let issued = OffsetDateTime::now_utc();
+        let contents = match format {
+            Format::Markdown => self.to_markdown(issued).into_bytes(),
+            Format::Pdf => self.to_pdf(issued),
+        };
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        let path = dir.join(format!(
+            "{}-{:02}{:02}-{}.{}",
+            self.start.date(),
+            self.start.hour(),
+            self.start.minute(),
+            slug(&self.space),
+            format.extension()
+        ));
+        fs::write(&path, contents).with_context(|| format!("writing {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// A space name made safe for a file name, e.g. "Room 2 (Big)" becomes "room-2-big".
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "space".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Encodes text for a standard font's WinAnsi encoding; characters it lacks become `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|ch| match ch {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => ch as u8,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '€' => 0x80,
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn receipt() -> Receipt {
+        Receipt {
+            venue: "Switchyards".to_string(),
+            location: "Decatur".to_string(),
+            space: "Room 2 (Big)".to_string(),
+            start: datetime!(2024-05-01 9:00),
+            end: datetime!(2024-05-01 10:30),
+            booked_for: None,
+            booking_id: Some("98765".to_string()),
+            price: None,
+        }
+    }
+
+    #[test]
+    fn markdown_lists_what_is_known() {
+        let markdown = receipt().to_markdown(datetime!(2024-05-02 8:00 UTC));
+        assert_eq!(
+            markdown,
+            "# Booking receipt\n\n\
+             - **Venue:** Switchyards\n\
+             - **Location:** Decatur\n\
+             - **Space:** Room 2 (Big)\n\
+             - **Date:** Wednesday 2024-05-01\n\
+             - **Time:** 09:00–10:30\n\
+             - **Booking ID:** 98765\n\
+             \nIssued 2024-05-02\n"
+        );
+    }
+
+    #[test]
+    fn pdf_is_one_page_with_the_text() {
+        let pdf = receipt().to_pdf(datetime!(2024-05-02 8:00 UTC));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-"));
+        assert!(text.contains("/Count 1"));
+        assert!(text.contains("Space: Room 2"));
+        assert!(text.contains("(Date: Wednesday 2024-05-01)"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+    }
+
+    #[test]
+    fn exports_under_a_readable_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = receipt().export(Format::Markdown, dir.path()).unwrap();
+        assert_eq!(path.file_name().unwrap(), "2024-05-01-0900-room-2-big.md");
+        assert!(fs::read_to_string(&path).unwrap().contains("Decatur"));
+    }
+
+    #[test]
+    fn escapes_and_encodes_odd_names() {
+        assert_eq!(escape_markdown("*Desk* [1]"), "\\*Desk\\* \\[1\\]");
+        assert_eq!(slug("  Café / Booth #3 "), "caf-booth-3");
+        assert_eq!(slug("???"), "space");
+        assert_eq!(win_ansi("09:00–10:30 café ✓"), b"09:00\x9610:30 caf\xe9 ?");
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::WrapErr;
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use regex::Regex;
+
+use crate::{logging::RotatingFile, paths::Paths};
+
+/// Number of recent HTTP request log lines included in the report.
+const REQUEST_SUMMARIES: usize = 20;
+
+/// Key names whose values are never written to a report. A webhook URL lets whoever has it
+/// post as syres, and an ntfy topic URL lets them post or read its notifications.
+const SECRET_KEYS: [&str; 9] = [
+    "token",
+    "password",
+    "secret",
+    "cookie",
+    "authorization",
+    "dsn",
+    "passphrase",
+    "webhook",
+    "ntfy",
+];
+
+static SECRET_PAIR: LazyLock<Regex> = LazyLock::new(|| {
+    let keys = SECRET_KEYS.join("|");
+    Regex::new(&format!(
+        r#"(?i)((?:{keys})[\w-]*"?\s*[=:]\s*)("[^"]*"|\S+)"#
+    ))
+    .expect("secret pattern is valid")
+});
+
+/// Bundles sanitized logs, config, version and terminal details into a `.tar.gz`.
+pub fn run(output: Option<PathBuf>, log_path: &Path) -> color_eyre::Result<()> {
+    let output = output.unwrap_or_else(|| {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        PathBuf::from(format!("syres-report-{timestamp}.tar.gz"))
+    });
+    let file = fs::File::create(&output)
+        .wrap_err_with(|| format!("failed to create {}", output.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let logs = read_logs(log_path);
+    append(&mut archive, "system.txt", &system_info())?;
+    append(&mut archive, "config.toml", &redacted_config())?;
+    append(&mut archive, "requests.log", &request_summaries(&logs))?;
+    append(&mut archive, "syres.log", &logs)?;
+    if let Some(daemon_log) = Paths::resolve().map(|paths| paths.daemon_log_file()) {
+        append(&mut archive, "syres-daemon.log", &read_logs(&daemon_log))?;
+    }
+    archive
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .wrap_err("failed to write report archive")?;
+
+    println!("Wrote {}", output.display());
+    println!("Please check it for anything you'd rather not share before attaching it.");
+    Ok(())
+}
+
+fn append<W: io::Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    contents: &str,
+) -> color_eyre::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default(),
+    );
+    header.set_cksum();
+    archive
+        .append_data(&mut header, name, contents.as_bytes())
+        .wrap_err_with(|| format!("failed to add {name} to report"))
+}
+
+fn system_info() -> String {
+    let mut info = vec![
+        format!("syres {}", env!("CARGO_PKG_VERSION")),
+        format!("os: {} ({})", env::consts::OS, env::consts::ARCH),
+    ];
+    for var in ["TERM", "COLORTERM", "TERM_PROGRAM", "TERM_PROGRAM_VERSION"] {
+        info.push(format!(
+            "{var}: {}",
+            env::var(var).unwrap_or_else(|_| "unset".to_string())
+        ));
+    }
+    match crossterm::terminal::size() {
+        Ok((columns, rows)) => info.push(format!("terminal size: {columns}x{rows}")),
+        Err(err) => info.push(format!("terminal size: unknown ({err})")),
+    }
+    info.join("\n") + "\n"
+}
+
+fn redacted_config() -> String {
+    let Some(path) = Paths::resolve()
+        .map(|paths| paths.config_file())
+        .filter(|path| path.exists())
+    else {
+        return "# no config file\n".to_string();
+    };
+    match fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| toml::from_str::<toml::Table>(&contents).map_err(|e| e.to_string()))
+    {
+        Ok(mut table) => {
+            redact_table(&mut table);
+            toml::to_string_pretty(&table).unwrap_or_default()
+        }
+        Err(err) => format!("# failed to read {}: {err}\n", path.display()),
+    }
+}
+
+fn redact_table(table: &mut toml::Table) {
+    for (key, value) in table.iter_mut() {
+        let key = key.to_lowercase();
+        if SECRET_KEYS.iter().any(|secret| key.contains(secret)) {
+            *value = toml::Value::String("[REDACTED]".to_string());
+        } else {
+            redact_value(value);
+        }
+    }
+}
+
+/// Redacts the tables within a value, including those in arrays such as `[[venues]]`.
+fn redact_value(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(inner) => redact_table(inner),
+        toml::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Reads the active and rotated log files, oldest first, with secrets masked.
+pub(crate) fn read_logs(log_path: &Path) -> String {
+    let mut files = RotatingFile::rotated_files(log_path);
+    files.reverse();
+    files.push(log_path.to_path_buf());
+
+    let mut logs = String::new();
+    for path in files {
+        let Ok(file) = fs::File::open(&path) else {
+            continue;
+        };
+        let reader: Box<dyn io::Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(GzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            logs.push_str(&sanitize(&line));
+            logs.push('\n');
+        }
+    }
+    logs
+}
+
+/// The most recent log lines from the HTTP layer.
+fn request_summaries(logs: &str) -> String {
+    let lines: Vec<&str> = logs
+        .lines()
+        .filter(|line| line.contains("syres::skedda"))
+        .collect();
+    if lines.is_empty() {
+        return "# no requests logged; run with `-vv` or `RUST_LOG=syres::skedda=debug`\n"
+            .to_string();
+    }
+    lines[lines.len().saturating_sub(REQUEST_SUMMARIES)..].join("\n") + "\n"
+}
+
+/// Masks values of secret-looking `key=value` and `key: value` pairs.
+pub fn sanitize(line: &str) -> String {
+    SECRET_PAIR.replace_all(line, "${1}[REDACTED]").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_secrets_are_redacted() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            [digest]
+            time = "7:30"
+            webhook_url = "https://hooks.example.com/T000/B000/XXXX"
+
+            [notify]
+            webhook_url = "https://hooks.example.com/T111/B111/YYYY"
+            ntfy_url = "https://ntfy.sh/my-desks-8f3k2"
+
+            [notify.routes]
+            booked = ["ntfy"]
+
+            [[venues]]
+            name = "Switchyards"
+            password = "hunter2"
+            "#,
+        )
+        .unwrap();
+        redact_table(&mut table);
+        let redacted = toml::to_string(&table).unwrap();
+        assert!(!redacted.contains("hooks.example.com"), "{redacted}");
+        assert!(!redacted.contains("hunter2"), "{redacted}");
+        assert!(!redacted.contains("my-desks"), "{redacted}");
+        assert_eq!(table["digest"]["time"].as_str(), Some("7:30"));
+        // Routes name channels rather than holding their addresses.
+        assert_eq!(
+            table["notify"]["routes"]["booked"][0].as_str(),
+            Some("ntfy")
+        );
+    }
+}
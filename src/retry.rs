@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+
+use crate::{config::RetryConfig, polling::Backoff};
+
+/// How one try of a request went, as far as trying it again goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attempt {
+    /// The server answered, asking for a wait with `Retry-After` or not.
+    Answered(StatusCode, Option<Duration>),
+    /// No connection could be made, so the request never reached the server.
+    Unreachable,
+    /// The server took too long to answer, and may have acted on the request.
+    TimedOut,
+}
+
+/// Tries a request again while it fails in a way that may pass: Skedda turning it away for
+/// coming too often, a gateway between us failing or timing out, or the venue not answering.
+/// Requests that may have changed something, such as a booking, are only sent again when
+/// they're known not to have.
+#[derive(Debug, Clone)]
+pub struct Retry {
+    config: RetryConfig,
+    backoff: Backoff,
+    attempts: u32,
+}
+
+impl Retry {
+    pub fn new(config: RetryConfig) -> Self {
+        Self {
+            config,
+            backoff: Backoff::new(
+                Duration::from_millis(config.base_delay_ms),
+                Duration::from_secs(config.max_delay_secs),
+            ),
+            attempts: 0,
+        }
+    }
+
+    /// How long to wait before sending `method` again after `attempt`, or `None` when it
+    /// shouldn't be: it worked or failed for good, the tries are used up, or the server asked
+    /// for a longer wait than a retry may take.
+    pub fn wait(&mut self, method: &Method, attempt: Attempt) -> Option<Duration> {
+        self.attempts += 1;
+        let asked = match attempt {
+            // Turned away unread, so safe to send again whatever it does.
+            Attempt::Answered(StatusCode::TOO_MANY_REQUESTS, after) => after,
+            Attempt::Answered(
+                StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT,
+                after,
+            ) if method.is_idempotent() => after,
+            Attempt::Unreachable => None,
+            Attempt::TimedOut if method.is_idempotent() => None,
+            Attempt::Answered(..) | Attempt::TimedOut => return None,
+        };
+        if self.attempts >= self.config.max_attempts
+            || asked.is_some_and(|after| after > Duration::from_secs(self.config.max_delay_secs))
+        {
+            return None;
+        }
+        Some(self.backoff.next_delay().max(asked.unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_what_may_pass_within_the_limits() {
+        let config = RetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 100,
+            max_delay_secs: 10,
+        };
+        let unavailable = Attempt::Answered(StatusCode::SERVICE_UNAVAILABLE, None);
+
+        let mut retry = Retry::new(config);
+        let first = retry.wait(&Method::GET, unavailable).unwrap();
+        assert!((Duration::from_millis(80)..=Duration::from_millis(120)).contains(&first));
+        let second = retry.wait(&Method::GET, Attempt::Unreachable).unwrap();
+        assert!((Duration::from_millis(160)..=Duration::from_millis(240)).contains(&second));
+        // The third try was the last.
+        assert_eq!(retry.wait(&Method::GET, unavailable), None);
+
+        // Retry-After is waited out when it's within the longest wait, and not otherwise.
+        let limited = |after| Attempt::Answered(StatusCode::TOO_MANY_REQUESTS, Some(after));
+        assert_eq!(
+            Retry::new(config).wait(&Method::POST, limited(Duration::from_secs(4))),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(
+            Retry::new(config).wait(&Method::POST, limited(Duration::from_secs(120))),
+            None
+        );
+
+        // A booking that may have gone through isn't sent twice.
+        assert_eq!(Retry::new(config).wait(&Method::POST, unavailable), None);
+        assert_eq!(
+            Retry::new(config).wait(&Method::POST, Attempt::TimedOut),
+            None
+        );
+        assert!(
+            Retry::new(config)
+                .wait(&Method::POST, Attempt::Unreachable)
+                .is_some()
+        );
+        assert!(
+            Retry::new(config)
+                .wait(&Method::DELETE, Attempt::TimedOut)
+                .is_some()
+        );
+
+        for status in [StatusCode::OK, StatusCode::NOT_FOUND, StatusCode::FORBIDDEN] {
+            assert_eq!(
+                Retry::new(config).wait(&Method::GET, Attempt::Answered(status, None)),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use time::{Duration, PrimitiveDateTime};
+
+/// Limits a venue sets on how a space can be booked, checked before booking so a form
+/// that breaks one says so instead of being turned down by Skedda.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BookingRules {
+    /// The longest a single booking can be.
+    pub max_length: Option<Duration>,
+    /// How far ahead of its start a booking must be made.
+    pub min_notice: Option<Duration>,
+    /// How far ahead a booking can be made.
+    pub max_advance: Option<Duration>,
+}
+
+impl BookingRules {
+    /// Reads the rules from the space's settings, which are in minutes; zero means no limit.
+    pub fn from_minutes(
+        max_length: Option<u32>,
+        min_notice: Option<u32>,
+        max_advance: Option<u32>,
+    ) -> Self {
+        let minutes = |value: Option<u32>| {
+            value
+                .filter(|&minutes| minutes > 0)
+                .map(|minutes| Duration::minutes(minutes.into()))
+        };
+        Self {
+            max_length: minutes(max_length),
+            min_notice: minutes(min_notice),
+            max_advance: minutes(max_advance),
+        }
+    }
+
+    /// Why booking `space` from `start` to `end` at `now` isn't allowed, if it isn't.
+    pub fn check(
+        &self,
+        space: &str,
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+        now: PrimitiveDateTime,
+    ) -> Result<(), String> {
+        if let Some(max) = self.max_length.filter(|&max| end - start > max) {
+            return Err(format!(
+                "{space} can be booked for at most {} at a time",
+                format_length(max)
+            ));
+        }
+        if let Some(notice) = self.min_notice.filter(|&notice| start - now < notice) {
+            return Err(format!(
+                "{space} must be booked at least {} ahead",
+                format_length(notice)
+            ));
+        }
+        if let Some(advance) = self.max_advance.filter(|&advance| start - now > advance) {
+            return Err(format!(
+                "{space} can be booked at most {} ahead",
+                format_length(advance)
+            ));
+        }
+        Ok(())
+    }
+
+    /// The rules in a few words for the space's details, e.g. "Up to 2h · 1h notice".
+    pub fn summary(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.max_length
+                .map(|max| format!("Up to {}", format_length(max))),
+            self.min_notice
+                .map(|notice| format!("{} notice", format_length(notice))),
+            self.max_advance
+                .map(|advance| format!("{} ahead at most", format_length(advance))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+}
+
+/// A length of time as the venue would put it: "45m", "2h", "2h 30m" or "14 days".
+pub fn format_length(length: Duration) -> String {
+    let minutes = length.whole_minutes();
+    match (minutes / 60, minutes % 60) {
+        (hours, 0) if hours >= 24 && hours % 24 == 0 => match hours / 24 {
+            1 => "1 day".to_string(),
+            days => format!("{days} days"),
+        },
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    const NOW: PrimitiveDateTime = datetime!(2024-05-01 9:10);
+
+    fn rules() -> BookingRules {
+        BookingRules::from_minutes(Some(120), Some(60), Some(14 * 24 * 60))
+    }
+
+    #[test]
+    fn allows_what_fits() {
+        let start = datetime!(2024-05-01 10:30);
+        assert_eq!(
+            rules().check("Boardroom", start, start + Duration::hours(2), NOW),
+            Ok(())
+        );
+        assert_eq!(
+            BookingRules::default().check("Desk", NOW, NOW + Duration::DAY, NOW),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn explains_each_broken_rule() {
+        let check = |start, end| rules().check("Boardroom", start, end, NOW).unwrap_err();
+        assert_eq!(
+            check(datetime!(2024-05-01 11:00), datetime!(2024-05-01 13:30)),
+            "Boardroom can be booked for at most 2h at a time"
+        );
+        assert_eq!(
+            check(datetime!(2024-05-01 10:00), datetime!(2024-05-01 11:00)),
+            "Boardroom must be booked at least 1h ahead"
+        );
+        assert_eq!(
+            check(datetime!(2024-05-16 9:00), datetime!(2024-05-16 10:00)),
+            "Boardroom can be booked at most 14 days ahead"
+        );
+    }
+
+    #[test]
+    fn summarizes_and_ignores_zero() {
+        assert_eq!(
+            rules().summary().as_deref(),
+            Some("Up to 2h · 1h notice · 14 days ahead at most")
+        );
+        assert_eq!(
+            BookingRules::from_minutes(Some(90), Some(0), None)
+                .summary()
+                .as_deref(),
+            Some("Up to 1h 30m")
+        );
+        assert_eq!(
+            BookingRules::from_minutes(Some(0), None, None),
+            BookingRules::default()
+        );
+    }
+}
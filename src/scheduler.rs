@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use crate::event::TICK_FPS;
+
+/// Braille frames for a busy indicator, advanced ten times a second.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Counts ticks so periodic work can run at its own pace off the one tick event: call
+/// [`Scheduler::advance`] on every tick, then ask [`Scheduler::every`] whether a job is due.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Scheduler {
+    ticks: u64,
+}
+
+impl Scheduler {
+    pub fn advance(&mut self) {
+        self.ticks += 1;
+    }
+
+    /// Whether a job that runs once per `period` is due on this tick. Periods shorter than a
+    /// tick run on every tick.
+    pub fn every(&self, period: Duration) -> bool {
+        self.ticks.is_multiple_of(ticks_in(period))
+    }
+
+    /// The spinner frame to draw on this tick.
+    pub fn spinner(&self) -> char {
+        SPINNER[(self.ticks / ticks_in(Duration::from_millis(100))) as usize % SPINNER.len()]
+    }
+}
+
+/// How many ticks make up `period`, at least one.
+pub fn ticks_in(period: Duration) -> u64 {
+    ((period.as_secs_f64() * TICK_FPS).round() as u64).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_run_once_per_period() {
+        let mut scheduler = Scheduler::default();
+        let mut runs = 0;
+        for _ in 0..10 * TICK_FPS as usize {
+            scheduler.advance();
+            if scheduler.every(Duration::from_secs(2)) {
+                runs += 1;
+            }
+        }
+        assert_eq!(runs, 5);
+    }
+
+    #[test]
+    fn short_periods_run_every_tick() {
+        let mut scheduler = Scheduler::default();
+        for _ in 0..5 {
+            scheduler.advance();
+            assert!(scheduler.every(Duration::ZERO));
+        }
+    }
+
+    #[test]
+    fn spinner_turns_ten_times_a_second() {
+        let mut scheduler = Scheduler::default();
+        let mut frames = vec![scheduler.spinner()];
+        for _ in 0..TICK_FPS as usize {
+            scheduler.advance();
+            if frames.last() != Some(&scheduler.spinner()) {
+                frames.push(scheduler.spinner());
+            }
+        }
+        assert_eq!(frames.len(), 11);
+        assert_eq!(frames[0], frames[10]);
+    }
+}
@@ -0,0 +1,471 @@
+use std::{
+    env, fmt, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use argon2::Argon2;
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce, aead::Aead};
+use cookie_store::{CookieExpiration, CookieStore, RawCookie};
+use rand::RngCore;
+use reqwest::{Url, header::HeaderValue};
+use tracing::{debug, warn};
+
+/// Environment variable holding a passphrase to derive the session key from instead of using
+/// the OS keychain.
+pub const PASSPHRASE_ENV: &str = "SYRES_PASSPHRASE";
+
+const MAGIC: &[u8; 6] = b"SYRES1";
+const KEYCHAIN_SERVICE: &str = "syres";
+const KEYCHAIN_USER: &str = "session-key";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// Held while the keychain key is read or created, so two first saves in this process can't
+/// each store a different key and leave the other's session undecryptable.
+static KEYCHAIN_KEY: Mutex<()> = Mutex::new(());
+
+/// Assumed lifetime of a session whose cookies don't carry an expiry.
+pub const SESSION_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Sessions this close to expiring are refreshed in the background.
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(30 * 60);
+
+/// Cookie jar shared with the HTTP client.
+#[derive(Debug, Default)]
+pub struct SessionCookies {
+    store: Mutex<CookieStore>,
+    issued_at: Mutex<Option<SystemTime>>,
+}
+
+impl SessionCookies {
+    pub fn new(store: CookieStore, issued_at: Option<SystemTime>) -> Self {
+        Self {
+            store: Mutex::new(store),
+            issued_at: Mutex::new(issued_at),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, CookieStore> {
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// When the server last handed out cookies.
+    pub fn issued_at(&self) -> Option<SystemTime> {
+        *self
+            .issued_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// When the session for `url` expires: the earliest cookie expiry, or [`SESSION_LIFETIME`]
+    /// after issue when the cookies only last for the browser session.
+    pub fn expires_at(&self, url: &Url) -> Option<SystemTime> {
+        let store = self.lock();
+        let cookies = store.matches(url);
+        if cookies.is_empty() {
+            return None;
+        }
+        cookies
+            .iter()
+            .filter_map(|cookie| match cookie.expires {
+                CookieExpiration::AtUtc(at) => Some(SystemTime::from(at)),
+                CookieExpiration::SessionEnd => None,
+            })
+            .min()
+            .or_else(|| {
+                self.issued_at()
+                    .map(|issued_at| issued_at + SESSION_LIFETIME)
+            })
+    }
+}
+
+/// How close a session is to expiring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionHealth {
+    Valid,
+    Expiring,
+    Expired,
+}
+
+impl SessionHealth {
+    pub fn of(expires_at: SystemTime) -> Self {
+        match expires_at.duration_since(SystemTime::now()) {
+            Ok(remaining) if remaining > REFRESH_MARGIN => SessionHealth::Valid,
+            Ok(_) => SessionHealth::Expiring,
+            Err(_) => SessionHealth::Expired,
+        }
+    }
+}
+
+impl reqwest::cookie::CookieStore for SessionCookies {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies: Vec<_> = cookie_headers
+            .filter_map(|header| {
+                let header = header.to_str().ok()?;
+                RawCookie::parse(header.to_string()).ok()
+            })
+            .collect();
+        if cookies.is_empty() {
+            return;
+        }
+        self.lock().store_response_cookies(cookies.into_iter(), url);
+        *self
+            .issued_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(SystemTime::now());
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self
+            .lock()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if header.is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(&header).ok()
+        }
+    }
+}
+
+/// Where the key encrypting the session file comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySource {
+    /// A random key kept in the OS keychain.
+    Keychain,
+    /// A key derived from [`PASSPHRASE_ENV`].
+    Passphrase,
+}
+
+impl KeySource {
+    pub fn current() -> Self {
+        if env::var_os(PASSPHRASE_ENV).is_some_and(|passphrase| !passphrase.is_empty()) {
+            KeySource::Passphrase
+        } else {
+            KeySource::Keychain
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            KeySource::Keychain => 0,
+            KeySource::Passphrase => 1,
+        }
+    }
+}
+
+/// Persists the cookie jar encrypted with ChaCha20-Poly1305.
+///
+/// The file is `MAGIC | key source | salt | nonce | ciphertext`; the salt is only used when
+/// the key is derived from a passphrase.
+#[derive(Clone)]
+pub struct SessionStore {
+    path: PathBuf,
+    source: KeySource,
+    /// What the key is derived from when it doesn't come from the keychain.
+    passphrase: Option<String>,
+}
+
+impl fmt::Debug for SessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SessionStore")
+            .field("path", &self.path)
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionStore {
+    /// A store whose key comes from [`PASSPHRASE_ENV`] when it's set, or else the keychain.
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_passphrase(
+            path,
+            env::var(PASSPHRASE_ENV)
+                .ok()
+                .filter(|passphrase| !passphrase.is_empty()),
+        )
+    }
+
+    /// A store whose key is derived from `passphrase`, or kept in the keychain without one.
+    pub fn with_passphrase(path: PathBuf, passphrase: Option<String>) -> Self {
+        Self {
+            path,
+            source: if passphrase.is_some() {
+                KeySource::Passphrase
+            } else {
+                KeySource::Keychain
+            },
+            passphrase,
+        }
+    }
+
+    /// Loads the saved cookies, starting a fresh session when there are none or they can't be
+    /// decrypted.
+    pub fn load(&self) -> SessionCookies {
+        match self.try_load() {
+            Ok(Some(store)) => {
+                let saved_at = fs::metadata(&self.path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                SessionCookies::new(store, saved_at)
+            }
+            Ok(None) => SessionCookies::default(),
+            Err(err) => {
+                warn!("discarding saved session: {err:#}");
+                SessionCookies::default()
+            }
+        }
+    }
+
+    fn try_load(&self) -> Result<Option<CookieStore>> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", self.path.display()));
+            }
+        };
+
+        let header_len = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+        if contents.len() < header_len || !contents.starts_with(MAGIC) {
+            bail!("{} is not a syres session file", self.path.display());
+        }
+        let source = contents[MAGIC.len()];
+        if source != self.source.tag() {
+            bail!("session was encrypted with a different key source");
+        }
+        let salt = &contents[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LEN];
+        let nonce = &contents[MAGIC.len() + 1 + SALT_LEN..header_len];
+
+        let cipher = ChaCha20Poly1305::new(&self.key(salt)?);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), &contents[header_len..])
+            .map_err(|_| anyhow!("failed to decrypt session, the key may have changed"))?;
+        let store = CookieStore::load_json_all(plaintext.as_slice())
+            .map_err(|err| anyhow!("failed to parse session cookies: {err}"))?;
+        debug!(path = %self.path.display(), "loaded session");
+        Ok(Some(store))
+    }
+
+    /// Encrypts and writes the cookies, readable only by the current user.
+    pub fn save(&self, store: &CookieStore) -> Result<()> {
+        let mut plaintext = Vec::new();
+        store
+            .save_incl_expired_and_nonpersistent_json(&mut plaintext)
+            .map_err(|err| anyhow!("failed to serialize session cookies: {err}"))?;
+
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let cipher = ChaCha20Poly1305::new(&self.key(&salt)?);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| anyhow!("failed to encrypt session"))?;
+
+        let mut contents = Vec::with_capacity(MAGIC.len() + 1 + SALT_LEN + NONCE_LEN);
+        contents.extend_from_slice(MAGIC);
+        contents.push(self.source.tag());
+        contents.extend_from_slice(&salt);
+        contents.extend_from_slice(&nonce);
+        contents.extend_from_slice(&ciphertext);
+        write_private(&self.path, &contents)?;
+        debug!(path = %self.path.display(), "saved session");
+        Ok(())
+    }
+
+    fn key(&self, salt: &[u8]) -> Result<Key> {
+        let mut key = [0u8; KEY_LEN];
+        match &self.passphrase {
+            Some(passphrase) => {
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|err| anyhow!("failed to derive session key: {err}"))?;
+            }
+            None => {
+                let _guard = KEYCHAIN_KEY
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER)
+                    .context("failed to open the OS keychain")?;
+                let secret = match entry.get_secret() {
+                    Ok(secret) if secret.len() == KEY_LEN => secret,
+                    Ok(_) | Err(keyring::Error::NoEntry) => {
+                        let mut fresh = [0u8; KEY_LEN];
+                        rand::thread_rng().fill_bytes(&mut fresh);
+                        entry
+                            .set_secret(&fresh)
+                            .context("failed to store the session key in the OS keychain")?;
+                        // Another process may have stored a key of its own in the meantime;
+                        // whichever the keychain kept is the one both must use.
+                        entry
+                            .get_secret()
+                            .context("failed to read the session key back from the keychain")?
+                    }
+                    Err(err) => {
+                        return Err(err)
+                            .context("failed to read the session key from the keychain");
+                    }
+                };
+                if secret.len() != KEY_LEN {
+                    bail!("the session key in the keychain is the wrong length");
+                }
+                key.copy_from_slice(&secret);
+            }
+        }
+        Ok(Key::from(key))
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(&tmp)
+        .and_then(|mut file| file.write_all(contents))
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{any::Any, sync::Arc, thread};
+
+    use keyring::credential::{Credential, CredentialApi, CredentialBuilderApi};
+
+    use super::*;
+
+    /// A session with one cookie saved under `passphrase`, in a directory of its own.
+    fn saved(passphrase: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("example.enc");
+        let url = Url::parse("https://example.skedda.com/").unwrap();
+        let mut store = CookieStore::default();
+        store.parse("session=abc123; Path=/", &url).unwrap();
+        SessionStore::with_passphrase(path.clone(), Some(passphrase.to_string()))
+            .save(&store)
+            .unwrap();
+        (dir, path)
+    }
+
+    fn session(store: &SessionStore) -> Option<String> {
+        store
+            .try_load()
+            .unwrap()?
+            .get("example.skedda.com", "/", "session")
+            .map(|cookie| cookie.value().to_string())
+    }
+
+    #[test]
+    fn saved_session_loads_back() {
+        let (_dir, path) = saved("correct horse");
+        let store = SessionStore::with_passphrase(path, Some("correct horse".to_string()));
+        assert_eq!(session(&store).as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn wrong_passphrase_starts_a_fresh_session() {
+        let (_dir, path) = saved("correct horse");
+        let store = SessionStore::with_passphrase(path, Some("battery staple".to_string()));
+        let err = store.try_load().unwrap_err();
+        assert!(err.to_string().contains("failed to decrypt"), "{err:#}");
+        assert_eq!(store.load().lock().iter_any().count(), 0);
+    }
+
+    #[test]
+    fn tampered_session_is_refused() {
+        let (_dir, path) = saved("correct horse");
+        let mut contents = fs::read(&path).unwrap();
+        *contents.last_mut().unwrap() ^= 1;
+        fs::write(&path, contents).unwrap();
+        let store = SessionStore::with_passphrase(path, Some("correct horse".to_string()));
+        assert!(store.try_load().is_err());
+        assert_eq!(store.load().lock().iter_any().count(), 0);
+    }
+
+    /// A keychain shared by every entry, slow to find nothing so that racing first saves overlap.
+    #[derive(Debug, Default)]
+    struct SharedKeychain(Arc<Mutex<Option<Vec<u8>>>>);
+
+    impl CredentialApi for SharedKeychain {
+        fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+            *self.0.lock().unwrap() = Some(secret.to_vec());
+            Ok(())
+        }
+
+        fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+            let secret = self.0.lock().unwrap().clone();
+            if secret.is_none() {
+                thread::sleep(Duration::from_millis(50));
+            }
+            secret.ok_or(keyring::Error::NoEntry)
+        }
+
+        fn delete_credential(&self) -> keyring::Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .take()
+                .map(drop)
+                .ok_or(keyring::Error::NoEntry)
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    impl CredentialBuilderApi for SharedKeychain {
+        fn build(&self, _: Option<&str>, _: &str, _: &str) -> keyring::Result<Box<Credential>> {
+            Ok(Box::new(SharedKeychain(self.0.clone())))
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn concurrent_first_saves_share_one_keychain_key() {
+        keyring::set_default_credential_builder(Box::new(SharedKeychain::default()));
+        let dir = tempfile::tempdir().unwrap();
+        let url = Url::parse("https://example.skedda.com/").unwrap();
+
+        let saves: Vec<_> = ["first", "second"]
+            .into_iter()
+            .map(|venue| {
+                let path = dir.path().join(format!("{venue}.enc"));
+                let url = url.clone();
+                thread::spawn(move || {
+                    let mut cookies = CookieStore::default();
+                    cookies
+                        .parse(&format!("session={venue}; Path=/"), &url)
+                        .unwrap();
+                    let store = SessionStore::with_passphrase(path, None);
+                    store.save(&cookies).unwrap();
+                    store
+                })
+            })
+            .collect();
+        let stores: Vec<_> = saves.into_iter().map(|save| save.join().unwrap()).collect();
+
+        assert_eq!(session(&stores[0]).as_deref(), Some("first"));
+        assert_eq!(session(&stores[1]).as_deref(), Some("second"));
+    }
+}
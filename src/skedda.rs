@@ -1,133 +1,1792 @@
-use anyhow::{Context, Result};
 use reqwest::{
-    blocking::Client,
-    header::{HeaderMap, HeaderValue},
+    Method, StatusCode, Url,
+    blocking::{Client, RequestBuilder, Response},
+    header::{CONTENT_TYPE, DATE, HeaderMap, RETRY_AFTER, SERVER},
 };
-use scraper::{Html, Selector};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::BufReader,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use time::PrimitiveDateTime;
+use tracing::{debug, warn};
+
+use crate::{
+    audit::{self, AuditEntry, AuditLog},
+    cache, clock,
+    config::VenueConfig,
+    history::{History, HistoryEntry},
+    hours::Schedule,
+    inspector::{self, RequestTiming},
+    models::{Booking, BookingRequest, CreateBookingResponse, CreatedBooking, WebsResponse},
+    network, page,
+    paths::Paths,
+    polling::Limiter,
+    retry::{Attempt, Retry},
+    rules::BookingRules,
+    session::{SessionCookies, SessionStore},
+};
+
+/// What the space detail panel shows about a space.
+#[derive(Debug, Clone, Default)]
+pub struct SpaceDetails {
+    pub name: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    /// The space's type, e.g. "Desk" or "Conference room", when the venue sets one.
+    pub kind: Option<String>,
+    pub rules: BookingRules,
+    /// When the space is open, from its own hours or its venue's.
+    pub hours: Schedule,
+}
+
+impl SpaceDetails {
+    /// Why booking the space from `start` to `end` at `now` isn't allowed, if it isn't: a
+    /// limit it breaks, or the space being closed then.
+    pub fn check(
+        &self,
+        start: PrimitiveDateTime,
+        end: PrimitiveDateTime,
+        now: PrimitiveDateTime,
+    ) -> Result<(), String> {
+        self.rules.check(&self.name, start, end, now)?;
+        self.hours.check(&self.name, start, end)
+    }
+}
+
+/// One of the logged-in account's own bookings, ready to show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MyBooking {
+    pub id: Option<String>,
+    /// The booked space's name, or names when the booking covers several.
+    pub space: String,
+    /// The configured location the space is tagged with.
+    pub location: Option<String>,
+    pub start: PrimitiveDateTime,
+    pub end: PrimitiveDateTime,
+    pub title: Option<String>,
+}
+
+/// Skedda answered with an outage or maintenance page instead of the page or data asked for.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "Skedda appears to be down (HTTP {}{})",
+    .status.as_u16(),
+    .title.as_ref().map(|title| format!(": {title}")).unwrap_or_default()
+)]
+pub struct Outage {
+    pub status: StatusCode,
+    /// The page's title, e.g. "Down for maintenance".
+    pub title: Option<String>,
+    /// How long the server asked clients to wait before trying again.
+    pub retry_after: Option<Duration>,
+}
+
+impl Outage {
+    /// Recognizes an outage from a server error status, or from an HTML page whose title
+    /// says the site is down.
+    fn detect(status: StatusCode, headers: &HeaderMap, body: &str) -> Option<Self> {
+        let title = page::title(body);
+        let down = matches!(status.as_u16(), 500 | 502 | 503 | 504)
+            || title.as_deref().is_some_and(|title| {
+                let title = title.to_ascii_lowercase();
+                [
+                    "maintenance",
+                    "down",
+                    "unavailable",
+                    "outage",
+                    "be right back",
+                ]
+                .iter()
+                .any(|word| title.contains(word))
+            });
+        down.then(|| Self {
+            status,
+            title,
+            retry_after: retry_after(headers),
+        })
+    }
+}
+
+/// A request's method and path, or `None` when it can't be built.
+fn describe(request: &RequestBuilder) -> Option<(Method, String)> {
+    let request = request.try_clone()?.build().ok()?;
+    Some((request.method().clone(), request.url().path().to_string()))
+}
+
+/// How long the server asked clients to wait, from a `Retry-After` given in seconds.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok()?.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// Cloudflare answered with a bot check instead of passing the request on to Skedda.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "Cloudflare wants a browser check (HTTP {}): open {url} in a browser, then try again",
+    .status.as_u16()
+)]
+pub struct Challenge {
+    pub status: StatusCode,
+    pub url: String,
+}
+
+impl Challenge {
+    /// Recognizes Cloudflare's challenge and block pages by their headers and markup.
+    fn detect(status: StatusCode, headers: &HeaderMap, body: &str, url: &str) -> Option<Self> {
+        let mitigated = headers
+            .get("cf-mitigated")
+            .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"challenge"));
+        let from_cloudflare = headers.contains_key("cf-ray")
+            || headers
+                .get(SERVER)
+                .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"cloudflare"));
+        let challenge_page = [
+            "/cdn-cgi/challenge-platform/",
+            "cf-chl-",
+            "cf_chl_opt",
+            "<title>Just a moment...</title>",
+            "Attention Required! | Cloudflare",
+        ]
+        .iter()
+        .any(|marker| body.contains(marker));
+        let challenged = mitigated
+            || (from_cloudflare && matches!(status.as_u16(), 403 | 429 | 503) && challenge_page);
+        challenged.then(|| Self {
+            status,
+            url: url.to_string(),
+        })
+    }
+}
+
+/// A failed request, sorted into the cases the app reacts to differently. Cheap to clone so it
+/// can travel back from a background thread in an event.
+///
+/// Every client method fails with one, sorted where the failure is found, so callers can
+/// match on it.
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum SkeddaError {
+    #[error(transparent)]
+    Outage(#[from] Outage),
+    #[error(transparent)]
+    Challenge(#[from] Challenge),
+    /// The venue couldn't be reached, or didn't answer in time.
+    #[error("{0}; check the connection and the venue's URL")]
+    Network(String),
+    /// The booking page came without the form token every request needs.
+    #[error(
+        "The booking page has no form token; Skedda may have changed it, \
+         so check for a newer syres"
+    )]
+    CsrfNotFound,
+    /// The session has run out and logging in again wasn't possible or didn't work.
+    #[error("{0}")]
+    AuthRequired(String),
+    /// Skedda is turning requests away for coming too often.
+    #[error(
+        "Skedda is turning away requests for coming too often; try again in {}",
+        .retry_after.map_or("a minute".to_string(), |wait| format!("{}s", wait.as_secs()))
+    )]
+    RateLimited { retry_after: Option<Duration> },
+    /// Skedda refused the booking because the time is taken.
+    #[error("Skedda refused the booking: {0}")]
+    BookingConflict(String),
+    /// A response didn't have the shape expected of it, after what was being done.
+    #[error("{0}; Skedda may have changed its data")]
+    Parse(String),
+    /// Anything else, after what was being done.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl SkeddaError {
+    /// Recognizes HTTP 429, which Skedda sends when requests come too often.
+    fn rate_limited(status: StatusCode, headers: &HeaderMap) -> Option<Self> {
+        (status == StatusCode::TOO_MANY_REQUESTS).then(|| Self::RateLimited {
+            retry_after: retry_after(headers),
+        })
+    }
+
+    /// A request that got no usable answer while `doing` something, sorted by why.
+    fn request(doing: &str, err: reqwest::Error) -> Self {
+        let message = format!("{doing}: {err}");
+        if err.is_connect() || err.is_timeout() {
+            Self::Network(message)
+        } else if err.is_decode() {
+            Self::Parse(message)
+        } else {
+            Self::Other(message)
+        }
+    }
+
+    /// A response that didn't parse while `doing` something.
+    fn parse(doing: &str, err: serde_json::Error) -> Self {
+        Self::Parse(format!("{doing}: {err}"))
+    }
+
+    /// Puts what was being done in front of the message, for the cases that carry one.
+    fn context(self, doing: impl fmt::Display) -> Self {
+        match self {
+            Self::Network(message) => Self::Network(format!("{doing}: {message}")),
+            Self::Parse(message) => Self::Parse(format!("{doing}: {message}")),
+            Self::Other(message) => Self::Other(format!("{doing}: {message}")),
+            err => err,
+        }
+    }
+}
+
+/// Each booking id asked to be cancelled, with how cancelling it went.
+pub type Cancellations = Vec<(String, Result<(), SkeddaError>)>;
+
+/// The logged-in account's standing at a venue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Member,
+    Admin,
+}
+
+/// The cookie jar and HTTP client every `Skedda` for a venue shares, so a connection opened
+/// by one request, or by [`Skedda::warm_up`], is reused by the next.
+#[derive(Clone)]
+struct Connection {
+    cookies: Arc<SessionCookies>,
+    /// Built on the first request, so creating a `Skedda` to read session state stays cheap.
+    client: Arc<OnceLock<Client>>,
+    limiter: Arc<Limiter>,
+    /// The booking page's form token, kept until Skedda turns it away or the session changes.
+    csrf_token: Arc<Mutex<Option<String>>>,
+}
+
+impl Connection {
+    /// A new connection to the venue, resuming the session saved in `session` if there is one.
+    fn open(venue: &VenueConfig, session: Option<&SessionStore>) -> Self {
+        let cookies = session.map(SessionStore::load).unwrap_or_default();
+        if let Some(token) = &venue.token {
+            add_token(&cookies, token, &venue.base_url);
+        }
+        Self {
+            cookies: Arc::new(cookies),
+            client: Arc::default(),
+            limiter: Arc::new(Limiter::new(network::limits(venue))),
+            csrf_token: Arc::default(),
+        }
+    }
+}
+
+/// Why Skedda turned a request down, from the error body it sends: a `message`, or the
+/// first of its `errors`.
+fn rejection(body: &serde_json::Value) -> Option<String> {
+    let text = |value: &serde_json::Value| {
+        value
+            .as_str()
+            .or_else(|| value.get("detail")?.as_str())
+            .or_else(|| value.get("title")?.as_str())
+            .filter(|text| !text.trim().is_empty())
+            .map(str::to_string)
+    };
+    body.get("message")
+        .and_then(text)
+        .or_else(|| body.get("errors")?.as_array()?.iter().find_map(text))
+}
+
+/// Whether a refusal is because the time is already taken, going by Skedda's wording.
+fn is_conflict(reason: &str) -> bool {
+    let reason = reason.to_ascii_lowercase();
+    ["conflict", "already booked", "overlap"]
+        .iter()
+        .any(|word| reason.contains(word))
+}
+
+/// Whether Skedda sent the request on to its login page, as it does once the session has
+/// run out.
+fn is_login_page(response: &Response) -> bool {
+    response.url().path().starts_with("/account/login")
+}
+
+/// Puts a session cookie from the config in the jar, unless the jar already has a cookie of
+/// that name for the venue.
+fn add_token(cookies: &SessionCookies, token: &str, base_url: &str) {
+    let Ok(url) = Url::parse(base_url) else {
+        return;
+    };
+    let name = token.split_once('=').map_or(token, |(name, _)| name).trim();
+    let mut store = cookies.lock();
+    if store
+        .matches(&url)
+        .iter()
+        .any(|cookie| cookie.name() == name)
+    {
+        return;
+    }
+    if let Err(err) = store.parse(&format!("{token}; Path=/"), &url) {
+        warn!("ignoring the configured token: {err}");
+    }
+}
+
+/// Connections by venue key.
+static CONNECTIONS: OnceLock<Mutex<HashMap<String, Connection>>> = OnceLock::new();
 
 pub struct Skedda {
-    client: Client,
+    client: Arc<OnceLock<Client>>,
     base_url: String,
-    pub venue_space_ids: HashMap<String, String>,
-    pub selected_location_space_ids: Vec<String>,
+    venue_key: String,
+    cookies: Arc<SessionCookies>,
+    limiter: Arc<Limiter>,
+    csrf_token: Arc<Mutex<Option<String>>>,
+    paths: Option<Paths>,
+    session: Option<SessionStore>,
+    audit: Option<AuditLog>,
+    history: Option<History>,
+    /// The email and password to log in with when the session has run out.
+    credentials: Option<(String, String)>,
 }
 
 impl Skedda {
-    pub fn new() -> Result<Self> {
-        let client = Client::builder()
-            .cookie_store(true)
-            .build()
-            .context("Failed to create HTTP client")?;
+    /// Creates a client for the venue, sharing the connection and session of any other client
+    /// for it, or resuming its saved session if this is the first.
+    pub fn new(venue: &VenueConfig) -> Self {
+        let paths = Paths::resolve();
+        let session = paths
+            .as_ref()
+            .map(|paths| SessionStore::new(paths.cookie_jar(&venue.key())));
+        let connection = CONNECTIONS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(venue.key())
+            .or_insert_with(|| Connection::open(venue, session.as_ref()))
+            .clone();
+        Self::with_connection(venue, connection, paths, session)
+    }
 
-        Ok(Self {
+    /// A client on a connection of its own that keeps everything under `root` and encrypts
+    /// its session with `passphrase`, out of the real data directory and keychain.
+    #[cfg(test)]
+    fn isolated(venue: &VenueConfig, root: &std::path::Path, passphrase: &str) -> Self {
+        let paths = Paths::under(root);
+        let session = SessionStore::with_passphrase(
+            paths.cookie_jar(&venue.key()),
+            Some(passphrase.to_string()),
+        );
+        let connection = Connection::open(venue, Some(&session));
+        Self::with_connection(venue, connection, Some(paths), Some(session))
+    }
+
+    fn with_connection(
+        venue: &VenueConfig,
+        connection: Connection,
+        paths: Option<Paths>,
+        session: Option<SessionStore>,
+    ) -> Self {
+        let Connection {
+            cookies,
             client,
-            base_url: "https://switchyards.skedda.com".to_string(),
-            selected_location_space_ids: Vec::new(),
-            venue_space_ids: HashMap::new(),
-        })
+            limiter,
+            csrf_token,
+        } = connection;
+        Self {
+            client,
+            base_url: venue.base_url.trim_end_matches('/').to_string(),
+            venue_key: venue.key(),
+            cookies,
+            limiter,
+            csrf_token,
+            audit: paths
+                .as_ref()
+                .map(|paths| AuditLog::new(paths.audit_file())),
+            history: paths
+                .as_ref()
+                .map(|paths| History::new(paths.history_db(&venue.key()))),
+            paths,
+            session,
+            credentials: venue.email.clone().zip(venue.password.clone()),
+        }
     }
 
-    // TODO: Need to auth and then add the cookies to the jar
-    pub fn get_booking_data(&self) -> Result<serde_json::Value> {
-        let csrf_token = self.get_booking_page()?;
-        let url = format!("{}/webs", self.base_url);
-        let mut headers = HeaderMap::new();
+    fn client(&self) -> Result<&Client, SkeddaError> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
+        }
+        let builder = Client::builder().cookie_provider(Arc::clone(&self.cookies));
+        let client = network::configure(builder, &self.base_url)
+            .build()
+            .map_err(|err| SkeddaError::request("Failed to create HTTP client", err))?;
+        Ok(self.client.get_or_init(|| client))
+    }
+
+    /// Sends the request, trying it again while it fails in a way that may pass, within the
+    /// configured retries. When they run out, the last response or error is returned for the
+    /// caller to sort into a [`SkeddaError`]. A request whose body can't be copied is only sent
+    /// once.
+    fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let mut retry = Retry::new(network::retry());
+        let mut request = request;
+        loop {
+            let (Some(next), Some((method, path))) = (request.try_clone(), describe(&request))
+            else {
+                return self.send_once(request);
+            };
+            let response = self.send_once(request);
+            let attempt = match &response {
+                Ok(response) => {
+                    Attempt::Answered(response.status(), retry_after(response.headers()))
+                }
+                Err(err) if err.is_connect() => Attempt::Unreachable,
+                Err(err) if err.is_timeout() => Attempt::TimedOut,
+                Err(_) => return response,
+            };
+            let Some(wait) = retry.wait(&method, attempt) else {
+                return response;
+            };
+            warn!(%method, path, ?attempt, wait_ms = wait.as_millis() as u64, "trying request again");
+            thread::sleep(wait);
+            request = next;
+        }
+    }
 
-        headers.insert(
-            "X-Skedda-RequestVerificationToken",
-            HeaderValue::from_str(&csrf_token)?,
+    /// Sends the request once one of the venue's request slots is free, noting how the local
+    /// clock compares with the server's and timing it for the request inspector.
+    fn send_once(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let (method, path) = describe(&request).map_or_else(
+            || ("?".to_string(), "?".to_string()),
+            |(method, path)| (method.to_string(), path),
         );
-        headers.insert("Accept", HeaderValue::from_str("application/json")?);
+        let queued_at = Instant::now();
+        let _permit = self.limiter.acquire();
+        let sent_at = Instant::now();
+        let response = request.send();
+        inspector::request_sent(RequestTiming {
+            method,
+            path,
+            outcome: match &response {
+                Ok(response) => Ok(response.status().as_u16()),
+                Err(err) => Err(err.to_string()),
+            },
+            queued: sent_at - queued_at,
+            first_byte: sent_at.elapsed(),
+        });
+        let response = response?;
+        if let Some(date) = response
+            .headers()
+            .get(DATE)
+            .and_then(|date| date.to_str().ok())
+        {
+            clock::observe_server_date(date);
+        }
+        Ok(response)
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .headers(headers)
-            .send()
-            .context("Failed to make request to /webs")?;
+    /// Whether data reloaded on a timer may be fetched from the venue again, given how
+    /// recently it was last fetched and the venue's poll floor.
+    pub fn may_poll(&self) -> bool {
+        self.limiter.poll_due()
+    }
+
+    /// Opens a connection to the venue and completes the TLS handshake, so the next request
+    /// only waits on the server.
+    pub fn warm_up(&self) -> Result<(), SkeddaError> {
+        inspector::call("warm-up", &self.venue_key, || {
+            debug!(url = %self.base_url, "warming up connection");
+            self.send(self.client()?.head(&self.base_url))
+                .map_err(|err| SkeddaError::request("Failed to connect to venue", err))?;
+            Ok(())
+        })
+    }
+
+    /// Fetches the venue's data: its spaces, their tags, bookings and the logged-in account,
+    /// logging in again with the configured account when the session has lapsed.
+    pub fn get_booking_data(&self) -> Result<WebsResponse, SkeddaError> {
+        inspector::call("venue data", &self.venue_key, || {
+            let response = self.request_booking_data()?;
+
+            // Large venues send several megabytes; deserialize as the body streams in rather
+            // than buffering it and building a `Value` tree first.
+            let webs = serde_json::from_reader(BufReader::new(response)).map_err(|err| {
+                SkeddaError::parse("Failed to parse JSON response from /webs", err)
+            })?;
 
-        let response_json = response
-            .json::<serde_json::Value>()
-            .context("Failed to parse JSON response from /webs")?;
+            self.save_session();
+            self.record_history(&webs);
+            Ok(webs)
+        })
+    }
 
-        Ok(response_json)
+    /// Fetches the venue's data and picks out the logged-in account's own bookings, placed in
+    /// the first of `locations` their space is tagged with.
+    pub fn get_my_bookings(&self, locations: &[String]) -> Result<Vec<MyBooking>, SkeddaError> {
+        let webs = self.get_booking_data()?;
+        Ok(Skedda::my_bookings(&webs, locations))
     }
 
-    fn get_booking_page(&self) -> Result<String> {
-        let url = format!("{}/booking", self.base_url);
+    /// Remembers my bookings in the venue's history, so they can be searched once Skedda no
+    /// longer sends them.
+    fn record_history(&self, webs: &WebsResponse) {
+        let (Some(history), Some(me)) = (
+            &self.history,
+            webs.venueuser.as_ref().and_then(|user| user.id.as_ref()),
+        ) else {
+            return;
+        };
+        let names = Skedda::space_names(webs);
+        let entries: Vec<HistoryEntry> = webs
+            .bookings
+            .iter()
+            .filter(|booking| booking.venueuser.as_ref() == Some(me))
+            .filter_map(|booking| {
+                Some(HistoryEntry {
+                    id: booking.id.clone()?,
+                    space: booking
+                        .space_ids
+                        .iter()
+                        .map(|id| names.get(id).map_or(id.as_str(), String::as_str))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    start: booking.start,
+                    end: booking.end,
+                    title: booking.title.clone(),
+                })
+            })
+            .collect();
+        history.record(&entries);
+    }
+
+    /// The raw `/webs` body, for parsing into a borrowed
+    /// [`WebsSummary`](crate::models::WebsSummary) when only spaces, space tags and bookings
+    /// are needed.
+    pub fn get_booking_data_body(&self) -> Result<Vec<u8>, SkeddaError> {
+        inspector::call("venue data", &self.venue_key, || {
+            let body = self
+                .request_booking_data()?
+                .bytes()
+                .map_err(|err| SkeddaError::request("Failed to read response from /webs", err))?;
+            self.save_session();
+            cache::keep_venue_data(self.paths.as_ref(), &self.venue_key, &body);
+            Ok(body.to_vec())
+        })
+    }
+
+    /// The venue's data, reused from the venue data cache while it's fresh unless `refresh`
+    /// asks for it anew, along with when it was fetched.
+    pub fn get_cached_booking_data(
+        &self,
+        refresh: bool,
+    ) -> Result<(WebsResponse, SystemTime), SkeddaError> {
+        if !refresh {
+            if let Some((body, fetched_at)) =
+                cache::venue_data(self.paths.as_ref(), &self.venue_key)
+            {
+                let webs = serde_json::from_slice(&body)
+                    .map_err(|err| SkeddaError::parse("Failed to parse cached venue data", err))?;
+                return Ok((webs, fetched_at));
+            }
+        }
+        let body = self.get_booking_data_body()?;
+        let webs = serde_json::from_slice(&body)
+            .map_err(|err| SkeddaError::parse("Failed to parse JSON response from /webs", err))?;
+        self.record_history(&webs);
+        Ok((webs, SystemTime::now()))
+    }
+
+    /// What's left to show when fetching the venue's data failed with `err`: when it was the
+    /// venue not being reachable, its data as last fetched, however old, and when that was.
+    pub fn last_known_booking_data(
+        config: &VenueConfig,
+        err: SkeddaError,
+    ) -> Result<(WebsResponse, SystemTime), SkeddaError> {
+        Self::last_known_under(Paths::resolve().as_ref(), config, err)
+    }
+
+    /// [`Skedda::last_known_booking_data`], kept under `paths`.
+    fn last_known_under(
+        paths: Option<&Paths>,
+        config: &VenueConfig,
+        err: SkeddaError,
+    ) -> Result<(WebsResponse, SystemTime), SkeddaError> {
+        if !matches!(err, SkeddaError::Network(_)) {
+            return Err(err);
+        }
+        let Some((body, fetched_at)) =
+            paths.and_then(|paths| cache::last_known_venue_data(paths, &config.key()))
+        else {
+            return Err(err);
+        };
+        match serde_json::from_slice(&body) {
+            Ok(webs) => {
+                warn!(venue = config.name, "showing offline venue data: {err}");
+                Ok((webs, fetched_at))
+            }
+            Err(parse_err) => {
+                warn!(
+                    venue = config.name,
+                    "failed to parse offline venue data: {parse_err}"
+                );
+                Err(err)
+            }
+        }
+    }
+
+    fn request_booking_data(&self) -> Result<Response, SkeddaError> {
+        let response = self.fetch_booking_data()?;
+        if !is_login_page(&response) {
+            return self.expect_json(response);
+        }
+        self.log_in()?;
+        self.expect_json(self.fetch_booking_data()?)
+    }
+
+    fn fetch_booking_data(&self) -> Result<Response, SkeddaError> {
+        let url = format!("{}/webs", self.base_url);
+        debug!(%url, "fetching venue data");
+        self.limiter.fetched();
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .context("Failed to fetch booking page")?;
+            .send_with_token(|token| {
+                Ok(self
+                    .client()?
+                    .get(&url)
+                    .header("X-Skedda-RequestVerificationToken", token)
+                    .header("Accept", "application/json"))
+            })?
+            .map_err(|err| SkeddaError::request("Failed to make request to /webs", err))?;
+        debug!(status = %response.status(), "received venue data");
+        Ok(response)
+    }
 
-        let html_content = response
-            .text()
-            .context("Failed to get response text")?;
+    /// Sends a request carrying the form token. The token is fetched from the booking page
+    /// once and kept; when Skedda turns a kept token away with HTTP 403, the page is fetched
+    /// again and the request sent once more.
+    fn send_with_token(
+        &self,
+        request: impl Fn(&str) -> Result<RequestBuilder, SkeddaError>,
+    ) -> Result<reqwest::Result<Response>, SkeddaError> {
+        let Some(token) = self.kept_csrf_token() else {
+            return Ok(self.send(request(&self.fresh_csrf_token()?)?));
+        };
+        let sent = self.send(request(&token)?);
+        if !matches!(&sent, Ok(response) if response.status() == StatusCode::FORBIDDEN) {
+            return Ok(sent);
+        }
+        debug!("the kept form token was turned away, fetching the booking page again");
+        Ok(self.send(request(&self.fresh_csrf_token()?)?))
+    }
 
-        let token = Skedda::extract_csrf_token(&html_content)?;
+    /// The kept form token, or else a fresh one.
+    fn csrf_token(&self) -> Result<String, SkeddaError> {
+        match self.kept_csrf_token() {
+            Some(token) => Ok(token),
+            None => self.fresh_csrf_token(),
+        }
+    }
+
+    fn kept_csrf_token(&self) -> Option<String> {
+        self.csrf_token
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Fetches the form token from the booking page and keeps it for later requests.
+    fn fresh_csrf_token(&self) -> Result<String, SkeddaError> {
+        let token = self.get_booking_page()?;
+        self.keep_csrf_token(Some(token.clone()));
         Ok(token)
     }
 
-    fn extract_csrf_token(html_content: &str) -> Result<String> {
-        let document = Html::parse_document(html_content);
-        let selectors = ["input[name='__RequestVerificationToken']"];
+    fn keep_csrf_token(&self, token: Option<String>) {
+        *self
+            .csrf_token
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = token;
+    }
+
+    /// Logs in with the venue's email and password through Skedda's login form, keeping the
+    /// session it hands out.
+    fn log_in(&self) -> Result<(), SkeddaError> {
+        let Some((email, password)) = &self.credentials else {
+            return Err(SkeddaError::AuthRequired(
+                "The session has run out and there's no account to log in with; \
+                 set email and password for the venue, or a fresh token"
+                    .to_string(),
+            ));
+        };
+        let url = format!("{}/account/login", self.base_url);
+        debug!(%url, "logging in");
+        let form = self
+            .send(self.client()?.get(&url))
+            .and_then(Response::text)
+            .map_err(|err| SkeddaError::request("Failed to fetch the login page", err))?;
+        let token = page::csrf_token(&form)
+            .ok_or_else(|| SkeddaError::Other("The login page has no form token".to_string()))?;
+        let response = self
+            .send(self.client()?.post(&url).form(&[
+                ("Email", email.as_str()),
+                ("Password", password.as_str()),
+                ("__RequestVerificationToken", token.as_str()),
+            ]))
+            .map_err(|err| SkeddaError::request("Failed to log in", err))?;
+        debug!(status = %response.status(), "received login response");
+        // The form token belongs to the session it was issued in.
+        self.keep_csrf_token(None);
+        if !response.status().is_success() || is_login_page(&response) {
+            return Err(SkeddaError::AuthRequired(format!(
+                "Skedda didn't accept the email and password for {email}; check them in config"
+            )));
+        }
+        self.save_session();
+        Ok(())
+    }
+
+    /// Passes JSON responses through, turning challenge and outage pages and other errors into
+    /// errors that say what came back rather than a JSON parse failure.
+    fn expect_json(&self, response: Response) -> Result<Response, SkeddaError> {
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_none_or(|content_type| content_type.contains("json"));
+        if response.status().is_success() && is_json {
+            return Ok(response);
+        }
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.text().unwrap_or_default();
+        if let Some(challenge) = Challenge::detect(status, &headers, &body, &self.base_url) {
+            return Err(challenge.into());
+        }
+        if let Some(outage) = Outage::detect(status, &headers, &body) {
+            return Err(outage.into());
+        }
+        if let Some(limited) = SkeddaError::rate_limited(status, &headers) {
+            return Err(limited);
+        }
+        Err(SkeddaError::Other(match page::title(&body) {
+            Some(title) => format!("Expected JSON but got a page titled {title:?} (HTTP {status})"),
+            None => format!("Unexpected response (HTTP {status})"),
+        }))
+    }
+
+    /// Makes the booking. When the request gets no answer, such as on a timeout, the venue's
+    /// bookings are checked for it before giving up, so trying again can't book it twice.
+    /// Waits first if the venue's last booking was sooner than its limits allow.
+    ///
+    /// The attempt and its outcome go in the audit log.
+    pub fn create_booking(&self, request: &BookingRequest) -> Result<CreatedBooking, SkeddaError> {
+        let result = inspector::call("booking", &self.venue_key, || self.send_booking(request));
+        if let Some(log) = &self.audit {
+            log.record(&AuditEntry::booking(
+                &self.venue_key,
+                &audit::request_id(),
+                request,
+                &result,
+            ));
+        }
+        result
+    }
+
+    fn send_booking(&self, request: &BookingRequest) -> Result<CreatedBooking, SkeddaError> {
+        self.limiter.wait_to_book();
+        let url = format!("{}/bookings", self.base_url);
+        debug!(%url, spaces = ?request.spaces, "creating booking");
+        let sent = self.send_with_token(|token| {
+            Ok(self
+                .client()?
+                .post(&url)
+                .header("X-Skedda-RequestVerificationToken", token)
+                .header("Accept", "application/json")
+                .json(&request.body()))
+        })?;
+        let response = match sent {
+            Ok(response) => response,
+            // Nothing reached the server.
+            Err(err) if err.is_connect() => {
+                return Err(SkeddaError::request("Failed to send booking", err));
+            }
+            Err(err) => {
+                warn!("no answer to the booking request, checking whether it was made: {err}");
+                return match self.find_booking(request) {
+                    Ok(Some(created)) => Ok(created),
+                    Ok(None) => Err(SkeddaError::request("Booking failed and wasn't made", err)),
+                    Err(check) => Err(check.context(format!(
+                        "Booking got no answer ({err}) and couldn't be checked; \
+                         look for it before trying again"
+                    ))),
+                };
+            }
+        };
+        debug!(status = %response.status(), "received booking response");
+        if response.status().is_client_error() {
+            let status = response.status();
+            if let Some(limited) = SkeddaError::rate_limited(status, response.headers()) {
+                return Err(limited);
+            }
+            let body: serde_json::Value = response.json().unwrap_or_default();
+            return Err(match rejection(&body) {
+                Some(reason) if status == StatusCode::CONFLICT || is_conflict(&reason) => {
+                    SkeddaError::BookingConflict(reason)
+                }
+                Some(reason) => SkeddaError::Other(format!("Skedda refused the booking: {reason}")),
+                None => SkeddaError::Other(format!("Skedda refused the booking (HTTP {status})")),
+            });
+        }
+        let created: CreateBookingResponse = serde_json::from_reader(BufReader::new(
+            self.expect_json(response)?,
+        ))
+        .map_err(|err| SkeddaError::parse("Failed to parse JSON response from /bookings", err))?;
+        self.save_session();
+        cache::forget_venue_data(self.paths.as_ref(), &self.venue_key);
+        Ok(created.booking)
+    }
+
+    /// The booking the request makes, if the venue already has it.
+    pub fn find_booking(
+        &self,
+        request: &BookingRequest,
+    ) -> Result<Option<CreatedBooking>, SkeddaError> {
+        let webs = self.get_booking_data()?;
+        Ok(webs
+            .bookings
+            .iter()
+            .filter(|booking| request.matches(booking))
+            .find_map(|booking| {
+                Some(CreatedBooking {
+                    id: booking.id.clone()?,
+                    price: None,
+                })
+            }))
+    }
 
-        for selector_str in &selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(element) = document.select(&selector).next() {
-                    if let Some(token) = element.value().attr("value") {
-                        return Ok(token.to_string());
+    /// Cancels each booking by id, returning every booking's outcome. The optional message is
+    /// passed on to the members whose bookings are cancelled.
+    ///
+    /// Each booking's outcome goes in the audit log, under one request id for the batch.
+    pub fn cancel_bookings(
+        &self,
+        booking_ids: &[String],
+        message: Option<&str>,
+    ) -> Result<Cancellations, SkeddaError> {
+        let result = inspector::call("cancellation", &self.venue_key, || {
+            self.send_cancellations(booking_ids, message)
+        });
+        if let Some(log) = &self.audit {
+            let request_id = audit::request_id();
+            match &result {
+                Ok(results) => {
+                    for (booking_id, result) in results {
+                        log.record(&AuditEntry::cancellation(
+                            &self.venue_key,
+                            &request_id,
+                            booking_id,
+                            result,
+                        ));
+                    }
+                }
+                Err(err) => {
+                    let failed = Err(err.clone());
+                    for booking_id in booking_ids {
+                        log.record(&AuditEntry::cancellation(
+                            &self.venue_key,
+                            &request_id,
+                            booking_id,
+                            &failed,
+                        ));
                     }
                 }
             }
         }
+        result
+    }
 
-        Err(anyhow::anyhow!("CSRF token not found in HTML content"))
+    /// Cancels one booking, recorded in the audit log like a bulk cancellation.
+    pub fn cancel_booking(&self, booking_id: &str) -> Result<(), SkeddaError> {
+        self.cancel_bookings(&[booking_id.to_string()], None)?
+            .pop()
+            .map_or_else(
+                || {
+                    Err(SkeddaError::Other(format!(
+                        "No answer for booking {booking_id}"
+                    )))
+                },
+                |(_, result)| result,
+            )
     }
 
-    pub fn fetch_space_ids(&self) -> HashMap<String, String> {
-        let mut venue_space_ids = HashMap::new();
-        let webs_data = self.get_booking_data().unwrap();
+    fn send_cancellations(
+        &self,
+        booking_ids: &[String],
+        message: Option<&str>,
+    ) -> Result<Cancellations, SkeddaError> {
+        // Without a token none of them can go, so that fails the whole call.
+        self.csrf_token()?;
+        let client = self.client()?;
+        let results = booking_ids
+            .iter()
+            .map(|booking_id| {
+                let url = format!("{}/bookings/{booking_id}", self.base_url);
+                debug!(%url, "cancelling booking");
+                let doing = format!("Failed to cancel booking {booking_id}");
+                let result = self
+                    .send_with_token(|token| {
+                        let request = client
+                            .delete(&url)
+                            .header("X-Skedda-RequestVerificationToken", token);
+                        Ok(match message {
+                            Some(message) => {
+                                request.json(&serde_json::json!({ "message": message }))
+                            }
+                            None => request,
+                        })
+                    })
+                    .map_err(|err| err.context(&doing))
+                    .and_then(|sent| {
+                        sent.and_then(Response::error_for_status)
+                            .map(drop)
+                            .map_err(|err| SkeddaError::request(&doing, err))
+                    });
+                (booking_id.clone(), result)
+            })
+            .collect();
+        self.save_session();
+        cache::forget_venue_data(self.paths.as_ref(), &self.venue_key);
+        Ok(results)
+    }
 
-        if let serde_json::Value::Array(items) = &webs_data["spaces"] {
-            for item in items {
-                if let (Some(id), Some(name)) = (
-                    item.get("id").and_then(serde_json::Value::as_str),
-                    item.get("name").and_then(serde_json::Value::as_str),
-                ) {
-                    venue_space_ids.insert(id.to_string(), name.to_string());
-                }
+    /// When the current session expires, or `None` when there is no session yet.
+    pub fn session_expiry(&self) -> Option<SystemTime> {
+        let url = Url::parse(&self.base_url).ok()?;
+        self.cookies.expires_at(&url)
+    }
+
+    /// Revisits the booking page so the server reissues the session cookies.
+    pub fn refresh_session(&self) -> Result<Option<SystemTime>, SkeddaError> {
+        inspector::call("session refresh", &self.venue_key, || {
+            self.fresh_csrf_token()?;
+            self.save_session();
+            Ok(self.session_expiry())
+        })
+    }
+
+    /// Visits the booking page so Skedda doesn't let an idle session lapse, logging in again
+    /// with the configured account when it already has. Returns when the session now expires.
+    pub fn keep_alive(&self) -> Result<Option<SystemTime>, SkeddaError> {
+        inspector::call("keep-alive", &self.venue_key, || self.touch_session())
+    }
+
+    fn touch_session(&self) -> Result<Option<SystemTime>, SkeddaError> {
+        let url = format!("{}/booking", self.base_url);
+        debug!(%url, "keeping session alive");
+        let response = self
+            .send(self.client()?.get(&url))
+            .map_err(|err| SkeddaError::request("Failed to reach the venue", err))?;
+        if is_login_page(&response) {
+            self.log_in()?;
+        } else {
+            let page = response
+                .error_for_status()
+                .and_then(Response::text)
+                .map_err(|err| SkeddaError::request("The venue didn't answer", err))?;
+            // The page is fetched anyway; its token saves the next request fetching it again.
+            if let Some(token) = page::csrf_token(&page) {
+                self.keep_csrf_token(Some(token));
             }
+            self.save_session();
         }
+        Ok(self.session_expiry())
+    }
 
-        return venue_space_ids;
-    }
-
-    pub fn fetch_location_space_ids(&self, selected_location: &str) -> Vec<String> {
-        let webs_data = self.get_booking_data();
-        let webs_response = &webs_data["venue"][0]["spacePresentation"]["spaceTags"];
-
-        if let serde_json::Value::Array(items) = &webs_data["spaces"] {
-            for item in items {
-                if let serde_json::Value::Object(obj) = item {
-                    if selected_location
-                    == obj.unwrap()
-                        .get("name")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string())
-                    {
-                        if let Some(serde_json::Value::Array(space_ids)) = obj.get("spaceIds") {
-                            return space_ids
-                                .iter()
-                                .filter_map(|v| v.as_i64())
-                                .map(|n| n.to_string())
-                                .collect();
-                        }
-                    }
-                }
+    /// Writes the session cookies to disk, encrypted, so the next run can reuse them.
+    fn save_session(&self) {
+        if let Some(session) = &self.session {
+            if let Err(err) = session.save(&self.cookies.lock()) {
+                warn!("failed to save session: {err:#}");
             }
         }
     }
+
+    fn get_booking_page(&self) -> Result<String, SkeddaError> {
+        let url = format!("{}/booking", self.base_url);
+        debug!(%url, "fetching booking page");
+        let response = self
+            .send(self.client()?.get(&url))
+            .map_err(|err| SkeddaError::request("Failed to fetch booking page", err))?;
+        let status = response.status();
+        debug!(%status, "received booking page");
+        let headers = response.headers().clone();
+
+        let html_content = response
+            .text()
+            .map_err(|err| SkeddaError::request("Failed to get response text", err))?;
+        if let Some(challenge) = Challenge::detect(status, &headers, &html_content, &url) {
+            return Err(challenge.into());
+        }
+        if let Some(outage) = Outage::detect(status, &headers, &html_content) {
+            return Err(outage.into());
+        }
+        if let Some(limited) = SkeddaError::rate_limited(status, &headers) {
+            return Err(limited);
+        }
+
+        page::csrf_token(&html_content).ok_or(SkeddaError::CsrfNotFound)
+    }
+
+    pub fn fetch_space_ids(&self) -> Result<HashMap<String, String>, SkeddaError> {
+        Ok(Skedda::space_names(&self.get_booking_data()?))
+    }
+
+    pub fn fetch_location_space_ids(
+        &self,
+        selected_location: &str,
+    ) -> Result<Vec<String>, SkeddaError> {
+        Ok(Skedda::location_space_ids(
+            &self.get_booking_data()?,
+            selected_location,
+        ))
+    }
+
+    /// Space names by id from a `/webs` response.
+    pub fn space_names(webs: &WebsResponse) -> HashMap<String, String> {
+        webs.spaces
+            .iter()
+            .map(|space| (space.id.clone(), space.name.clone()))
+            .collect()
+    }
+
+    /// Details of every space by id from a `/webs` response.
+    pub fn space_details(webs: &WebsResponse) -> HashMap<String, SpaceDetails> {
+        let text = |values: &[&Option<String>]| {
+            values
+                .iter()
+                .find_map(|value| value.as_ref().filter(|value| !value.trim().is_empty()))
+                .cloned()
+        };
+        let venue_hours = webs
+            .venue
+            .first()
+            .map_or(&[][..], |venue| &venue.opening_hours);
+        webs.spaces
+            .iter()
+            .map(|space| {
+                (
+                    space.id.clone(),
+                    SpaceDetails {
+                        name: space.name.clone(),
+                        description: text(&[&space.description, &space.details]),
+                        image_url: text(&[
+                            &space.image_url,
+                            &space.image,
+                            &space.photo_url,
+                            &space.thumbnail_url,
+                        ]),
+                        kind: text(&[&space.space_type, &space.type_name, &space.category]),
+                        rules: BookingRules::from_minutes(
+                            space.max_booking_length,
+                            space.min_booking_lead_time,
+                            space.max_booking_lead_time,
+                        ),
+                        hours: Schedule::new(venue_hours, &space.opening_hours),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// The venue's locations in a `/webs` response: the space tags that have spaces, by name.
+    pub fn location_names(webs: &WebsResponse) -> Vec<String> {
+        let mut names: Vec<String> = webs
+            .venue
+            .first()
+            .and_then(|venue| venue.space_presentation.as_ref())
+            .map(|presentation| {
+                presentation
+                    .space_tags
+                    .iter()
+                    .filter(|tag| !tag.space_ids.is_empty())
+                    .map(|tag| tag.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort_by_key(|name| name.to_lowercase());
+        names.dedup();
+        names
+    }
+
+    /// Ids of the spaces tagged with the location in a `/webs` response.
+    pub fn location_space_ids(webs: &WebsResponse, selected_location: &str) -> Vec<String> {
+        webs.venue
+            .first()
+            .and_then(|venue| venue.space_presentation.as_ref())
+            .and_then(|presentation| {
+                presentation
+                    .space_tags
+                    .iter()
+                    .find(|tag| tag.name == selected_location)
+            })
+            .map(|tag| tag.space_ids.clone())
+            .unwrap_or_default()
+    }
+
+    /// Existing bookings from a `/webs` response, with their holders filled in when the
+    /// response includes the member list.
+    pub fn bookings(webs: &WebsResponse) -> Vec<Booking> {
+        let members = Skedda::member_names(webs);
+        webs.bookings
+            .iter()
+            .map(|booking| Booking {
+                holder: booking
+                    .venueuser
+                    .as_ref()
+                    .and_then(|member| members.get(member).cloned())
+                    .or_else(|| {
+                        booking
+                            .title
+                            .clone()
+                            .filter(|title| !title.trim().is_empty())
+                    }),
+                ..booking.clone()
+            })
+            .collect()
+    }
+
+    /// The logged-in account's own bookings, soonest first, placed in the first of
+    /// `locations` their space is tagged with.
+    pub fn my_bookings(webs: &WebsResponse, locations: &[String]) -> Vec<MyBooking> {
+        let Some(me) = webs.venueuser.as_ref().and_then(|user| user.id.as_ref()) else {
+            return Vec::new();
+        };
+        let spaces = Skedda::space_details(webs);
+        let tagged: Vec<(&String, Vec<String>)> = locations
+            .iter()
+            .map(|location| (location, Skedda::location_space_ids(webs, location)))
+            .collect();
+        let mut mine: Vec<MyBooking> = webs
+            .bookings
+            .iter()
+            .filter(|booking| booking.venueuser.as_ref() == Some(me))
+            .map(|booking| MyBooking {
+                id: booking.id.clone(),
+                space: booking
+                    .space_ids
+                    .iter()
+                    .map(|id| spaces.get(id).map_or(id.as_str(), |space| &space.name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                location: tagged
+                    .iter()
+                    .find(|(_, space_ids)| {
+                        booking.space_ids.iter().any(|id| space_ids.contains(id))
+                    })
+                    .map(|(location, _)| location.to_string()),
+                start: booking.start,
+                end: booking.end,
+                title: booking
+                    .title
+                    .clone()
+                    .filter(|title| !title.trim().is_empty()),
+            })
+            .collect();
+        mine.sort_by_key(|booking| booking.start);
+        mine
+    }
+
+    /// Member names by id from a `/webs` response; only admins get the member list.
+    pub fn member_names(webs: &WebsResponse) -> HashMap<String, String> {
+        webs.venueusers
+            .iter()
+            .filter_map(|member| {
+                let name = match (&member.first_name, &member.last_name) {
+                    (Some(first), Some(last)) => format!("{first} {last}"),
+                    (Some(name), None) | (None, Some(name)) => name.clone(),
+                    (None, None) => member.name.clone().or(member.username.clone())?,
+                };
+                Some((member.id.clone(), name.trim().to_string()))
+            })
+            .collect()
+    }
+
+    /// The logged-in account's role from a `/webs` response, or `None` when the response
+    /// doesn't describe the account.
+    pub fn role(webs: &WebsResponse) -> Option<Role> {
+        let user = webs.venueuser.as_ref()?;
+        let is_admin_name = |name: &str| {
+            ["admin", "owner", "manager"]
+                .iter()
+                .any(|role| name.to_ascii_lowercase().contains(role))
+        };
+        let flagged = [user.is_admin, user.admin, user.is_venue_admin].contains(&Some(true));
+        let in_roles = user.roles.iter().any(|role| is_admin_name(role))
+            || user.role.as_deref().is_some_and(is_admin_name);
+        Some(if flagged || in_roles {
+            Role::Admin
+        } else {
+            Role::Member
+        })
+    }
+
+    /// The step, in minutes, that booking times must fall on, from the venue's settings.
+    pub fn booking_increment(webs: &WebsResponse) -> Option<u32> {
+        let venue = webs.venue.first()?;
+        [
+            venue.booking_increment,
+            venue.time_increment,
+            venue.minimum_booking_increment,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|minutes| (1..=24 * 60).contains(minutes))
+    }
+
+    /// Downloads a space photo, reusing the copy cached on disk when there is one.
+    pub fn fetch_image(&self, image_url: &str) -> Result<Vec<u8>, SkeddaError> {
+        let url = Url::parse(&self.base_url)
+            .and_then(|base| base.join(image_url))
+            .map_err(|err| SkeddaError::Other(format!("Invalid image URL: {err}")))?;
+        let cached = self.paths.as_ref().map(|paths| {
+            paths
+                .venue_cache_dir()
+                .join(&self.venue_key)
+                .join("images")
+                .join(format!("{:016x}", fnv1a(url.as_str())))
+        });
+        if let Some(bytes) = cached.as_ref().and_then(|path| fs::read(path).ok()) {
+            return Ok(bytes);
+        }
+
+        debug!(%url, "fetching space image");
+        let bytes = self
+            .send(self.client()?.get(url))
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+            .map_err(|err| SkeddaError::request("Failed to fetch space image", err))?
+            .to_vec();
+        if let Some(path) = cached {
+            if let Err(err) = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::write(&path, &bytes))
+            {
+                warn!("failed to cache space image: {err}");
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// FNV-1a, a stable hash for naming cached files.
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::{fixtures, grid};
+
+    #[test]
+    fn maps_small_venue_for_the_booking_form() {
+        let webs = fixtures::webs_response("small_venue.json");
+
+        let spaces = Skedda::space_details(&webs);
+        assert_eq!(spaces.len(), 6);
+        let desk = &spaces["101"];
+        assert_eq!(desk.name, "Desk 1");
+        assert_eq!(desk.kind.as_deref(), Some("Desk"));
+        assert_eq!(desk.image_url.as_deref(), Some("/images/desk-1.jpg"));
+        assert_eq!(spaces["102"].description, None);
+        assert_eq!(
+            spaces["103"].rules.summary().as_deref(),
+            Some("Up to 2h · 1h notice")
+        );
+        assert_eq!(desk.rules, BookingRules::default());
+        assert_eq!(
+            spaces["103"].check(
+                datetime!(2024-05-01 17:00),
+                datetime!(2024-05-01 19:00),
+                datetime!(2024-05-01 9:00)
+            ),
+            Err("Boardroom is open 08:00–18:00 on Wed 1 May".to_string())
+        );
+        assert!(
+            !desk
+                .hours
+                .is_open(datetime!(2024-05-05 9:00), datetime!(2024-05-05 10:00))
+        );
+        assert!(
+            desk.hours
+                .is_open(datetime!(2024-05-01 17:00), datetime!(2024-05-01 19:00))
+        );
+
+        assert_eq!(
+            Skedda::location_space_ids(&webs, "Decatur"),
+            ["101", "102", "103", "104"]
+        );
+        assert!(Skedda::location_space_ids(&webs, "Roswell").is_empty());
+        assert_eq!(Skedda::booking_increment(&webs), Some(15));
+        assert_eq!(Skedda::role(&webs), Some(Role::Member));
+    }
+
+    #[test]
+    fn sorts_failures_for_the_app() {
+        // Nothing listens on port 1.
+        let refused = Client::new().get("http://127.0.0.1:1/").send().unwrap_err();
+        match SkeddaError::request("Failed to connect to venue", refused) {
+            SkeddaError::Network(message) => {
+                assert!(
+                    message.starts_with("Failed to connect to venue: "),
+                    "{message}"
+                )
+            }
+            other => panic!("expected Network, got {other:?}"),
+        }
+
+        let err = SkeddaError::parse(
+            "Failed to parse JSON response from /webs",
+            serde_json::from_str::<u32>("{").unwrap_err(),
+        );
+        assert!(matches!(err, SkeddaError::Parse(_)));
+        assert!(
+            err.to_string()
+                .ends_with("; Skedda may have changed its data")
+        );
+
+        // What was being done goes in front of a message, and leaves the other cases be.
+        let err = SkeddaError::Other("connection reset".to_string()).context("fetching /webs");
+        assert_eq!(err.to_string(), "fetching /webs: connection reset");
+        let outage = Outage::detect(StatusCode::SERVICE_UNAVAILABLE, &HeaderMap::new(), "")
+            .expect("503 is an outage");
+        let err = SkeddaError::from(outage).context("fetching /webs");
+        assert_eq!(err.to_string(), "Skedda appears to be down (HTTP 503)");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        let limited = SkeddaError::rate_limited(StatusCode::TOO_MANY_REQUESTS, &headers).unwrap();
+        assert!(matches!(
+            limited,
+            SkeddaError::RateLimited {
+                retry_after: Some(wait)
+            } if wait == Duration::from_secs(30)
+        ));
+        assert!(limited.to_string().ends_with("try again in 30s"));
+    }
+
+    #[test]
+    fn finds_my_bookings_and_their_locations() {
+        let webs = fixtures::webs_response("small_venue.json");
+        let mine = Skedda::my_bookings(&webs, &["Midtown".to_string(), "Decatur".to_string()]);
+        let summary: Vec<(&str, Option<&str>, String)> = mine
+            .iter()
+            .map(|booking| {
+                (
+                    booking.space.as_str(),
+                    booking.location.as_deref(),
+                    grid::format_time(booking.start.time()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Boardroom", Some("Decatur"), "09:00".to_string()),
+                ("Phone Booth", Some("Decatur"), "11:30".to_string()),
+            ]
+        );
+        assert!(Skedda::my_bookings(&webs, &[])[0].location.is_none());
+    }
+
+    #[test]
+    fn members_see_booking_titles_as_holders() {
+        let webs = fixtures::webs_response("small_venue.json");
+        let bookings = Skedda::bookings(&webs);
+        let holders: Vec<Option<&str>> = bookings
+            .iter()
+            .map(|booking| booking.holder.as_deref())
+            .collect();
+        assert_eq!(
+            holders,
+            [Some("Standup"), None, Some("Client call"), Some("Workshop")]
+        );
+    }
+
+    #[test]
+    fn admins_see_member_names_as_holders() {
+        let webs = fixtures::webs_response("large_venue.json");
+        assert_eq!(Skedda::role(&webs), Some(Role::Admin));
+        assert_eq!(Skedda::member_names(&webs).len(), 50);
+        assert!(Skedda::bookings(&webs).iter().all(|booking| {
+            booking
+                .holder
+                .as_deref()
+                .is_some_and(|holder| holder.starts_with("Member") && holder.ends_with(" Example"))
+        }));
+        assert_eq!(Skedda::booking_increment(&webs), Some(30));
+    }
+
+    #[test]
+    fn venue_without_tags_has_no_location_spaces() {
+        let webs = fixtures::webs_response("empty_space_tags.json");
+        assert_eq!(Skedda::space_names(&webs).len(), 1);
+        assert!(Skedda::location_space_ids(&webs, "Decatur").is_empty());
+        assert!(Skedda::location_names(&webs).is_empty());
+        assert_eq!(Skedda::booking_increment(&webs), None);
+        assert_eq!(Skedda::role(&webs), Some(Role::Member));
+    }
+
+    #[test]
+    fn quirky_values_fall_back_sensibly() {
+        let webs = fixtures::webs_response("quirks.json");
+        let spaces = Skedda::space_details(&webs);
+        assert_eq!(spaces["101"].kind.as_deref(), Some("Desk"));
+        assert_eq!(spaces["102"].kind.as_deref(), Some("Meeting"));
+        // A tag without spaces isn't offered as a location.
+        assert_eq!(Skedda::location_names(&webs), ["Decatur"]);
+        assert_eq!(Skedda::booking_increment(&webs), Some(30));
+        assert_eq!(Skedda::role(&webs), Some(Role::Admin));
+        assert!(
+            Skedda::bookings(&webs)
+                .iter()
+                .all(|booking| booking.holder.is_none())
+        );
+    }
+}
+
+/// Replays the recorded responses in `tests/fixtures/http` through the client, so a change in
+/// what Skedda sends shows up here first. CI runs these on a schedule with
+/// `cargo test contract`.
+#[cfg(test)]
+mod contract {
+    use std::ops::Deref;
+
+    use tempfile::TempDir;
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::{
+        fixtures::{self, ReplayServer},
+        models::WebsSummary,
+    };
+
+    /// A client whose session, caches and logs live in a directory of its own, removed along
+    /// with it.
+    struct Isolated {
+        skedda: Skedda,
+        _home: TempDir,
+    }
+
+    impl Deref for Isolated {
+        type Target = Skedda;
+
+        fn deref(&self) -> &Skedda {
+            &self.skedda
+        }
+    }
+
+    fn isolated(venue: &VenueConfig) -> Isolated {
+        let home = tempfile::tempdir().unwrap();
+        Isolated {
+            skedda: Skedda::isolated(venue, home.path(), "contract-tests"),
+            _home: home,
+        }
+    }
+
+    fn client(server: &ReplayServer) -> Isolated {
+        isolated(&VenueConfig {
+            name: "Example Workspace".to_string(),
+            base_url: server.base_url.clone(),
+            locations: vec!["Decatur".to_string(), "Midtown".to_string()],
+            presets: None,
+            admin: false,
+            limits: None,
+            email: None,
+            password: None,
+            token: None,
+            discover_locations: false,
+        })
+    }
+
+    #[test]
+    fn booking_page_carries_the_csrf_token() {
+        let server = ReplayServer::start(&[("GET /booking", "booking_page.http")]);
+        let token = client(&server).get_booking_page().unwrap();
+        assert_eq!(token, "CfDJ8Example-Token_0123456789abcdef");
+    }
+
+    #[test]
+    fn venue_data_reads_into_both_models() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("GET /webs", "webs.http"),
+        ]);
+        let skedda = client(&server);
+
+        let webs = skedda.get_booking_data().unwrap();
+        assert_eq!(Skedda::space_details(&webs).len(), 6);
+        assert_eq!(Skedda::location_space_ids(&webs, "Midtown"), ["201", "202"]);
+
+        let body = skedda.get_booking_data_body().unwrap();
+        let summary = WebsSummary::parse(&body).unwrap();
+        assert_eq!(summary.bookings.len(), webs.bookings.len());
+
+        // The form token from the first fetch is kept for the second.
+        assert_eq!(
+            server.requests(),
+            ["GET /booking", "GET /webs", "GET /webs"]
+        );
+    }
+
+    #[test]
+    fn turned_away_token_is_fetched_again_once() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("GET /webs", "webs_forbidden.http"),
+        ]);
+        let skedda = client(&server);
+        // A token just fetched isn't stale, so there's nothing to try again with.
+        skedda.get_booking_data().unwrap_err();
+        assert_eq!(server.requests(), ["GET /booking", "GET /webs"]);
+
+        skedda.get_booking_data().unwrap_err();
+        assert_eq!(
+            server.requests(),
+            [
+                "GET /booking",
+                "GET /webs",
+                "GET /webs",
+                "GET /booking",
+                "GET /webs"
+            ]
+        );
+    }
+
+    #[test]
+    fn rate_limited_requests_are_tried_again_until_the_retries_run_out() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("GET /webs", "rate_limited.http"),
+        ]);
+        let err = client(&server).get_booking_data().unwrap_err();
+        assert!(
+            matches!(
+                err,
+                SkeddaError::RateLimited {
+                    retry_after: Some(wait)
+                } if wait == Duration::from_secs(1)
+            ),
+            "unexpected error: {err}"
+        );
+        assert_eq!(
+            server.requests(),
+            ["GET /booking", "GET /webs", "GET /webs", "GET /webs"]
+        );
+    }
+
+    #[test]
+    fn login_page_instead_of_venue_data_is_named() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("GET /webs", "webs_login_page.http"),
+        ]);
+        let err = client(&server).get_booking_data().unwrap_err();
+        assert!(
+            err.to_string().contains("Log in - Skedda"),
+            "unexpected error: {err:#}"
+        );
+    }
+
+    #[test]
+    fn lapsed_session_logs_in_with_the_configured_account() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("GET /webs", "webs_logged_out.http"),
+            ("GET /account/login", "login_page.http"),
+            ("POST /account/login", "login_rejected.http"),
+        ]);
+        let skedda = isolated(&VenueConfig {
+            email: Some("ada@example.com".to_string()),
+            password: Some("wrong".to_string()),
+            ..VenueConfig {
+                base_url: server.base_url.clone(),
+                ..VenueConfig::switchyards()
+            }
+        });
+        let err = skedda.get_booking_data().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("didn't accept the email and password"),
+            "unexpected error: {err:#}"
+        );
+        assert!(matches!(err, SkeddaError::AuthRequired(_)));
+        assert!(
+            server
+                .requests()
+                .contains(&"POST /account/login".to_string())
+        );
+    }
+
+    #[test]
+    fn keep_alive_says_when_the_session_has_run_out() {
+        let server = ReplayServer::start(&[("GET /booking", "booking_page.http")]);
+        client(&server).keep_alive().unwrap();
+
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_logged_out.http"),
+            ("GET /account/login", "login_page.http"),
+        ]);
+        let err = client(&server).keep_alive().unwrap_err();
+        assert!(
+            err.to_string().contains("no account to log in with"),
+            "unexpected error: {err:#}"
+        );
+    }
+
+    #[test]
+    fn maintenance_page_is_an_outage() {
+        let server = ReplayServer::start(&[("GET /booking", "maintenance.http")]);
+        let err = client(&server).get_booking_data().unwrap_err();
+        let SkeddaError::Outage(outage) = err else {
+            panic!("not an outage: {err:?}");
+        };
+        assert_eq!(outage.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(outage.retry_after, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn cloudflare_check_is_a_challenge() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("GET /webs", "cloudflare_challenge.http"),
+        ]);
+        let err = client(&server).get_booking_data().unwrap_err();
+        let SkeddaError::Challenge(challenge) = err else {
+            panic!("not a challenge: {err:?}");
+        };
+        assert_eq!(challenge.status, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn bookings_are_made_or_refused_with_a_reason() {
+        let request = BookingRequest::new(
+            "1",
+            &["103".to_string()],
+            None,
+            datetime!(2024-05-01 14:00),
+            datetime!(2024-05-01 15:00),
+        );
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("POST /bookings", "booking_created.http"),
+        ]);
+        let created = client(&server).create_booking(&request).unwrap();
+        assert_eq!((created.id.as_str(), created.price), ("9005", Some(12.5)));
+
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("POST /bookings", "booking_rejected.http"),
+        ]);
+        let err = client(&server).create_booking(&request).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Skedda refused the booking: This booking conflicts with an existing booking."
+        );
+        assert!(matches!(err, SkeddaError::BookingConflict(_)));
+    }
+
+    #[test]
+    fn cancellations_report_each_booking() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("DELETE /bookings/9001", "cancel_booking.http"),
+            ("DELETE /bookings/9999", "cancel_booking_not_found.http"),
+        ]);
+        let results = client(&server)
+            .cancel_bookings(&["9001".to_string(), "9999".to_string()], None)
+            .unwrap();
+        assert_eq!(results[0].0, "9001");
+        assert!(results[0].1.is_ok());
+        assert_eq!(results[1].0, "9999");
+        assert!(results[1].1.is_err());
+    }
+
+    #[test]
+    fn cancelling_one_booking_says_when_it_failed() {
+        let server = ReplayServer::start(&[
+            ("GET /booking", "booking_page.http"),
+            ("DELETE /bookings/9001", "cancel_booking.http"),
+            ("DELETE /bookings/9999", "cancel_booking_not_found.http"),
+        ]);
+        let skedda = client(&server);
+        skedda.cancel_booking("9001").unwrap();
+        let err = skedda.cancel_booking("9999").unwrap_err();
+        assert!(
+            err.to_string().starts_with("Failed to cancel booking 9999"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn unreachable_venue_falls_back_to_its_last_data() {
+        let home = tempfile::tempdir().unwrap();
+        let paths = Paths::under(home.path());
+        // Nothing listens on port 1.
+        let venue = VenueConfig {
+            base_url: "http://127.0.0.1:1".to_string(),
+            ..VenueConfig::switchyards()
+        };
+        let last_known = |err| Skedda::last_known_under(Some(&paths), &venue, err);
+        let unreachable = || {
+            let refused = Client::new().get(&venue.base_url).send().unwrap_err();
+            SkeddaError::request("Failed to connect to venue", refused)
+        };
+        assert!(matches!(
+            last_known(unreachable()),
+            Err(SkeddaError::Network(_))
+        ));
+
+        let path = paths.offline_data(&venue.key());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, fixtures::webs("small_venue.json")).unwrap();
+        let (webs, _) = last_known(unreachable()).unwrap();
+        assert_eq!(Skedda::location_names(&webs), ["Decatur", "Midtown"]);
+        // Only the venue being out of reach is papered over.
+        assert!(matches!(
+            last_known(SkeddaError::Other("the venue said no".to_string())),
+            Err(SkeddaError::Other(_))
+        ));
+    }
+}
+
+/// Run with `cargo test --features live-tests`; these need the network and a reachable venue.
+#[cfg(all(test, feature = "live-tests"))]
+mod live_tests {
+    use super::*;
+    use crate::models::WebsSummary;
+
+    fn venue() -> Skedda {
+        Skedda::new(&VenueConfig::switchyards())
+    }
+
+    #[test]
+    fn booking_page_has_a_csrf_token() {
+        let token = venue().get_booking_page().unwrap();
+        assert!(!token.is_empty());
+    }
+
+    #[test]
+    fn venue_data_parses_into_both_models() {
+        let skedda = venue();
+        let body = skedda.get_booking_data_body().unwrap();
+        let full: WebsResponse = serde_json::from_slice(&body).unwrap();
+        let summary = WebsSummary::parse(&body).unwrap();
+        assert!(!full.spaces.is_empty());
+        assert_eq!(full.spaces.len(), summary.spaces.len());
+        assert_eq!(full.bookings.len(), summary.bookings.len());
+    }
 }
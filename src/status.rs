@@ -0,0 +1,360 @@
+use std::{
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use color_eyre::eyre::eyre;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{Event as CrosstermEvent, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Alignment, Constraint, Flex, Layout, Rect},
+    style::{Color, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph},
+};
+use time::{PrimitiveDateTime, Time};
+
+use crate::{
+    clock,
+    config::{Config, VenueConfig},
+    event::{AppEvent, Event, EventHandler},
+    grid,
+    models::WebsResponse,
+    skedda::Skedda,
+};
+
+/// How often the board fetches the venue again.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Whether a space is in use right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpaceState {
+    /// Free, until the next booking today if there is one.
+    Free { until: Option<PrimitiveDateTime> },
+    /// Booked, until the end of the run of back-to-back bookings under way.
+    Busy { until: PrimitiveDateTime },
+}
+
+/// One row of the status board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceStatus {
+    pub name: String,
+    pub state: SpaceState,
+}
+
+/// Each of the location's spaces and whether it's in use at `now`, in the venue's order.
+pub fn board(webs: &WebsResponse, location: &str, now: PrimitiveDateTime) -> Vec<SpaceStatus> {
+    let names = Skedda::space_names(webs);
+    let bookings = Skedda::bookings(webs);
+    let tomorrow = now.date().midnight() + time::Duration::DAY;
+    Skedda::location_space_ids(webs, location)
+        .into_iter()
+        .filter_map(|space_id| {
+            let name = names.get(&space_id)?.clone();
+            let mut spans: Vec<(PrimitiveDateTime, PrimitiveDateTime)> = bookings
+                .iter()
+                .filter(|booking| booking.end > now && booking.space_ids.contains(&space_id))
+                .map(|booking| (booking.start, booking.end))
+                .collect();
+            spans.sort();
+            let state = match spans.first() {
+                Some(&(start, end)) if start <= now => {
+                    let until = spans
+                        .iter()
+                        .skip(1)
+                        .try_fold(end, |until, &(start, end)| {
+                            if start <= until {
+                                Ok(until.max(end))
+                            } else {
+                                Err(until)
+                            }
+                        })
+                        .unwrap_or_else(|until| until);
+                    SpaceState::Busy { until }
+                }
+                next => SpaceState::Free {
+                    until: next
+                        .map(|&(start, _)| start)
+                        .filter(|&start| start < tomorrow),
+                },
+            };
+            Some(SpaceStatus { name, state })
+        })
+        .collect()
+}
+
+/// Shows a read-only board of the location's spaces, refreshed every minute, until `q` or
+/// `Esc`. Fullscreen drops the frame and help so the board fills a wall display; `at` shows
+/// the board for that time today, or the start of that named window, rather than now.
+pub fn run(
+    config: &Config,
+    venue: Option<&str>,
+    location: &str,
+    fullscreen: bool,
+    at: Option<&str>,
+) -> color_eyre::Result<()> {
+    let at = at
+        .map(|at| {
+            config
+                .start_time(at)
+                .map_err(|reason| eyre!("--at {at:?}: {reason}"))
+        })
+        .transpose()?;
+    let venue = match venue {
+        Some(name) => config
+            .venue(name)
+            .ok_or_else(|| eyre!("no venue named {name:?} in config"))?,
+        None => config
+            .find_location(location)
+            .map(|(venue, _)| &config.venues[venue])
+            .ok_or_else(|| eyre!("no venue in config has a location named {location:?}"))?,
+    };
+    let location = venue
+        .locations
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(location))
+        .ok_or_else(|| eyre!("{} has no location named {location:?}", venue.name))?
+        .clone();
+
+    let terminal = ratatui::init();
+    let result = StatusBoard::new(venue, location, fullscreen, at).run(terminal);
+    ratatui::restore();
+    result
+}
+
+struct StatusBoard {
+    venue: VenueConfig,
+    location: String,
+    fullscreen: bool,
+    /// The time of day shown, when it isn't now.
+    at: Option<Time>,
+    events: EventHandler,
+    webs: Option<WebsResponse>,
+    /// When the shown data arrived.
+    loaded_at: Option<Instant>,
+    /// When the last fetch went out, while it's in flight or after it failed.
+    fetched_at: Option<Instant>,
+    error: Option<String>,
+    running: bool,
+}
+
+impl StatusBoard {
+    fn new(venue: &VenueConfig, location: String, fullscreen: bool, at: Option<Time>) -> Self {
+        Self {
+            venue: venue.clone(),
+            location,
+            fullscreen,
+            at,
+            events: EventHandler::new(),
+            webs: None,
+            loaded_at: None,
+            fetched_at: None,
+            error: None,
+            running: true,
+        }
+    }
+
+    fn run(mut self, mut terminal: DefaultTerminal) -> color_eyre::Result<()> {
+        self.fetch();
+        while self.running {
+            terminal.draw(|frame| self.render(frame))?;
+            match self.events.next()? {
+                Event::Tick => {
+                    if self
+                        .fetched_at
+                        .is_none_or(|at| at.elapsed() >= REFRESH_INTERVAL)
+                        && Skedda::new(&self.venue).may_poll()
+                    {
+                        self.fetch();
+                    }
+                }
+                Event::Crossterm(CrosstermEvent::Key(key)) if key.kind == KeyEventKind::Press => {
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => self.running = false,
+                        KeyCode::Char('c' | 'C') if key.modifiers == KeyModifiers::CONTROL => {
+                            self.running = false
+                        }
+                        _ => {}
+                    }
+                }
+                Event::App(AppEvent::BookingDataLoaded(_, webs, ..)) => {
+                    self.webs = Some(*webs);
+                    self.loaded_at = Some(Instant::now());
+                    self.error = None;
+                }
+                Event::App(AppEvent::RequestFailed(_, err)) => self.error = Some(err.to_string()),
+                Event::Crossterm(_) | Event::App(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn fetch(&mut self) {
+        self.fetched_at = Some(Instant::now());
+        let sender = self.events.sender();
+        let config = self.venue.clone();
+        thread::spawn(move || {
+            let event = match Skedda::new(&config).get_booking_data() {
+                Ok(webs) => AppEvent::BookingDataLoaded(0, Box::new(webs), None, SystemTime::now()),
+                Err(err) => AppEvent::RequestFailed(0, err),
+            };
+            let _ = sender.send(Event::App(event));
+        });
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let now = match self.at {
+            Some(at) => clock::now().replace_time(at),
+            None => clock::now(),
+        };
+        let title = format!(
+            "{} · {} · {}",
+            self.location,
+            self.venue.name,
+            grid::format_time(now.time())
+        );
+        let area = if self.fullscreen {
+            let [title_area, area] =
+                Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(frame.area());
+            frame.render_widget(
+                Paragraph::new(Line::from(title).bold()).alignment(Alignment::Center),
+                title_area,
+            );
+            area
+        } else {
+            let block = Block::bordered()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded);
+            let area = block.inner(frame.area());
+            frame.render_widget(block, frame.area());
+            area
+        };
+        let [board_area, footer_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+        self.render_board(frame, board_area, now);
+
+        let mut footer = Vec::new();
+        if let Some(error) = &self.error {
+            footer.push(Span::from(format!("Couldn't refresh: {error}")).fg(Color::Red));
+        } else if let Some(at) = self.loaded_at {
+            footer.push(
+                Span::from(match at.elapsed().as_secs() / 60 {
+                    0 => "Updated just now".to_string(),
+                    minutes => format!("Updated {minutes}m ago"),
+                })
+                .fg(Color::DarkGray),
+            );
+        }
+        if !self.fullscreen {
+            if !footer.is_empty() {
+                footer.push(Span::from(" · ").fg(Color::DarkGray));
+            }
+            footer.push(Span::from("q to quit").fg(Color::DarkGray));
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(footer)).alignment(Alignment::Center),
+            footer_area,
+        );
+    }
+
+    fn render_board(&self, frame: &mut Frame, area: Rect, now: PrimitiveDateTime) {
+        let Some(webs) = &self.webs else {
+            let message = if self.error.is_some() {
+                "No data yet"
+            } else {
+                "Loading…"
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(message).fg(Color::DarkGray))
+                    .alignment(Alignment::Center),
+                area,
+            );
+            return;
+        };
+        let board = board(webs, &self.location, now);
+        let width = board
+            .iter()
+            .map(|space| space.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = board
+            .iter()
+            .flat_map(|space| {
+                let (color, state) = match space.state {
+                    SpaceState::Free { until: None } => (Color::Green, "Free".to_string()),
+                    SpaceState::Free { until: Some(until) } => (
+                        Color::Green,
+                        format!("Free until {}", grid::format_time(until.time())),
+                    ),
+                    SpaceState::Busy { until } => (
+                        Color::Red,
+                        format!("Busy until {}", grid::format_time(until.time())),
+                    ),
+                };
+                let row = Line::from(vec![
+                    Span::from("● ").fg(color),
+                    Span::from(format!("{:<width$}   ", space.name)).bold(),
+                    Span::from(format!("{state:<16}")).fg(color),
+                ]);
+                // A blank line between rows reads better from across the room.
+                let gap = self.fullscreen.then(Line::default);
+                std::iter::once(row).chain(gap)
+            })
+            .collect();
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::fixtures;
+
+    fn states(now: PrimitiveDateTime) -> Vec<(String, SpaceState)> {
+        let webs = fixtures::webs_response("small_venue.json");
+        board(&webs, "Decatur", now)
+            .into_iter()
+            .map(|space| (space.name, space.state))
+            .collect()
+    }
+
+    #[test]
+    fn shows_what_is_in_use_now() {
+        assert_eq!(
+            states(datetime!(2024-05-01 9:30)),
+            [
+                (
+                    "Desk 1".to_string(),
+                    SpaceState::Busy {
+                        until: datetime!(2024-05-01 12:00)
+                    }
+                ),
+                ("Desk 2".to_string(), SpaceState::Free { until: None }),
+                (
+                    "Boardroom".to_string(),
+                    SpaceState::Busy {
+                        until: datetime!(2024-05-01 10:00)
+                    }
+                ),
+                (
+                    "Phone Booth".to_string(),
+                    SpaceState::Free {
+                        until: Some(datetime!(2024-05-01 11:30))
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn bookings_on_later_days_leave_a_space_free_today() {
+        let states = states(datetime!(2024-04-30 17:00));
+        assert_eq!(states[3].1, SpaceState::Free { until: None });
+    }
+}
@@ -0,0 +1,109 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::scheduler;
+
+/// The most toasts shown at once; a new one pushes out the oldest.
+const MAX_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// How long a toast stays up; the worse the news, the longer it has to be read.
+    fn lifetime(self) -> Duration {
+        match self {
+            Self::Info | Self::Success => Duration::from_secs(3),
+            Self::Warning => Duration::from_secs(5),
+            Self::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+/// A short message about something that happened in the background, shown in a corner
+/// for a few seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast {
+    pub severity: Severity,
+    pub message: String,
+    /// Ticks left before it goes.
+    ticks_left: u64,
+}
+
+/// The toasts on screen, oldest first.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Shows a toast. Repeating the newest one restarts its time rather than stacking a copy.
+    pub fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        let message = message.into();
+        if self
+            .toasts
+            .back()
+            .is_some_and(|last| last.severity == severity && last.message == message)
+        {
+            self.toasts.pop_back();
+        }
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            severity,
+            message,
+            ticks_left: scheduler::ticks_in(severity.lifetime()),
+        });
+    }
+
+    /// Counts down one tick, dropping toasts whose time is up.
+    pub fn tick(&mut self) {
+        for toast in &mut self.toasts {
+            toast.ticks_left = toast.ticks_left.saturating_sub(1);
+        }
+        self.toasts.retain(|toast| toast.ticks_left > 0);
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Toast> {
+        self.toasts.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::TICK_FPS;
+
+    fn messages(toasts: &Toasts) -> Vec<&str> {
+        toasts.iter().map(|toast| toast.message.as_str()).collect()
+    }
+
+    #[test]
+    fn expire_after_their_lifetime() {
+        let mut toasts = Toasts::default();
+        toasts.push(Severity::Success, "Session refreshed");
+        toasts.push(Severity::Error, "Couldn't refresh");
+        for _ in 0..3 * TICK_FPS as usize {
+            toasts.tick();
+        }
+        assert_eq!(messages(&toasts), ["Couldn't refresh"]);
+        for _ in 0..5 * TICK_FPS as usize {
+            toasts.tick();
+        }
+        assert!(messages(&toasts).is_empty());
+    }
+
+    #[test]
+    fn stack_up_to_a_limit_without_repeats() {
+        let mut toasts = Toasts::default();
+        for message in ["a", "b", "b", "c", "d", "e"] {
+            toasts.push(Severity::Info, message);
+        }
+        assert_eq!(messages(&toasts), ["b", "c", "d", "e"]);
+    }
+}
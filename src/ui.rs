@@ -1,23 +1,394 @@
+use image::{DynamicImage, imageops::FilterType};
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Stylize},
-    text::{Line, Text},
-    widgets::{Block, BorderType, Clear, List, Paragraph},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Clear, List, Padding, Paragraph, Widget, Wrap},
 };
 
-use crate::{app::{App, ViewState}, skedda::Skedda};
+use std::time::{Duration, Instant, SystemTime};
+use time::{format_description::BorrowedFormatItem, macros::format_description};
+
+use crate::{
+    app::{App, FormField, LocationRow, Prompt, SpaceRow, ViewState},
+    batch::ItemState,
+    calendar, clock, grid, inspector, logging, markup,
+    plan::PlanEntry,
+    rules,
+    session::SessionHealth,
+    toast::Severity,
+};
 
 /// Renders the user interface.
 pub fn render(app: &mut App, frame: &mut Frame) {
+    let breadcrumb = app.breadcrumb();
+    let [breadcrumb_area, area, message_area, status_area] = Layout::vertical([
+        Constraint::Length(u16::from(!breadcrumb.is_empty())),
+        Constraint::Min(0),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    render_breadcrumb(&breadcrumb, frame, breadcrumb_area);
+
     match app.current_view {
-        ViewState::LocationSelection => render_location_selection(app, frame),
-        ViewState::BookingForm => render_booking_form(app, frame),
-        ViewState::Confirmation => render_confirmation(app, frame),
+        ViewState::LocationSelection => render_location_selection(app, frame, area),
+        ViewState::BookingForm => render_booking_form(app, frame, area),
+        ViewState::Confirmation => render_confirmation(app, frame, area),
+        ViewState::SpaceBookings => render_space_bookings(app, frame, area),
+        ViewState::Overview => render_overview(app, frame, area),
+        ViewState::WeekPlan => render_week_plan(app, frame, area),
+        ViewState::MyBookings => render_my_bookings(app, frame, area),
+        ViewState::MonthCalendar => render_month_calendar(app, frame, area),
+        ViewState::Calendar => render_week_calendar(app, frame, area),
+        ViewState::BatchResults => render_batch_results(app, frame, area),
+        ViewState::VenuePicker => {
+            render_location_selection(app, frame, area);
+            render_venue_picker(app, frame, area);
+        }
+    }
+    if app.show_inspector {
+        render_inspector(frame, area);
+    }
+    if let Some(scroll) = app.log_viewer {
+        render_log_viewer(frame, area, scroll);
+    }
+    render_toasts(app, frame, area);
+    render_message_line(app, frame, message_area);
+    render_status_bar(app, frame, status_area);
+}
+
+/// What the venues are busy with, or how the last call to one went, on the left; the keys
+/// that work in the current view on the right, as many as fit beside it.
+fn render_message_line(app: &App, frame: &mut Frame, area: Rect) {
+    let in_flight = inspector::in_flight();
+    let activity = if let Some(name) = in_flight.first() {
+        let others = match in_flight.len() {
+            1 => String::new(),
+            calls => format!(" (+{})", calls - 1),
+        };
+        Line::from(format!(
+            "{} {}…{others}",
+            app.scheduler.spinner(),
+            call_status(name, false)
+        ))
+    } else {
+        match inspector::latest() {
+            Some(call) => match call.error {
+                None => Line::from(format!("✓ {}", call_status(call.name, true))).fg(Color::Green),
+                Some(err) => Line::from(format!("✗ Error: {err}")).fg(Color::Red),
+            },
+            None => Line::default(),
+        }
+    };
+
+    let room = usize::from(area.width).saturating_sub(activity.width() + 2);
+    let mut keys = app.status_keys();
+    let keys = loop {
+        let mut spans = Vec::new();
+        for (key, action) in &keys {
+            if !spans.is_empty() {
+                spans.push(Span::from(" · "));
+            }
+            spans.push(Span::from(*key).bold());
+            spans.push(Span::from(format!(" {action}")));
+        }
+        let line = Line::from(spans).fg(Color::DarkGray);
+        if line.width() <= room || keys.pop().is_none() {
+            break line;
+        }
+    };
+    frame.render_widget(Paragraph::new(activity), area);
+    frame.render_widget(Paragraph::new(keys).alignment(Alignment::Right), area);
+}
+
+/// A call to a venue under way, e.g. "Fetching venue data", or done, e.g. "Venue data
+/// fetched".
+fn call_status(name: &str, done: bool) -> String {
+    let (under_way, finished) = match name {
+        "venue data" => ("Fetching venue data", "Venue data fetched"),
+        "booking" => ("Booking", "Booking created"),
+        "cancellation" => ("Cancelling", "Cancelled"),
+        "session refresh" => ("Refreshing session", "Session refreshed"),
+        "keep-alive" => ("Keeping session alive", "Session kept alive"),
+        "warm-up" => ("Connecting", "Connected"),
+        other => return other.to_string(),
+    };
+    if done { finished } else { under_way }.to_string()
+}
+
+/// The latest calls to the venues, newest first, with how long each of their requests took.
+fn render_inspector(frame: &mut Frame, area: Rect) {
+    let popup_area = centered_rect(80, 80, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::bordered()
+        .title("Requests")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let [list_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+
+    let calls = inspector::recent();
+    let mut lines = Vec::new();
+    for call in &calls {
+        let (mark, color) = if call.error.is_none() {
+            ("✓", Color::Green)
+        } else {
+            ("✗", Color::Red)
+        };
+        lines.push(Line::from(vec![
+            Span::from(format!("{mark} ")).fg(color),
+            Span::from(format!(
+                "{}  ",
+                call.finished_at
+                    .time()
+                    .format(format_description!("[hour]:[minute]:[second]"))
+                    .unwrap_or_default()
+            ))
+            .fg(Color::DarkGray),
+            Span::from(call.name).bold(),
+            Span::from(format!(" · {}  ", call.venue)),
+            Span::from(format_elapsed(call.total)).fg(Color::Yellow),
+        ]));
+        for request in &call.requests {
+            let outcome = match &request.outcome {
+                Ok(status) => Span::from(status.to_string()),
+                Err(err) => Span::from(err.clone()).fg(Color::Red),
+            };
+            let mut spans = vec![
+                Span::from(format!("    {:<6} {:<24} ", request.method, request.path)),
+                outcome,
+                Span::from(format!(
+                    "  first byte {}",
+                    format_elapsed(request.first_byte)
+                )),
+            ];
+            if !request.queued.is_zero() {
+                spans.push(
+                    Span::from(format!("  queued {}", format_elapsed(request.queued)))
+                        .fg(Color::DarkGray),
+                );
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from("No requests yet").fg(Color::DarkGray));
+    }
+    frame.render_widget(Paragraph::new(lines), list_area);
+    frame.render_widget(
+        Paragraph::new(
+            Line::from(format!(
+                "Last {} calls, newest first · first byte includes DNS and connecting · Esc close",
+                inspector::RECENT_CALLS
+            ))
+            .fg(Color::DarkGray),
+        )
+        .alignment(Alignment::Center),
+        help_area,
+    );
+}
+
+/// The latest lines of the log, `scroll` lines back from the newest, colored by level.
+fn render_log_viewer(frame: &mut Frame, area: Rect, scroll: usize) {
+    let popup_area = centered_rect(90, 80, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::bordered()
+        .title("Log")
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let [list_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+
+    let recent = logging::recent();
+    let end = recent.len().saturating_sub(scroll);
+    let start = end.saturating_sub(usize::from(list_area.height));
+    let mut lines: Vec<Line> = recent[start..end]
+        .iter()
+        .map(|line| {
+            let color = match line.split_whitespace().nth(1) {
+                Some("ERROR") => Color::Red,
+                Some("WARN") => Color::Yellow,
+                Some("DEBUG" | "TRACE") => Color::DarkGray,
+                _ => Color::Reset,
+            };
+            Line::from(line.as_str()).fg(color)
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from("Nothing logged yet at this level").fg(Color::DarkGray));
+    }
+    frame.render_widget(Paragraph::new(lines), list_area);
+    let position = if scroll == 0 {
+        "newest at the bottom".to_string()
+    } else {
+        format!("{scroll} lines back")
+    };
+    frame.render_widget(
+        Paragraph::new(
+            Line::from(format!(
+                "Last {} lines of syres.log · {position} · Esc close",
+                logging::RECENT_LINES
+            ))
+            .fg(Color::DarkGray),
+        )
+        .alignment(Alignment::Center),
+        help_area,
+    );
+}
+
+/// "180ms" under a second, "1.24s" from there.
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
+
+/// Stacks the toasts in the bottom right corner, newest at the bottom.
+fn render_toasts(app: &App, frame: &mut Frame, area: Rect) {
+    const MAX_WIDTH: u16 = 48;
+
+    let mut bottom = area.bottom();
+    for toast in app.toasts.iter().rev() {
+        let (color, icon) = match toast.severity {
+            Severity::Info => (Color::Blue, "ℹ"),
+            Severity::Success => (Color::Green, "✓"),
+            Severity::Warning => (Color::Yellow, "!"),
+            Severity::Error => (Color::Red, "✗"),
+        };
+        let text = format!("{icon} {}", toast.message);
+        let width = (text.chars().count() as u16 + 4)
+            .min(MAX_WIDTH)
+            .min(area.width);
+        let height = wrapped_lines(&text, usize::from(width.saturating_sub(4))) + 2;
+        if bottom < area.y + height {
+            break;
+        }
+        bottom -= height;
+        let toast_area = Rect::new(area.right() - width, bottom, width, height);
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(
+            Paragraph::new(text).wrap(Wrap { trim: true }).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .border_style(color)
+                    .padding(Padding::horizontal(1)),
+            ),
+            toast_area,
+        );
+    }
+}
+
+/// "Switchyards › Decatur › Room 2 › Tue Mar 4" across the top of nested views, with the
+/// innermost part in bold.
+fn render_breadcrumb(crumbs: &[String], frame: &mut Frame, area: Rect) {
+    let Some((last, outer)) = crumbs.split_last() else {
+        return;
+    };
+    let mut spans: Vec<Span> = Vec::new();
+    for crumb in outer {
+        spans.push(Span::from(crumb.as_str()).fg(Color::DarkGray));
+        spans.push(Span::from(" › ").fg(Color::DarkGray));
+    }
+    spans.push(Span::from(last.as_str()).bold());
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn render_status_bar(app: &App, frame: &mut Frame, area: Rect) {
+    render_next_booking(app, frame, area);
+    let Some(venue) = app.current_venue() else {
+        return;
+    };
+    let session = &app.sessions[venue];
+    let (color, label) = match session.expires_at {
+        None if !session.loaded => (Color::DarkGray, "checking session…".to_string()),
+        None => (Color::DarkGray, "no session".to_string()),
+        Some(expires_at) => {
+            let color = match SessionHealth::of(expires_at) {
+                SessionHealth::Valid => Color::Green,
+                SessionHealth::Expiring => Color::Yellow,
+                SessionHealth::Expired => Color::Red,
+            };
+            let label = match expires_at.duration_since(SystemTime::now()) {
+                Ok(remaining) => format!("session {}", format_remaining(remaining)),
+                Err(_) => "session expired".to_string(),
+            };
+            let label = if session.refreshing {
+                format!("{label} (refreshing)")
+            } else {
+                label
+            };
+            (color, label)
+        }
+    };
+    let label = if app.venues.len() > 1 {
+        format!("{}: {label}", app.venues[venue].name)
+    } else {
+        label
+    };
+
+    let mut spans = Vec::new();
+    if let Some(fetched_at) = app.offline[venue] {
+        spans.push(Span::from(format!("{} ", offline_label(fetched_at))).fg(Color::Red));
+    }
+    if let Some(skew) = clock::notable_skew() {
+        spans.push(Span::from(format!("clock {} ", clock::describe_skew(skew))).fg(Color::Red));
+    }
+    spans.extend([Span::from("● ").fg(color), Span::from(label)]);
+    frame.render_widget(
+        Paragraph::new(Line::from(spans)).alignment(Alignment::Right),
+        area,
+    );
+}
+
+/// "Next: Room 2 @ Midtown in 1h 20m", or what's booked right now, on the left of the
+/// status bar.
+fn render_next_booking(app: &App, frame: &mut Frame, area: Rect) {
+    let Some((venue, booking)) = app.next_booking() else {
+        return;
+    };
+    let place = app.booking_place(venue, booking);
+    let now = clock::now();
+    let line = if booking.start <= now {
+        Line::from(format!(
+            "Now: {place} until {}",
+            grid::format_time(booking.end.time())
+        ))
+        .fg(Color::Green)
+    } else {
+        let starts_in = (booking.start - now).unsigned_abs();
+        let when = if booking.start.date() == now.date() {
+            format!("in {}", format_remaining(starts_in))
+        } else {
+            format!(
+                "{} {}",
+                booking.start.weekday(),
+                grid::format_time(booking.start.time())
+            )
+        };
+        Line::from(format!("Next: {place} {when}"))
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn format_remaining(remaining: Duration) -> String {
+    let minutes = remaining.as_secs() / 60;
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes}m")
     }
 }
 
-fn render_location_selection(app: &mut App, frame: &mut Frame) {
+fn render_location_selection(app: &mut App, frame: &mut Frame, area: Rect) {
     let locations_list = List::new(app.locations.clone())
         .block(
             Block::default()
@@ -33,7 +404,11 @@ fn render_location_selection(app: &mut App, frame: &mut Frame) {
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Rounded);
 
-    let text = format!("Make a booking at Switchyards");
+    let venue = app
+        .active_venue
+        .or((app.venues.len() == 1).then_some(0))
+        .map_or("your venues", |venue| app.venues[venue].name.as_str());
+    let text = format!("Make a booking at {venue}");
 
     let paragraph = Paragraph::new(text)
         .block(block)
@@ -41,29 +416,539 @@ fn render_location_selection(app: &mut App, frame: &mut Frame) {
         .bg(Color::Black)
         .centered();
 
-    frame.render_widget(paragraph, frame.area());
-    frame.render_stateful_widget(locations_list, frame.area(), &mut app.list_state);
+    frame.render_widget(paragraph, area);
+    app.list_page = usize::from(area.height.saturating_sub(1));
+    frame.render_stateful_widget(locations_list, area, &mut app.list_state);
+}
+
+/// Lists every location with how many of its spaces are free right now.
+fn render_overview(app: &mut App, frame: &mut Frame, area: Rect) {
+    let space_type = match app.go_now.space_type.as_str() {
+        "" => "space",
+        space_type => space_type,
+    };
+    let title = if app.overview_go_now {
+        format!(
+            "Where to go for a {space_type} (next {}h)",
+            app.go_now.hours
+        )
+    } else {
+        "Available now".to_string()
+    };
+    let block = Block::bordered()
+        .title(title)
+        .title_top(data_age(app))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [list_area, status_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    let grouped = app.active_venue.is_none() && app.venues.len() > 1;
+    let width = app
+        .overview
+        .iter()
+        .map(|location| {
+            location.location.chars().count()
+                + if grouped {
+                    app.venues[location.venue].name.chars().count() + 3
+                } else {
+                    0
+                }
+        })
+        .max()
+        .unwrap_or(0);
+    let items: Vec<_> = app
+        .overview
+        .iter()
+        .map(|location| {
+            let name = if grouped {
+                format!(
+                    "{} · {}",
+                    app.venues[location.venue].name, location.location
+                )
+            } else {
+                location.location.clone()
+            };
+            let color = if location.free_matching > 0 {
+                Color::Green
+            } else if location.free > 0 {
+                Color::Yellow
+            } else {
+                Color::DarkGray
+            };
+            let rooms = match (app.overview_go_now, location.free_matching) {
+                (true, matching) => format!("{matching} {space_type} free"),
+                (false, 0) => "no meeting rooms".to_string(),
+                (false, 1) => "1 meeting room".to_string(),
+                (false, rooms) => format!("{rooms} meeting rooms"),
+            };
+            Line::from(vec![
+                Span::from("● ").fg(color),
+                Span::from(format!("{name:<width$}  ")),
+                Span::from(format!(
+                    "{:>7} free  {rooms}",
+                    format!("{}/{}", location.free, location.spaces)
+                )),
+            ])
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Color::Yellow)
+        .highlight_symbol(">> ");
+    app.list_page = usize::from(list_area.height);
+    frame.render_stateful_widget(list, list_area, &mut app.overview_state);
+
+    let status = if !app.overview_pending.is_empty() {
+        let spinner = app.scheduler.spinner();
+        Line::from(match app.overview_pending.len() {
+            1 => format!("{spinner} Checking 1 venue…"),
+            venues => format!("{spinner} Checking {venues} venues…"),
+        })
+        .fg(Color::DarkGray)
+    } else if app.overview_go_now && app.overview.is_empty() {
+        Line::from(format!(
+            "No {space_type} is free anywhere for the next {}h",
+            app.go_now.hours
+        ))
+        .fg(Color::Yellow)
+    } else if !app.overview_errors.is_empty() {
+        let venues: Vec<&str> = app
+            .overview_errors
+            .keys()
+            .map(|venue| app.venues[*venue].name.as_str())
+            .collect();
+        Line::from(format!("Couldn't check {}", venues.join(", "))).fg(Color::Red)
+    } else {
+        Line::from("Enter to book · Esc to go back")
+    };
+    frame.render_widget(
+        Paragraph::new(status).alignment(Alignment::Center),
+        status_area,
+    );
+}
+
+/// The next seven days side by side: my bookings, and the gaps between them to book into.
+fn render_week_plan(app: &mut App, frame: &mut Frame, area: Rect) {
+    let block = Block::bordered()
+        .title("Week ahead")
+        .title_top(data_age(app))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [columns_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    let week = app.week_plan();
+    let columns = Layout::horizontal(vec![Constraint::Ratio(1, week.len() as u32); week.len()])
+        .split(columns_area);
+    for (index, (day, area)) in week.iter().zip(columns.iter()).enumerate() {
+        let selected = index == app.plan_day;
+        let items: Vec<Text> = day
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                PlanEntry::Booking(_, booking) => Text::from(vec![
+                    Line::from(grid::format_time(booking.start.time())).bold(),
+                    Line::from(booking.space.clone()),
+                    Line::from(booking.location.clone().unwrap_or_default()).fg(Color::DarkGray),
+                ]),
+                PlanEntry::Gap { start, end } => Text::from(Line::from(format!(
+                    "{}–{}",
+                    grid::format_time(start.time()),
+                    grid::format_time(end.time())
+                )))
+                .fg(Color::Green),
+            })
+            .collect();
+        let title = Line::from(format!(
+            "{:.3} {}",
+            day.date.weekday().to_string(),
+            day.date.day()
+        ));
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(if selected { title.yellow() } else { title })
+                    .border_type(BorderType::Rounded),
+            )
+            .highlight_style(Color::Yellow);
+        if selected {
+            app.list_page = usize::from(area.height.saturating_sub(2) / 3).max(1);
+            frame.render_stateful_widget(list, *area, &mut app.plan_state);
+        } else {
+            frame.render_widget(list, *area);
+        }
+    }
+
+    let location =
+        app.list_state
+            .selected()
+            .and_then(|selected| match app.location_rows.get(selected)? {
+                LocationRow::Location { name, .. } => Some(name.as_str()),
+                LocationRow::Venue(_) => None,
+            });
+    let help = match location {
+        Some(location) => {
+            format!("Green is free · ←/→ day · ↑/↓ time · Enter book it at {location} · Esc back")
+        }
+        None => "Green is free · ←/→ day · ↑/↓ time · Esc back".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(help).fg(Color::DarkGray)).alignment(Alignment::Center),
+        help_area,
+    );
+}
+
+/// My upcoming bookings, one a line: day, times, space and location, and any title.
+fn render_my_bookings(app: &mut App, frame: &mut Frame, area: Rect) {
+    let block = Block::bordered()
+        .title("My bookings")
+        .title_top(data_age(app))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [list_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    let now = clock::now();
+    let items: Vec<Line> = app
+        .upcoming_bookings()
+        .into_iter()
+        .map(|(venue, booking)| {
+            let mut spans = vec![
+                Span::from(format!(
+                    "{} {:>2} {}  {}–{}  ",
+                    &booking.start.weekday().to_string()[..3],
+                    booking.start.day(),
+                    &booking.start.month().to_string()[..3],
+                    grid::format_time(booking.start.time()),
+                    grid::format_time(booking.end.time()),
+                )),
+                Span::from(app.booking_place(venue, booking)),
+            ];
+            if let Some(title) = &booking.title {
+                spans.push(Span::from(format!("  {title}")).fg(Color::DarkGray));
+            }
+            if booking.start <= now {
+                spans.push(Span::from("  now").fg(Color::Green));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    if items.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No upcoming bookings")
+                .fg(Color::DarkGray)
+                .alignment(Alignment::Center),
+            list_area,
+        );
+    } else {
+        let list = List::new(items)
+            .highlight_style(Color::Yellow)
+            .highlight_symbol(">> ");
+        app.list_page = usize::from(list_area.height);
+        frame.render_stateful_widget(list, list_area, &mut app.my_bookings_state);
+    }
+    frame.render_widget(
+        Paragraph::new(
+            Line::from("↑/↓ move · Enter open its location · c cancel it · r refresh · Esc back")
+                .fg(Color::DarkGray),
+        )
+        .alignment(Alignment::Center),
+        help_area,
+    );
+    render_cancel_confirmation(app, frame, area);
+}
+
+/// Asks whether to cancel the booking, over the my bookings list.
+fn render_cancel_confirmation(app: &App, frame: &mut Frame, area: Rect) {
+    let Some((venue, booking)) = &app.cancel_pending else {
+        return;
+    };
+    let mut lines = vec![
+        Line::from(app.booking_place(*venue, booking)).bold(),
+        Line::from(format!(
+            "{} {} {}  {}–{}",
+            booking.start.weekday(),
+            booking.start.day(),
+            booking.start.month(),
+            grid::format_time(booking.start.time()),
+            grid::format_time(booking.end.time())
+        )),
+    ];
+    if let Some(title) = &booking.title {
+        lines.push(Line::from(title.as_str()).fg(Color::DarkGray));
+    }
+    lines.push(Line::default());
+    lines.push(Line::from("y cancel it · n keep it").fg(Color::DarkGray));
+
+    let width = 48.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup_area = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::bordered()
+                .title("Cancel this booking?")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .border_style(Color::Red),
+        ),
+        popup_area,
+    );
+}
+
+fn render_month_calendar(app: &mut App, frame: &mut Frame, area: Rect) {
+    const CELL: usize = 8;
+
+    let space = app
+        .highlighted_space()
+        .map(|space| space.name.clone())
+        .unwrap_or_default();
+    let date = app.calendar_date;
+    let block = Block::bordered()
+        .title(format!("{space} · {} {}", date.month(), date.year()))
+        .title_top(data_age(app))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [calendar_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    let days = app.calendar_month();
+    let today = clock::now().date();
+    let mut lines = vec![
+        Line::from(
+            ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+                .map(|day| format!("{day:^CELL$}"))
+                .concat(),
+        )
+        .fg(Color::DarkGray),
+    ];
+    for week in calendar::weeks(date) {
+        let cells = week.map(|day| {
+            let Some(day) = day else {
+                return Span::raw(" ".repeat(CELL));
+            };
+            let marker = days
+                .get(&day)
+                .filter(|calendar_day| calendar_day.bookings > 0)
+                .map(|calendar_day| format!(" ●{}", calendar_day.bookings))
+                .unwrap_or_default();
+            let text = format!("{:^CELL$}", format!("{}{marker}", day.day()));
+            let span = match days.get(&day) {
+                Some(calendar_day) if calendar_day.mine => Span::raw(text).fg(Color::Cyan),
+                Some(_) => Span::raw(text),
+                None => Span::raw(text).fg(Color::DarkGray),
+            };
+            match day {
+                _ if day == date => span.black().on_yellow(),
+                _ if day == today => span.bold().underlined(),
+                _ => span,
+            }
+        });
+        lines.push(Line::default());
+        lines.push(Line::from(cells.to_vec()));
+    }
+    let height = lines.len() as u16;
+    let [calendar_area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(calendar_area);
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        calendar_area,
+    );
+
+    frame.render_widget(
+        Paragraph::new(
+            Line::from(
+                "●N bookings · ←/→ day · ↑/↓ week · PgUp/PgDn month · Enter open · Esc back",
+            )
+            .fg(Color::DarkGray),
+        )
+        .alignment(Alignment::Center),
+        help_area,
+    );
 }
 
-fn render_booking_form(app: &mut App, frame: &mut Frame) {
-    let skedda = Skedda::new();
-    let spaces_list = List::new(
-        skedda.fetch_space_ids(skedda, app.selected_location)
-            .clone()
+/// Draws the highlighted space's week as a slot for each row and a day for each column, with
+/// my bookings in cyan and slots that have passed dimmed.
+fn render_week_calendar(app: &mut App, frame: &mut Frame, area: Rect) {
+    const CELL: usize = 8;
+    const DAY: &[BorrowedFormatItem] =
+        format_description!("[weekday repr:short] [day padding:none]");
+
+    let space = app
+        .highlighted_space()
+        .map(|space| space.name.clone())
+        .unwrap_or_default();
+    let block = Block::bordered()
+        .title(format!("{space} · week"))
+        .title_top(data_age(app))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [calendar_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    let days = calendar::week(app.calendar_date);
+    let slots = app.calendar_week();
+    let now = clock::now();
+    let today = now.date();
+    let cursor = days
+        .iter()
+        .position(|&day| day == app.calendar_date)
+        .unwrap_or(0);
+
+    let header = days.map(|day| {
+        let span = Span::raw(format!(
+            "{:^CELL$}",
+            day.format(DAY).unwrap_or_else(|_| day.to_string())
+        ));
+        if day == today {
+            span.bold().underlined()
+        } else {
+            span.fg(Color::DarkGray)
+        }
+    });
+    let mut lines = vec![Line::from(
+        [Span::raw("      ")]
             .into_iter()
-            .map(|space_id| app.venue_space_ids.get(&space_id).unwrap().to_string()),
-    )
+            .chain(header)
+            .collect::<Vec<_>>(),
+    )];
+    // Keep the cursor's slot in view when the day has more slots than fit.
+    let rows = usize::from(calendar_area.height.saturating_sub(1)).max(1);
+    let first = app
+        .week_slot
+        .saturating_sub(rows / 2)
+        .min(slots.len().saturating_sub(rows));
+    for (slot, row) in slots.iter().enumerate().skip(first).take(rows) {
+        let mut spans = vec![
+            Span::raw(format!(
+                "{} ",
+                grid::format_time(app.grid.slot_start(slot).time())
+            ))
+            .fg(Color::DarkGray),
+        ];
+        for (column, state) in row.iter().enumerate() {
+            let end =
+                days[column].midnight() + (app.grid.slot_end(slot) - app.grid.date.midnight());
+            let span = match state {
+                calendar::Slot::Free => Span::raw(format!("{:^CELL$}", "·")),
+                calendar::Slot::Booked => Span::raw(format!(" {} ", "█".repeat(CELL - 2))),
+                calendar::Slot::Mine => {
+                    Span::raw(format!(" {} ", "█".repeat(CELL - 2))).fg(Color::Cyan)
+                }
+            };
+            spans.push(match span {
+                _ if slot == app.week_slot && column == cursor => span.black().on_yellow(),
+                _ if end <= now => span.fg(Color::DarkGray),
+                _ => span,
+            });
+        }
+        lines.push(Line::from(spans));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        calendar_area,
+    );
+
+    frame.render_widget(
+        Paragraph::new(
+            Line::from(
+                "█ booked · ←/→ day · ↑/↓ slot · PgUp/PgDn week · Enter book here · Esc back",
+            )
+            .fg(Color::DarkGray),
+        )
+        .alignment(Alignment::Center),
+        help_area,
+    );
+}
+
+fn render_venue_picker(app: &mut App, frame: &mut Frame, area: Rect) {
+    let popup_area = centered_rect(50, 50, area);
+    frame.render_widget(Clear, popup_area);
+
+    let items = std::iter::once("All venues".to_string())
+        .chain(app.venues.iter().map(|venue| venue.name.clone()))
+        .enumerate()
+        .map(|(row, name)| {
+            let active = app.active_venue.map_or(0, |venue| venue + 1) == row;
+            if active { format!("{name} •") } else { name }
+        });
+    let venues_list = List::new(items)
+        .block(
+            Block::bordered()
+                .title("Switch venue")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded),
+        )
+        .highlight_style(Color::Yellow)
+        .highlight_symbol(">> ");
+
+    app.list_page = usize::from(popup_area.height.saturating_sub(2));
+    frame.render_stateful_widget(venues_list, popup_area, &mut app.venue_list_state);
+}
+
+fn render_booking_form(app: &mut App, frame: &mut Frame, area: Rect) {
+    let rows = app.space_rows();
+    let grouped = rows.iter().any(|row| matches!(row, SpaceRow::Group { .. }));
+    let visible_spaces = rows
+        .iter()
+        .filter(|row| matches!(row, SpaceRow::Space(_)))
+        .count();
+    let marking = !app.marked_spaces.is_empty();
+    let spaces_list = List::new(rows.into_iter().map(|row| {
+        match row {
+            SpaceRow::Group {
+                kind,
+                spaces,
+                collapsed,
+            } => Line::from(format!(
+                "{} {kind} ({spaces})",
+                if collapsed { "▸" } else { "▾" }
+            ))
+            .bold(),
+            SpaceRow::Space(index) => {
+                let space_id = &app.selected_location_space_ids[index];
+                let mark = match (marking, app.marked_spaces.contains(space_id)) {
+                    (false, _) => "",
+                    (true, true) => "[x] ",
+                    (true, false) => "[ ] ",
+                };
+                Line::from(format!(
+                    "{}{mark}{}",
+                    if grouped { "  " } else { "" },
+                    app.venue_spaces[space_id].name
+                ))
+            }
+        }
+    }))
     .block(
         Block::default()
-            .title("Spaces")
+            .title(if app.loading_spaces {
+                format!("Spaces {}", app.scheduler.spinner())
+            } else {
+                "Spaces".to_string()
+            })
             .title_alignment(Alignment::Center)
             .border_type(BorderType::Rounded),
     )
     .highlight_style(Color::Yellow)
     .highlight_symbol(">> ");
 
-    let area = frame.area();
-
     // Create a centered popup area
     let popup_area = centered_rect(80, 60, area);
 
@@ -76,30 +961,520 @@ fn render_booking_form(app: &mut App, frame: &mut Frame) {
             .as_ref()
             .unwrap_or(&"Unknown".to_string())
     );
+    let title = match &app.booking_for {
+        Some(member) => format!("{title} (for {member})"),
+        None => title,
+    };
 
-    let block = Block::bordered()
+    let mut block = Block::bordered()
         .title(title)
+        .title_top(data_age(app))
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Rounded);
+    if let Some(opening) = &app.opening {
+        block = block.title_bottom(Line::from(opening.describe(clock::now())).fg(Color::Cyan));
+    }
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
 
-    let content = vec![
-        Line::from(""),
-        Line::from("Booking details will go here..."),
-        Line::from(""),
-        Line::from("Press Enter to confirm booking"),
-        Line::from("Press Esc to go back"),
+    let grid_height = visible_spaces.min(8) as u16 + 3;
+    let [body_area, fields_area, grid_area, help_area] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(1),
+        Constraint::Length(grid_height),
+        Constraint::Length(2),
+    ])
+    .areas(inner);
+    let [list_area, detail_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .areas(body_area);
+
+    let content = match (&app.prompt, &app.notice) {
+        (Some((Prompt::StartTime, input)), _) => vec![
+            Line::from(format!("Start time: {input}▏")),
+            Line::from("Enter to jump there, Esc to cancel").fg(Color::DarkGray),
+        ],
+        (Some((Prompt::BookFor, input)), _) => vec![
+            Line::from(format!("Book for member: {input}▏")),
+            Line::from("Enter to confirm, empty to book for yourself, Esc to cancel")
+                .fg(Color::DarkGray),
+        ],
+        (_, notice) => vec![
+            match (&app.outage, notice) {
+                (Some((message, retry_at)), _) => Line::from(format!(
+                    "{message} · retrying in {}s",
+                    retry_at
+                        .saturating_duration_since(Instant::now())
+                        .as_secs()
+                        .max(1)
+                ))
+                .fg(Color::Red),
+                (None, Some(notice)) => Line::from(notice.as_str()).fg(Color::Yellow),
+                (None, None) if app.highlighted_group.is_some() => {
+                    Line::from("Enter or Space to fold or unfold the group")
+                }
+                (None, None) if app.form_field.is_some() => {
+                    Line::from("←/→ change · Tab next field · Esc done")
+                }
+                (None, None) => match (app.rule_violation(), app.batch_windows().len()) {
+                    (Some(reason), _) => Line::from(reason).fg(Color::Red),
+                    (None, 0 | 1) => Line::from(
+                        "←/→ time · Shift+←/→ extend · T type a time · Tab fields · Enter book",
+                    ),
+                    (None, bookings) => Line::from(format!(
+                        "{bookings} bookings: {} × {} · x mark spaces · +/- days · Enter book",
+                        match bookings / usize::from(app.repeat_days) {
+                            1 => "1 space".to_string(),
+                            spaces => format!("{spaces} spaces"),
+                        },
+                        match app.repeat_days {
+                            1 => "1 day".to_string(),
+                            days => format!("{days} days"),
+                        },
+                    )),
+                },
+            },
+            Line::from(if app.is_admin() {
+                "x also book · +/- days · M month · W week · F book for a member · B all bookings · Esc to go back"
+            } else {
+                "x also book · +/- days · M month · W week · Esc to go back"
+            }),
+        ],
+    };
+
+    let paragraph = Paragraph::new(Text::from(content)).alignment(Alignment::Center);
+
+    frame.render_widget(paragraph, help_area);
+    app.list_page = usize::from(list_area.height.saturating_sub(1));
+    app.space_rows_state.select(app.space_row());
+    frame.render_stateful_widget(spaces_list, list_area, &mut app.space_rows_state);
+    render_space_detail(app, frame, detail_area);
+    render_form_fields(app, frame, fields_area);
+    render_availability(app, frame, grid_area);
+}
+
+/// Draws the selected window as date, start and duration fields, the focused one marked,
+/// the name of the configured window it matches, and the title being typed with its cursor.
+fn render_form_fields(app: &App, frame: &mut Frame, area: Rect) {
+    const DATE: &[BorrowedFormatItem] =
+        format_description!("[weekday repr:short] [day padding:none] [month repr:short]");
+    let Some((_, start, end)) = app.selected_window() else {
+        return;
+    };
+    let fields = [
+        (
+            FormField::Date,
+            "Date",
+            start
+                .date()
+                .format(DATE)
+                .unwrap_or_else(|_| start.date().to_string()),
+        ),
+        (FormField::Start, "Start", grid::format_time(start.time())),
+        (
+            FormField::Duration,
+            "Duration",
+            rules::format_length(end - start),
+        ),
     ];
+    let mut spans = Vec::new();
+    for (field, label, value) in fields {
+        if !spans.is_empty() {
+            spans.push(Span::from(" · ").fg(Color::DarkGray));
+        }
+        spans.push(Span::from(format!("{label} ")).fg(Color::DarkGray));
+        let value = Span::from(format!(" {value} "));
+        spans.push(if app.form_field == Some(field) {
+            value.black().on_yellow()
+        } else {
+            value.bold()
+        });
+    }
+    if let Some(name) = app.window_name(start, end) {
+        spans.push(Span::from(format!(" · {name}")).fg(Color::Cyan));
+    }
+    spans.push(Span::from(" · ").fg(Color::DarkGray));
+    spans.push(Span::from("Title ").fg(Color::DarkGray));
+    if app.form_field == Some(FormField::Title) {
+        let (before, under, after) = app.booking_title.split_at_cursor();
+        spans.extend([
+            Span::from(format!(" {before}")).black().on_yellow(),
+            Span::from(under.map_or_else(|| " ".to_string(), String::from))
+                .black()
+                .on_yellow()
+                .reversed(),
+            Span::from(format!("{after} ")).black().on_yellow(),
+        ]);
+    } else if app.booking_title.text().trim().is_empty() {
+        spans.push(Span::from(" none ").fg(Color::DarkGray));
+    } else {
+        spans.push(Span::from(format!(" {} ", app.booking_title.text())).bold());
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(spans)).alignment(Alignment::Center),
+        area,
+    );
+}
 
-    let paragraph = Paragraph::new(Text::from(content))
-        .block(block)
-        .alignment(Alignment::Center);
+/// Draws the day's availability, one row per space and one cell per slot, with the cursor on
+/// the highlighted space and slot.
+fn render_availability(app: &App, frame: &mut Frame, area: Rect) {
+    const NAME_WIDTH: usize = 14;
 
-    frame.render_widget(paragraph, popup_area);
-    frame.render_stateful_widget(spaces_list, popup_area, &mut app.list_state);
+    let grid = &app.grid;
+    let day = if grid.date == clock::now().date() {
+        String::new()
+    } else {
+        format!(" {} {}", grid.date.weekday(), grid.date)
+    };
+    let block = Block::bordered()
+        .title(format!("Availability{day} ({} min)", grid.slot_minutes))
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Scroll the slots horizontally to keep the cursor in view.
+    let visible = (inner.width as usize).saturating_sub(NAME_WIDTH).max(1);
+    let first = (app.grid_slot + 1).saturating_sub(visible);
+    let slots = first..grid.slots.min(first + visible);
+
+    // Label the hours, leaving out any that would run into the previous label.
+    let mut header = " ".repeat(NAME_WIDTH);
+    for slot in slots.clone() {
+        let column = NAME_WIDTH + slot - first;
+        if header.len() > column {
+            continue;
+        }
+        let start = grid.slot_start(slot);
+        if start.minute() == 0 && (column == NAME_WIDTH || header.ends_with("  ")) {
+            header.push_str(&start.hour().to_string());
+        } else {
+            header.push(' ');
+        }
+    }
+    let mut lines = vec![Line::from(header).fg(Color::DarkGray)];
+
+    let now = clock::now();
+    let highlighted = app.space_list_state.selected();
+    let selected = app.selected_slots();
+    // The spaces in view in the space list, leaving out collapsed groups.
+    let visible: Vec<usize> = app
+        .space_rows()
+        .into_iter()
+        .filter_map(|row| match row {
+            SpaceRow::Space(index) => Some(index),
+            SpaceRow::Group { .. } => None,
+        })
+        .collect();
+    let first_row = highlighted
+        .and_then(|highlighted| visible.iter().position(|&row| row == highlighted))
+        .map_or(0, |row| (row + 1).saturating_sub(8));
+    for (row, space_id) in visible
+        .iter()
+        .skip(first_row)
+        .take(8)
+        .map(|&row| (row, &app.selected_location_space_ids[row]))
+    {
+        let space = app.venue_spaces.get(space_id);
+        let name = space.map_or(space_id.as_str(), |space| space.name.as_str());
+        let name: String = name.chars().take(NAME_WIDTH - 1).collect();
+        let mut spans = vec![Span::from(format!("{name:<NAME_WIDTH$}"))];
+        for slot in slots.clone() {
+            let booked = grid.is_booked(&app.bookings, space_id, slot);
+            let past = grid.slot_end(slot) <= now;
+            let closed = space.is_some_and(|space| {
+                !space
+                    .hours
+                    .is_open(grid.slot_start(slot), grid.slot_end(slot))
+            });
+            spans.push(if highlighted == Some(row) && selected.contains(&slot) {
+                // Selected slots that can't be booked show through the selection.
+                if booked || past || closed {
+                    Span::from("▒").fg(Color::Yellow).bg(if booked {
+                        Color::Red
+                    } else {
+                        Color::DarkGray
+                    })
+                } else {
+                    Span::from("█").fg(Color::Yellow)
+                }
+            } else if booked {
+                Span::from("█").fg(Color::Red)
+            } else if past {
+                Span::from("█").fg(Color::DarkGray)
+            } else if closed {
+                Span::from("░").fg(Color::DarkGray)
+            } else {
+                Span::from("█").fg(Color::Green)
+            });
+        }
+        lines.push(Line::from(spans));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
 }
 
-fn render_confirmation(app: &mut App, frame: &mut Frame) {
-    let area = frame.area();
+/// Shows the highlighted space's photo and description.
+fn render_space_detail(app: &App, frame: &mut Frame, area: Rect) {
+    let Some(space) = app.highlighted_space() else {
+        return;
+    };
+    let block = Block::bordered()
+        .title(space.name.as_str())
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let image = space
+        .image_url
+        .as_ref()
+        .and_then(|url| app.space_images.get(url));
+    let image_height = if image.is_some() { inner.height / 2 } else { 0 };
+    let hours = space
+        .hours
+        .on(app.grid.date)
+        .map(|hours| format!("{} {}", app.grid.date.weekday(), hours.describe()));
+    let rules = match (space.rules.summary(), hours) {
+        (Some(rules), Some(hours)) => Some(format!("{rules} · {hours}")),
+        (rules, hours) => rules.or(hours),
+    };
+    let [image_area, rules_area, description_area] = Layout::vertical([
+        Constraint::Length(image_height),
+        Constraint::Length(u16::from(rules.is_some())),
+        Constraint::Min(0),
+    ])
+    .areas(inner);
+
+    match image {
+        Some(Some(image)) => frame.render_widget(Thumbnail(image), image_area),
+        Some(None) => frame.render_widget(
+            Paragraph::new("Loading photo…")
+                .fg(Color::DarkGray)
+                .alignment(Alignment::Center),
+            image_area,
+        ),
+        None => {}
+    }
+
+    if let Some(rules) = rules {
+        frame.render_widget(Paragraph::new(rules).fg(Color::DarkGray), rules_area);
+    }
+
+    match space.description.as_deref() {
+        Some(description) => {
+            let (text, links) = markup::html_to_text(description);
+            frame.render_widget(
+                Paragraph::new(text).wrap(Wrap { trim: true }),
+                description_area,
+            );
+            markup::hyperlink(frame.buffer_mut(), description_area, &links);
+        }
+        None => frame.render_widget(
+            Paragraph::new("No description").fg(Color::DarkGray),
+            description_area,
+        ),
+    }
+}
+
+/// Draws an image with half-block characters, two pixels per cell, which works in any
+/// true-colour terminal and composes with the rest of the buffer.
+struct Thumbnail<'a>(&'a DynamicImage);
+
+impl Widget for Thumbnail<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let image = self
+            .0
+            .resize(
+                u32::from(area.width),
+                u32::from(area.height) * 2,
+                FilterType::Triangle,
+            )
+            .to_rgb8();
+        let x_offset = (area.width - image.width() as u16) / 2;
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let cell = &mut buf[(area.x + x_offset + x as u16, area.y + (y / 2) as u16)];
+            let color = Color::Rgb(pixel[0], pixel[1], pixel[2]);
+            cell.set_symbol("▀");
+            if y % 2 == 0 {
+                cell.set_fg(color);
+            } else {
+                cell.set_bg(color);
+            }
+        }
+    }
+}
+
+/// Lists everyone's bookings of the highlighted space, for admins resolving conflicts.
+fn render_space_bookings(app: &mut App, frame: &mut Frame, area: Rect) {
+    let popup_area = centered_rect(80, 60, area);
+    frame.render_widget(Clear, popup_area);
+
+    let space = app
+        .highlighted_space()
+        .map_or("Unknown", |space| space.name.as_str());
+    let period = if app.space_bookings_week {
+        "this week"
+    } else {
+        "today"
+    };
+    let block = Block::bordered()
+        .title(format!("Bookings - {space} ({period})"))
+        .title_top(data_age(app))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [list_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    let bookings = app.space_bookings();
+    if bookings.is_empty() {
+        frame.render_widget(
+            Paragraph::new(format!("No bookings {period}"))
+                .fg(Color::DarkGray)
+                .alignment(Alignment::Center),
+            list_area,
+        );
+    } else {
+        let items: Vec<_> = bookings
+            .iter()
+            .map(|booking| {
+                let id = booking.id.as_deref().unwrap_or_default();
+                let mark = if app.marked_bookings.contains(id) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                let mut spans = vec![
+                    Span::from(mark),
+                    Span::from(format!(
+                        "{} {:>2} {}  {}–{}  ",
+                        &booking.start.weekday().to_string()[..3],
+                        booking.start.day(),
+                        &booking.start.month().to_string()[..3],
+                        grid::format_time(booking.start.time()),
+                        grid::format_time(booking.end.time()),
+                    )),
+                    match &booking.holder {
+                        Some(holder) => Span::from(holder.clone()),
+                        None => Span::from("unknown member").fg(Color::DarkGray),
+                    },
+                ];
+                match app.cancellations.get(id) {
+                    Some(Ok(())) => spans.push(Span::from("  cancelled").fg(Color::Green)),
+                    Some(Err(err)) => spans.push(Span::from(format!("  {err}")).fg(Color::Red)),
+                    None => {}
+                }
+                Line::from(spans)
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Color::Yellow)
+            .highlight_symbol(">> ");
+        app.list_page = usize::from(list_area.height);
+        frame.render_stateful_widget(list, list_area, &mut app.space_bookings_state);
+    }
+    let help = match (&app.prompt, &app.notice) {
+        (Some((Prompt::CancelMessage, input)), _) => {
+            Line::from(format!("Message to members (optional): {input}▏"))
+        }
+        (_, Some(notice)) => Line::from(notice.as_str()).fg(Color::Yellow),
+        _ => Line::from("Space mark · a mark all · C cancel marked · w day/week · Esc back"),
+    };
+    frame.render_widget(Paragraph::new(help).alignment(Alignment::Center), help_area);
+}
+
+fn render_batch_results(app: &mut App, frame: &mut Frame, area: Rect) {
+    let popup_area = centered_rect(80, 60, area);
+    frame.render_widget(Clear, popup_area);
+    let Some(batch) = &app.batch else {
+        return;
+    };
+
+    let tally = batch.tally();
+    let mut counts = vec![format!("{} made", tally.booked)];
+    for (count, label) in [
+        (tally.failed, "failed"),
+        (tally.skipped, "skipped"),
+        (tally.rolled_back, "rolled back"),
+        (tally.pending, "sending"),
+    ] {
+        if count > 0 {
+            counts.push(format!("{count} {label}"));
+        }
+    }
+    let block = Block::bordered()
+        .title(format!("Bookings · {}", counts.join(" · ")))
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [list_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    let width = batch
+        .items
+        .iter()
+        .map(|item| item.space.chars().count())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<_> = batch
+        .items
+        .iter()
+        .map(|item| {
+            let (color, status, detail) = match &item.state {
+                ItemState::Pending => (Color::Yellow, "sending", String::new()),
+                ItemState::Booked(created) => (Color::Green, "booked", format!("#{}", created.id)),
+                ItemState::Failed(err) => (Color::Red, "failed", err.clone()),
+                ItemState::Skipped(reason) => (Color::DarkGray, "skipped", reason.clone()),
+                ItemState::RolledBack => (Color::DarkGray, "rolled back", String::new()),
+            };
+            let request = &item.request;
+            let mut spans = vec![
+                Span::from(format!("{status:<12}")).fg(color),
+                Span::from(format!(
+                    "{:<width$}  {} {:>2} {}  {}–{}  ",
+                    item.space,
+                    &request.start.weekday().to_string()[..3],
+                    request.start.day(),
+                    &request.start.month().to_string()[..3],
+                    grid::format_time(request.start.time()),
+                    grid::format_time(request.end.time()),
+                )),
+                Span::from(detail).fg(color),
+            ];
+            if let Some(err) = &item.rollback_error {
+                spans.push(Span::from(format!("  not cancelled: {err}")).fg(Color::Red));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Color::Yellow)
+        .highlight_symbol(">> ");
+    app.list_page = usize::from(list_area.height);
+    frame.render_stateful_widget(list, list_area, &mut app.batch_state);
+
+    let help = match &app.notice {
+        Some(notice) => Line::from(notice.as_str()).fg(Color::Yellow),
+        None => {
+            let mut keys = Vec::new();
+            if tally.failed > 0 {
+                keys.push("r retry failed");
+            }
+            if tally.booked > 0 {
+                keys.push("u roll back");
+            }
+            keys.push("Enter keep");
+            Line::from(keys.join(" · "))
+        }
+    };
+    frame.render_widget(Paragraph::new(help).alignment(Alignment::Center), help_area);
+}
+
+fn render_confirmation(app: &mut App, frame: &mut Frame, area: Rect) {
     let popup_area = centered_rect(50, 30, area);
 
     frame.render_widget(Clear, popup_area);
@@ -109,7 +1484,7 @@ fn render_confirmation(app: &mut App, frame: &mut Frame) {
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Rounded);
 
-    let content = vec![
+    let mut content = vec![
         Line::from(""),
         Line::from(format!(
             "Your booking at {} has been confirmed!",
@@ -117,9 +1492,32 @@ fn render_confirmation(app: &mut App, frame: &mut Frame) {
                 .as_ref()
                 .unwrap_or(&"Unknown".to_string())
         )),
-        Line::from(""),
-        Line::from("Press Esc to return to location selection"),
     ];
+    if let Some((space_id, start, end)) = app.selected_window() {
+        let space = app
+            .venue_spaces
+            .get(space_id)
+            .map_or(space_id, |space| space.name.as_str());
+        content.push(Line::from(format!(
+            "{space}, {}–{}",
+            grid::format_time(start.time()),
+            grid::format_time(end.time())
+        )));
+    }
+    if let Some(member) = &app.booking_for {
+        content.push(Line::from(format!("Booked for {member}")));
+    }
+    if let Some(created) = &app.created_booking {
+        content.push(Line::from(format!("Booking #{}", created.id)).fg(Color::DarkGray));
+    }
+    content.push(Line::from(""));
+    if let Some(notice) = &app.notice {
+        content.push(Line::from(notice.as_str()).fg(Color::Yellow));
+    }
+    content.extend([
+        Line::from("e save a receipt · p save it as a PDF").fg(Color::DarkGray),
+        Line::from("Press Esc to return to location selection"),
+    ]);
 
     let paragraph = Paragraph::new(Text::from(content))
         .block(block)
@@ -128,6 +1526,59 @@ fn render_confirmation(app: &mut App, frame: &mut Frame) {
     frame.render_widget(paragraph, popup_area);
 }
 
+/// "updated 12s ago" for the right of a view's title, or nothing while its data loads.
+fn data_age(app: &App) -> Line<'static> {
+    let Some(age) = app.data_age() else {
+        return Line::default();
+    };
+    if let Some(fetched_at) = app.offline_since() {
+        return Line::from(offline_label(fetched_at))
+            .fg(Color::Red)
+            .right_aligned();
+    }
+    let secs = age.as_secs();
+    let age = match secs {
+        0..5 => "updated just now".to_string(),
+        5..60 => format!("updated {secs}s ago"),
+        60..3600 => format!("updated {}m ago", secs / 60),
+        _ => format!("updated {}h ago", secs / 3600),
+    };
+    Line::from(age).fg(Color::DarkGray).right_aligned()
+}
+
+/// "offline (data from 09:12)", with the day when the data is from an earlier one.
+fn offline_label(fetched_at: SystemTime) -> String {
+    const DAY: &[BorrowedFormatItem] =
+        format_description!("[weekday repr:short] [day padding:none] [month repr:short] ");
+    let at = clock::local(fetched_at);
+    let day = if at.date() == clock::now().date() {
+        String::new()
+    } else {
+        at.date().format(DAY).unwrap_or_default()
+    };
+    format!("offline (data from {day}{})", grid::format_time(at.time()))
+}
+
+/// How many lines `text` takes wrapped at word boundaries to `width` columns.
+fn wrapped_lines(text: &str, width: usize) -> u16 {
+    let width = width.max(1);
+    let mut lines = 1;
+    let mut used = 0;
+    for word in text.split(' ') {
+        let len = word.chars().count();
+        if used > 0 && used + 1 + len > width {
+            lines += 1;
+            used = 0;
+        }
+        used = if used == 0 { len } else { used + 1 + len };
+        while used > width {
+            lines += 1;
+            used -= width;
+        }
+    }
+    lines
+}
+
 /// Helper function to create a centered rectangle
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()